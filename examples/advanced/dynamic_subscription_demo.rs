@@ -24,18 +24,17 @@ pub async fn main() -> Result<(), String> {
   }
 
   // Create configuration optimized for dynamic operations
-  let config = KiteManagerConfig {
-    max_symbols_per_connection: 3000,
-    max_connections: 3,
-    connection_buffer_size: 5000,
-    parser_buffer_size: 10000,
-    connection_timeout: Duration::from_secs(30),
-    health_check_interval: Duration::from_secs(5),
-    max_reconnect_attempts: 5,
-    reconnect_delay: Duration::from_secs(2),
-    enable_dedicated_parsers: true,
-    default_mode: Mode::LTP, // Start with LTP for efficiency
-  };
+  let mut config = KiteManagerConfig::default();
+  config.max_symbols_per_connection = 3000;
+  config.max_connections = 3;
+  config.connection_buffer_size = 5000;
+  config.parser_buffer_size = 10000;
+  config.connection_timeout = Duration::from_secs(30);
+  config.health_check_interval = Duration::from_secs(5);
+  config.max_reconnect_attempts = 5;
+  config.reconnect_delay = Duration::from_secs(2);
+  config.enable_dedicated_parsers = true;
+  config.default_mode = Mode::LTP; // Start with LTP for efficiency
 
   println!("🔧 Configuration for Dynamic Operations:");
   println!("   Max connections: {}", config.max_connections);
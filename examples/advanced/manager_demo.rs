@@ -25,19 +25,18 @@ pub async fn main() -> Result<(), String> {
   }
 
   // Create high-performance configuration - RESTORED TO 3 CONNECTIONS
-  let config = KiteManagerConfig {
-    max_symbols_per_connection: 3000,
-    max_connections: 3,            // BACK TO 3 CONNECTIONS!
-    connection_buffer_size: 10000, // High buffer for performance
-    parser_buffer_size: 20000,     // Even higher for parsed messages
-    connection_timeout: Duration::from_secs(30),
-    health_check_interval: Duration::from_secs(5),
-    max_reconnect_attempts: 5,
-    reconnect_delay: Duration::from_secs(2),
-    enable_dedicated_parsers: true, // Use dedicated parser tasks
-    default_mode: Mode::Full,       // Full mode for maximum data
-    heartbeat_liveness_threshold: Duration::from_secs(10),
-  };
+  let mut config = KiteManagerConfig::default();
+  config.max_symbols_per_connection = 3000;
+  config.max_connections = 3; // BACK TO 3 CONNECTIONS!
+  config.connection_buffer_size = 10000; // High buffer for performance
+  config.parser_buffer_size = 20000; // Even higher for parsed messages
+  config.connection_timeout = Duration::from_secs(30);
+  config.health_check_interval = Duration::from_secs(5);
+  config.max_reconnect_attempts = 5;
+  config.reconnect_delay = Duration::from_secs(2);
+  config.enable_dedicated_parsers = true; // Use dedicated parser tasks
+  config.default_mode = Mode::Full; // Full mode for maximum data
+  config.heartbeat_liveness_threshold = Duration::from_secs(10);
 
   println!("🔧 Configuration:");
   println!("   Max connections: {}", config.max_connections);
@@ -65,7 +64,7 @@ pub async fn main() -> Result<(), String> {
     }
     Ok(Err(e)) => {
       println!("❌ Manager failed to start: {}", e);
-      return Err(e);
+      return Err(e.into());
     }
     Err(_) => {
       println!("⏱️  Manager startup timeout");
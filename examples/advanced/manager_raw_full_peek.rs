@@ -1,6 +1,6 @@
 use kiteticker_async_manager::{
-  as_index_quote_32, as_inst_header_64, as_tick_raw, ChannelId,
-  KiteTickerManagerBuilder, Mode, INDEX_QUOTE_SIZE, INST_HEADER_SIZE,
+  as_index_quote_32, as_inst_header_64, as_tick_raw, KiteTickerManagerBuilder,
+  Mode, INDEX_QUOTE_SIZE, INST_HEADER_SIZE,
 };
 use std::time::{Duration, Instant};
 use tokio::time::{sleep, timeout};
@@ -46,7 +46,7 @@ async fn main() -> Result<(), String> {
     }
     Ok(Err(e)) => {
       println!("❌ Manager failed to start: {}", e);
-      return Err(e);
+      return Err(e.into());
     }
     Err(_) => {
       println!("⏱️  Manager startup timeout");
@@ -180,7 +180,9 @@ async fn main() -> Result<(), String> {
             }
 
             // Lightweight periodic stats every ~10s
-            if started.elapsed().as_secs() % 10 == 0 && frames_seen % 50 == 0 {
+            if started.elapsed().as_secs().is_multiple_of(10)
+              && frames_seen.is_multiple_of(50)
+            {
               let secs = started.elapsed().as_secs_f64();
               println!(
                 "[{id:?}] 📊 frames={} ({:.1}/s) packets={} full={} index={} inst={}",
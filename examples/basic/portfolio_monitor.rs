@@ -47,15 +47,13 @@ async fn main() -> Result<(), String> {
   println!();
 
   // Create manager with optimized configuration for portfolio monitoring
-  let config = KiteManagerConfig {
-    max_connections: 1, // Single connection for small portfolio
-    max_symbols_per_connection: 100,
-    connection_buffer_size: 2000,
-    parser_buffer_size: 5000,
-    enable_dedicated_parsers: true,
-    default_mode: Mode::Quote, // Quote mode for price + volume
-    ..Default::default()
-  };
+  let mut config = KiteManagerConfig::default();
+  config.max_connections = 1; // Single connection for small portfolio
+  config.max_symbols_per_connection = 100;
+  config.connection_buffer_size = 2000;
+  config.parser_buffer_size = 5000;
+  config.enable_dedicated_parsers = true;
+  config.default_mode = Mode::Quote; // Quote mode for price + volume
 
   // Start manager
   let mut manager = KiteTickerManager::new(api_key, access_token, config);
@@ -86,6 +86,15 @@ async fn main() -> Result<(), String> {
           TickerMessage::Error(error) => {
             println!("❌ Error: {}", error);
           }
+          TickerMessage::NoData(_) => {
+            // Only emitted by the manager's receive timeout
+          }
+          TickerMessage::UnknownPacket { size, .. } => {
+            println!("❓ Unknown packet size: {} bytes", size);
+          }
+          TickerMessage::Throttled(notice) => {
+            println!("⏳ Throttled by Kite: {}", notice);
+          }
           TickerMessage::OrderPostback(order_result) => match order_result {
             Ok(order) => println!("📋 Order update: {:?}", order),
             Err(err) => println!("❌ Order error: {}", err),
@@ -93,9 +102,13 @@ async fn main() -> Result<(), String> {
           TickerMessage::Raw(_) => {
             // Raw frames are not used in this example
           }
+          TickerMessage::Snapshot(_) => {
+            // Only the manager's last-value cache emits snapshots
+          }
           TickerMessage::ClosingMessage(close_msg) => {
             println!("🔌 Connection closing: {}", close_msg);
           }
+          _ => {}
         }
       }
       Ok(None) => {
@@ -0,0 +1,142 @@
+//! Allocation comparison: broadcast delivery vs borrowed `on_ticks` delivery.
+//!
+//! Streams synthetic frames of LTP packets from a local WebSocket server to a
+//! `KiteTickerAsync` and counts heap allocations per frame, once reading
+//! `TickerMessage::Ticks` from a subscriber and once through a
+//! `KiteTickerConfig::on_ticks` callback. The server runs in the same process,
+//! so its share is counted too; it is identical in both runs, so the
+//! difference is the cost of the broadcast path. No network access is needed.
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use kiteticker_async_manager::{
+  Environment, KiteTickerAsync, KiteTickerConfig, TickHandler, TickerMessage,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::{oneshot, Notify};
+use tokio_tungstenite::tungstenite::Message;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    System.alloc(layout)
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    System.dealloc(ptr, layout)
+  }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const PACKETS_PER_FRAME: u16 = 16;
+const FRAMES: usize = 20_000;
+// Frames sent between pauses, so the reader's parse queue never overflows
+const BATCH: usize = 200;
+
+/// Frame of `n` LTP packets (token, last price in paise)
+fn ltp_frame(n: u16) -> Vec<u8> {
+  let mut frame = n.to_be_bytes().to_vec();
+  for i in 0..n as u32 {
+    frame.extend_from_slice(&8u16.to_be_bytes());
+    frame.extend_from_slice(&(256_265 + i).to_be_bytes());
+    frame.extend_from_slice(&(2_450_000 + i as i32).to_be_bytes());
+  }
+  frame
+}
+
+/// Accept one client and send it `FRAMES` copies of the frame once `go` fires
+async fn serve(listener: TcpListener, go: oneshot::Receiver<()>) {
+  let (stream, _) = listener.accept().await.unwrap();
+  let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+  let frame = Bytes::from(ltp_frame(PACKETS_PER_FRAME));
+  go.await.unwrap();
+  for _ in 0..FRAMES / BATCH {
+    for _ in 0..BATCH {
+      ws.feed(Message::Binary(frame.clone())).await.unwrap();
+    }
+    ws.flush().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(1)).await;
+  }
+  while let Some(Ok(_)) = ws.next().await {}
+}
+
+async fn run(name: &str, borrowed: bool) {
+  let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let url = format!("ws://{}", listener.local_addr().unwrap());
+  let (go, go_rx) = oneshot::channel();
+  let server = tokio::spawn(serve(listener, go_rx));
+
+  let expected = FRAMES * PACKETS_PER_FRAME as usize;
+  let received = Arc::new(AtomicUsize::new(0));
+  let done = Arc::new(Notify::new());
+  let mut config = KiteTickerConfig::default();
+  config.environment = Environment::Custom(url);
+  if borrowed {
+    let (received, done) = (Arc::clone(&received), Arc::clone(&done));
+    config.on_ticks = Some(TickHandler::new(move |ticks| {
+      let total = received.fetch_add(ticks.len(), Ordering::Relaxed);
+      if total + ticks.len() >= expected {
+        done.notify_one();
+      }
+    }));
+  }
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  let mut subscriber = ticker.subscribe(&[], None).await.unwrap();
+
+  let before = ALLOCATIONS.load(Ordering::Relaxed);
+  let start = Instant::now();
+  go.send(()).unwrap();
+  let wait = Duration::from_secs(30);
+  if borrowed {
+    let _ = tokio::time::timeout(wait, done.notified()).await;
+  } else {
+    let _ = tokio::time::timeout(wait, async {
+      while received.load(Ordering::Relaxed) < expected {
+        match subscriber.next_message().await {
+          Ok(Some(TickerMessage::Ticks(ticks))) => {
+            received.fetch_add(ticks.len(), Ordering::Relaxed);
+          }
+          Ok(Some(_)) => {}
+          _ => break,
+        }
+      }
+    })
+    .await;
+  }
+  let elapsed = start.elapsed();
+  let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+  println!("{}:", name);
+  println!(
+    "  ticks received: {} of {}",
+    received.load(Ordering::Relaxed),
+    expected
+  );
+  println!("  allocations: {}", allocations);
+  println!(
+    "  allocations per frame: {:.2}",
+    allocations as f64 / FRAMES as f64
+  );
+  println!("  total: {:?}", elapsed);
+
+  let _ = ticker.close().await;
+  server.abort();
+}
+
+#[tokio::main]
+async fn main() {
+  println!("{} frames x {} LTP packets\n", FRAMES, PACKETS_PER_FRAME);
+  run("TickerMessage::Ticks (broadcast)", false).await;
+  run("on_ticks (borrowed slice)", true).await;
+}
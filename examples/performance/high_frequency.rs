@@ -25,15 +25,13 @@ async fn main() -> Result<(), String> {
   }
 
   // Ultra-high performance configuration for HFT
-  let config = KiteManagerConfig {
-    max_connections: 3,
-    max_symbols_per_connection: 1000, // Focused symbol set for HFT
-    connection_buffer_size: 100000,   // Maximum possible buffer
-    parser_buffer_size: 200000,       // Ultra-large parser buffer
-    enable_dedicated_parsers: true,
-    default_mode: Mode::Full, // Full market depth data
-    ..Default::default()
-  };
+  let mut config = KiteManagerConfig::default();
+  config.max_connections = 3;
+  config.max_symbols_per_connection = 1000; // Focused symbol set for HFT
+  config.connection_buffer_size = 100000; // Maximum possible buffer
+  config.parser_buffer_size = 200000; // Ultra-large parser buffer
+  config.enable_dedicated_parsers = true;
+  config.default_mode = Mode::Full; // Full market depth data
 
   println!("🚀 Optimized for:");
   println!("   Sub-microsecond latency");
@@ -135,7 +133,7 @@ impl HFTProcessor {
       }
 
       // Record processing latency (sampling every 100th tick for performance)
-      if self.tick_count.load(Ordering::Relaxed) % 100 == 0 {
+      if self.tick_count.load(Ordering::Relaxed).is_multiple_of(100) {
         let latency = process_start.elapsed();
         if let Ok(mut samples) = self.latency_samples.try_lock() {
           samples.push_back(latency);
@@ -23,15 +23,13 @@ async fn main() -> Result<(), String> {
   }
 
   // High-performance configuration for scanning
-  let config = KiteManagerConfig {
-    max_connections: 3,
-    max_symbols_per_connection: 3000,
-    connection_buffer_size: 20000, // Large buffer for high volume
-    parser_buffer_size: 50000,     // Even larger for parsed data
-    enable_dedicated_parsers: true,
-    default_mode: Mode::LTP, // LTP mode for scanning (minimal bandwidth)
-    ..Default::default()
-  };
+  let mut config = KiteManagerConfig::default();
+  config.max_connections = 3;
+  config.max_symbols_per_connection = 3000;
+  config.connection_buffer_size = 20000; // Large buffer for high volume
+  config.parser_buffer_size = 50000; // Even larger for parsed data
+  config.enable_dedicated_parsers = true;
+  config.default_mode = Mode::LTP; // LTP mode for scanning (minimal bandwidth)
 
   let mut manager = KiteTickerManager::new(api_key, access_token, config);
   manager.start().await?;
@@ -149,7 +147,7 @@ impl MarketScanner {
     self.last_update = Instant::now();
 
     // Print progress every 1000 ticks
-    if self.tick_count % 1000 == 0 {
+    if self.tick_count.is_multiple_of(1000) {
       self.print_progress();
     }
   }
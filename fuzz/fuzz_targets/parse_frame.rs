@@ -0,0 +1,12 @@
+// Feeds arbitrary bytes to the binary frame decoders; any panic (including
+// an out-of-bounds slice) or hang is a bug.
+//
+//   cargo +nightly fuzz run parse_frame
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+  kiteticker_async_manager::fuzz_frame(data);
+});
@@ -1,3 +1,5 @@
+use crate::manager::ChannelId;
+use std::collections::HashMap;
 use std::fmt;
 
 #[derive(Debug, Clone)]
@@ -11,3 +13,76 @@ impl fmt::Display for ParseTickError {
 }
 
 impl std::error::Error for ParseTickError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Invalid or conflicting manager configuration
+pub struct ConfigError(pub String);
+
+impl fmt::Display for ConfigError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "invalid configuration: {}", self.0)
+  }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+/// Error of a `KiteTickerManager` operation (start, subscribe, unsubscribe,
+/// change_mode, reconnect, ...)
+///
+/// Match on `Closed`, `Timeout` and `SilentConnections` to react to them;
+/// every other failure
+/// is `Other` with a description. Converts into `String` for callers that
+/// propagate errors as text.
+pub enum KiteError {
+  /// The manager was stopped (or its cancellation token fired); build a new
+  /// one to connect again
+  Closed,
+  /// Subscribed tokens produced no data within the allowed time, by the
+  /// connection each is subscribed on
+  Timeout {
+    unconfirmed: HashMap<ChannelId, Vec<u32>>,
+  },
+  /// Connections that received no frame, not even a heartbeat, within
+  /// the `verify_connections` timeout
+  SilentConnections(Vec<ChannelId>),
+  /// Any other failure: a connection, command or configuration problem
+  Other(String),
+}
+
+impl fmt::Display for KiteError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Closed => write!(f, "manager has been stopped"),
+      Self::Timeout { unconfirmed } => {
+        let mut channels: Vec<_> = unconfirmed.iter().collect();
+        channels.sort_unstable_by_key(|(channel, _)| channel.to_index());
+        write!(f, "timed out waiting for data")?;
+        for (i, (channel, tokens)) in channels.into_iter().enumerate() {
+          let sep = if i == 0 { " from" } else { "," };
+          write!(f, "{} {:?}: {:?}", sep, channel, tokens)?;
+        }
+        Ok(())
+      }
+      Self::SilentConnections(channels) => {
+        write!(f, "no frames received on connections {:?}", channels)
+      }
+      Self::Other(message) => f.write_str(message),
+    }
+  }
+}
+
+impl std::error::Error for KiteError {}
+
+impl From<String> for KiteError {
+  fn from(value: String) -> Self {
+    Self::Other(value)
+  }
+}
+
+impl From<KiteError> for String {
+  fn from(value: KiteError) -> Self {
+    value.to_string()
+  }
+}
@@ -57,13 +57,11 @@
 //!     let access_token = std::env::var("KITE_ACCESS_TOKEN").unwrap();
 //!     
 //!     // Create high-performance manager
-//!     let config = KiteManagerConfig {
-//!         max_connections: 3,
-//!         max_symbols_per_connection: 3000,
-//!         enable_dedicated_parsers: true,
-//!         default_mode: Mode::LTP,
-//!         ..Default::default()
-//!     };
+//!     let mut config = KiteManagerConfig::default();
+//!     config.max_connections = 3;
+//!     config.max_symbols_per_connection = 3000;
+//!     config.enable_dedicated_parsers = true;
+//!     config.default_mode = Mode::LTP;
 //!     
 //!     // Start manager
 //!     let mut manager = KiteTickerManager::new(api_key, access_token, config);
@@ -271,24 +269,40 @@ mod errors;
 pub mod manager;
 mod models;
 mod parser;
-pub use errors::ParseTickError;
+pub use errors::{ConfigError, KiteError, ParseTickError};
 pub use models::tick_raw::{
   as_184 as tick_as_184, as_index_quote_32, as_inst_header_64, as_tick_raw,
   DepthItemRaw, DepthRaw, IndexQuoteRaw32, InstHeaderRaw64, TickHeaderRaw,
   TickRaw, INDEX_QUOTE_SIZE, INST_HEADER_SIZE, TICK_FULL_SIZE,
 };
 pub use models::{
-  Depth, DepthItem, Exchange, Mode, Order, OrderStatus, OrderTransactionType,
-  OrderValidity, Request, TextMessage, Tick, TickMessage, TickerMessage, OHLC,
+  Candle, CandleAggregator, CloseInfo, DedupKey, Depth, DepthItem, Exchange,
+  InstrumentDirectory, InstrumentToken, Mode, Order, OrderStatus,
+  OrderTransactionType, OrderValidity, RateLimitedReceiver, Request,
+  TextMessage, Tick, TickDirection, TickMessage, TickRateLimiter,
+  TickerMessage, OHLC,
 };
 
 pub mod ticker;
 pub use manager::{
-  ApiCredentials, ApiKeyId, ApiKeyStats, ChannelId, DistributionStrategy,
-  HealthSummary, KiteManagerConfig, KiteTickerManager,
-  KiteTickerManagerBuilder, ManagerStats, MultiApiConfig,
-  MultiApiKiteTickerManager, MultiApiKiteTickerManagerBuilder, MultiApiStats,
+  ApiCredentials, ApiKeyId, ApiKeyStats, BreakerState, ChannelId,
+  DashboardConnection, DashboardSnapshot, Diagnostics, DistributionStrategy,
+  DistributionView, FnSink, HealthEvent, HealthSummary, KiteManagerConfig,
+  KiteTickerManager, KiteTickerManagerBuilder, LeastLoadedRouter,
+  LifecycleEvent, LifecycleEventKind, ManagerStats, MarketSession,
+  MultiApiConfig, MultiApiKiteTickerManager, MultiApiKiteTickerManagerBuilder,
+  MultiApiStats, OperationLatency, PruneEvent, ResyncReceiver,
+  RoundRobinRouter, SubscriptionLatencies, SubscriptionOp, SubscriptionOpKind,
+  SubscriptionState, SymbolActivity, SymbolRouter, TickSink, WatchlistGroup,
+  WatchlistManager,
+};
+pub use ticker::{
+  CommandSender, Environment, KiteTickerAsync, KiteTickerConfig,
+  KiteTickerSubscriber, PauseHandle, TickHandler,
 };
-pub use ticker::{KiteTickerAsync, KiteTickerSubscriber};
 // Expose the raw 184-byte subscriber helper
 pub use ticker::KiteTickerRawSubscriber184;
+
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub use parser::fuzz_frame;
@@ -0,0 +1,120 @@
+//! # Reconnect Circuit Breaker
+//!
+//! Stops a manager from hammering the ticker endpoint while it is down.
+//! After `threshold` failed connection attempts within `window` the breaker
+//! opens and rejects attempts for `cooldown`; the next attempt after that is
+//! a single probe (half-open) whose outcome closes or re-opens the breaker.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Current state of the reconnect circuit breaker
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+  /// Connection attempts are allowed
+  #[default]
+  Closed,
+  /// Too many recent failures; attempts are rejected until the cooldown ends
+  Open,
+  /// Cooldown elapsed; one probe attempt is in flight
+  HalfOpen,
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+  threshold: usize,
+  window: Duration,
+  cooldown: Duration,
+  failures: VecDeque<Instant>,
+  opened_at: Option<Instant>,
+  /// Cooldown of the current open period (longer after throttling)
+  open_for: Duration,
+  state: BreakerState,
+}
+
+impl CircuitBreaker {
+  pub(crate) fn new(
+    threshold: usize,
+    window: Duration,
+    cooldown: Duration,
+  ) -> Self {
+    Self {
+      threshold,
+      window,
+      cooldown,
+      failures: VecDeque::new(),
+      opened_at: None,
+      open_for: cooldown,
+      state: BreakerState::Closed,
+    }
+  }
+
+  pub(crate) fn state(&self) -> BreakerState {
+    self.state
+  }
+
+  /// Time left before an open breaker lets a probe through
+  pub(crate) fn remaining_cooldown(&self) -> Duration {
+    self.opened_at.map_or(Duration::ZERO, |at| {
+      self.open_for.saturating_sub(at.elapsed())
+    })
+  }
+
+  /// Whether a connection attempt may proceed now
+  pub(crate) fn allow_attempt(&mut self) -> bool {
+    match self.state {
+      BreakerState::Closed => true,
+      BreakerState::HalfOpen => false,
+      BreakerState::Open => {
+        if self.remaining_cooldown().is_zero() {
+          self.state = BreakerState::HalfOpen;
+          true
+        } else {
+          false
+        }
+      }
+    }
+  }
+
+  pub(crate) fn record_success(&mut self) {
+    self.failures.clear();
+    self.opened_at = None;
+    self.state = BreakerState::Closed;
+  }
+
+  /// Record a failed attempt; returns true if this opened the breaker
+  pub(crate) fn record_failure(&mut self) -> bool {
+    let now = Instant::now();
+    if self.state == BreakerState::HalfOpen {
+      self.open(now, self.cooldown);
+      return true;
+    }
+    self.failures.push_back(now);
+    while self
+      .failures
+      .front()
+      .is_some_and(|&t| now.duration_since(t) > self.window)
+    {
+      self.failures.pop_front();
+    }
+    if self.threshold > 0 && self.failures.len() >= self.threshold {
+      self.open(now, self.cooldown);
+      return true;
+    }
+    false
+  }
+
+  /// Open immediately for `cooldown`, e.g. when Kite throttles the account
+  pub(crate) fn trip_for(&mut self, cooldown: Duration) {
+    self.open(Instant::now(), cooldown);
+  }
+
+  fn open(&mut self, now: Instant, cooldown: Duration) {
+    self.failures.clear();
+    self.opened_at = Some(now);
+    self.open_for = cooldown;
+    self.state = BreakerState::Open;
+  }
+}
@@ -1,12 +1,35 @@
-use crate::models::Mode;
-use std::time::Duration;
+use crate::errors::ConfigError;
+use crate::models::{Exchange, Mode, TickerMessage};
+use crate::ticker::Environment;
+use chrono::{
+  DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, Utc, Weekday,
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 
 /// Configuration for the KiteTicker multi-connection manager
-#[derive(Debug, Clone)]
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[non_exhaustive]
 pub struct KiteManagerConfig {
   /// Maximum symbols per WebSocket connection (Kite limit: 3000)
   pub max_symbols_per_connection: usize,
 
+  /// Per-connection overrides of `max_symbols_per_connection`, by index.
+  ///
+  /// Connections without an entry use the global cap, so `[100]` keeps
+  /// connection 1 to a small low-latency set while the rest are packed. In
+  /// the multi-API manager the caps apply to each API key's connections.
+  pub per_connection_symbol_caps: Vec<usize>,
+
+  /// If set, only these tokens can be subscribed; others are dropped with a
+  /// warning (guards against buggy watchlist generators)
+  pub allowed_tokens: Option<HashSet<u32>>,
+
   /// Number of WebSocket connections to maintain (Kite limit: 3)
   pub max_connections: usize,
 
@@ -16,16 +39,39 @@ pub struct KiteManagerConfig {
   /// Buffer size for each parser's output channel
   pub parser_buffer_size: usize,
 
+  /// Frames each connection's reader may queue for its parser before
+  /// dropping new ones under burst
+  pub reader_parser_channel_cap: usize,
+
+  /// Parser buffer size for a connection that mostly carries LTP ticks
+  /// (default: `parser_buffer_size`)
+  ///
+  /// Each connection's mode is judged from the subscriptions queued before
+  /// `start()`, placed the way the router will place them.
+  pub ltp_buffer_size: Option<usize>,
+
+  /// Parser buffer size for a connection that mostly carries Full ticks,
+  /// which are several times larger (default: `parser_buffer_size`)
+  pub full_buffer_size: Option<usize>,
+
   /// Connection timeout for establishing WebSocket connections
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub connection_timeout: Duration,
 
-  /// Health check interval for monitoring connections
+  /// Interval of the periodic health log; zero disables the log while
+  /// `get_stats`/`get_health` keep working
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub health_check_interval: Duration,
 
+  /// Healthy connections a strategy needs; dropping below it publishes
+  /// `HealthEvent::BelowMinimum` from the periodic check (0 = off)
+  pub min_healthy_connections: usize,
+
   /// Maximum reconnection attempts per connection
   pub max_reconnect_attempts: usize,
 
   /// Delay between reconnection attempts
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub reconnect_delay: Duration,
 
   /// Enable dedicated parser tasks for each connection
@@ -35,55 +81,560 @@ pub struct KiteManagerConfig {
   pub default_mode: Mode,
 
   /// Consider the websocket alive if a frame (including heartbeat) arrived within this duration
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub heartbeat_liveness_threshold: Duration,
+
+  /// How long a connection waits for a message before emitting
+  /// `TickerMessage::NoData` and re-checking liveness
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub receive_timeout: Duration,
+
+  /// Maximum number of entries kept in the subscription operation log
+  pub operation_log_capacity: usize,
+
+  /// Maximum number of connection lifecycle events kept (0 disables the log)
+  pub lifecycle_log_capacity: usize,
+
+  /// Keep the latest tick per token and replay it as a
+  /// `TickerMessage::Snapshot` when that token is subscribed again
+  pub enable_last_value_cache: bool,
+
+  /// Also replay the cached ticks of a connection's subscribed tokens each
+  /// time `get_channel`/`get_all_channels` hands out a receiver, so a
+  /// reconnecting consumer starts from current state (needs the cache).
+  ///
+  /// The snapshot goes to the new receiver only. It is fed through a
+  /// forwarding task, which costs one extra clone per message.
+  pub snapshot_on_get_channel: bool,
+
+  /// CPU core to pin each connection's dedicated parser to, by connection
+  /// index; connections without an entry are not pinned. Needs the
+  /// `core_affinity` feature (default: no pinning)
+  pub parser_core_affinity: Option<Vec<usize>>,
+
+  /// How many WebSocket handshakes `start()` runs at once (1 = sequential)
+  pub startup_concurrency: usize,
+
+  /// Warn when parsing a single frame takes longer than this (default: off)
+  #[serde_as(as = "Option<serde_with::DurationSecondsWithFrac<f64>>")]
+  pub slow_parse_threshold: Option<Duration>,
+
+  /// Publish packets of unrecognised length as
+  /// `TickerMessage::UnknownPacket` instead of only counting them as parse
+  /// errors (default: off)
+  pub emit_unknown_packets: bool,
+
+  /// Salvage the prices of Full packets with damaged depth as `partial`
+  /// ticks instead of dropping them (default: off)
+  pub best_effort_parsing: bool,
+
+  /// Drop a Quote/Full tick when it is identical in every field to the
+  /// previous tick of the same token on that connection (default: off)
+  pub coalesce_duplicate_ticks: bool,
+
+  /// Fill `TickMessage::requested_mode` with each token's subscribed mode,
+  /// delivering mid-mode-change packets instead of holding them back
+  /// (default: off)
+  pub stamp_requested_mode: bool,
+
+  /// How long after a subscribe or mode change packets still in the
+  /// previous mode are held back; after that they are delivered in the
+  /// mode they came in (default: 2s)
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub mode_hold_back: Duration,
+
+  /// How long `subscribe_symbols_confirmed` waits for a tick before it
+  /// accepts a token whose connection has sent a frame (a heartbeat, say)
+  /// since the subscribe; illiquid tokens may not tick for minutes
+  /// (default: 5s)
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub subscribe_confirm_grace: Duration,
+
+  /// Budget for messages queued across every connection's parser input
+  /// and output channels. Nearing it (80%) makes the last connection drop
+  /// incoming ticks; reaching it makes every connection drop them. Output
+  /// counts the backlog of the slowest receiver handed out; must be at
+  /// least the largest parser buffer size (default: unlimited)
+  pub max_total_buffered_messages: Option<usize>,
+
+  /// Grace period after which tokens that have not ticked are pruned,
+  /// counted only while their exchange's session is open; checked
+  /// automatically after `start()` (default: off)
+  #[serde_as(as = "Option<serde_with::DurationSecondsWithFrac<f64>>")]
+  pub auto_prune_inactive: Option<Duration>,
+
+  /// Trading hours per exchange, used by `auto_prune_inactive`; tokens of
+  /// an exchange without a session are never pruned (default: Kite's
+  /// regular sessions, without holidays)
+  pub market_sessions: HashMap<Exchange, MarketSession>,
+
+  /// Ticker endpoint used by every connection (default: live)
+  pub environment: Environment,
+
+  /// Fail `start()` if any connection fails. When false, the manager starts
+  /// with the connections that succeeded and skips the rest when
+  /// distributing symbols.
+  pub require_all_connections: bool,
+
+  /// Failed reconnects within `circuit_breaker_window` that open the
+  /// reconnect circuit breaker (0 disables it)
+  pub circuit_breaker_threshold: usize,
+
+  /// Sliding window over which reconnect failures are counted
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub circuit_breaker_window: Duration,
+
+  /// How long an open breaker refuses reconnects before allowing a probe
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub circuit_breaker_cooldown: Duration,
+
+  /// Cooldown applied instead when Kite throttles the account, so
+  /// reconnecting does not make the throttle worse
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub throttle_cooldown: Duration,
 }
 
 impl Default for KiteManagerConfig {
   fn default() -> Self {
     Self {
       max_symbols_per_connection: 3000,
+      per_connection_symbol_caps: Vec::new(),
+      allowed_tokens: None,
       max_connections: 3,
       connection_buffer_size: 5000, // High buffer for performance
       parser_buffer_size: 10000,    // Even higher for parsed messages
+      reader_parser_channel_cap: 4096,
+      ltp_buffer_size: None,
+      full_buffer_size: None,
       connection_timeout: Duration::from_secs(30),
       health_check_interval: Duration::from_secs(10),
+      min_healthy_connections: 0,
       max_reconnect_attempts: 5,
       reconnect_delay: Duration::from_secs(2),
       enable_dedicated_parsers: true,
       default_mode: Mode::Quote,
       heartbeat_liveness_threshold: Duration::from_secs(10),
+      receive_timeout: Duration::from_secs(30),
+      operation_log_capacity: 1000,
+      lifecycle_log_capacity: 100,
+      enable_last_value_cache: false,
+      snapshot_on_get_channel: false,
+      parser_core_affinity: None,
+      startup_concurrency: 3,
+      slow_parse_threshold: None,
+      emit_unknown_packets: false,
+      best_effort_parsing: false,
+      coalesce_duplicate_ticks: false,
+      stamp_requested_mode: false,
+      mode_hold_back: Duration::from_secs(2),
+      subscribe_confirm_grace: Duration::from_secs(5),
+      max_total_buffered_messages: None,
+      auto_prune_inactive: None,
+      market_sessions: MarketSession::kite_defaults(),
+      environment: Environment::Live,
+      require_all_connections: true,
+      circuit_breaker_threshold: 5,
+      circuit_breaker_window: Duration::from_secs(60),
+      circuit_breaker_cooldown: Duration::from_secs(30),
+      throttle_cooldown: Duration::from_secs(300),
     }
   }
 }
 
+impl KiteManagerConfig {
+  /// Symbol cap for the connection at `index`
+  pub fn symbol_cap(&self, index: usize) -> usize {
+    self
+      .per_connection_symbol_caps
+      .get(index)
+      .copied()
+      .unwrap_or(self.max_symbols_per_connection)
+  }
+
+  /// Core the parser of connection `index` should be pinned to, if any
+  pub(crate) fn parser_core(&self, index: usize) -> Option<usize> {
+    self.parser_core_affinity.as_ref()?.get(index).copied()
+  }
+
+  /// Parser buffer size for connections whose dominant mode is `mode`
+  pub fn parser_buffer_size_for(&self, mode: Mode) -> usize {
+    match mode {
+      Mode::LTP => self.ltp_buffer_size,
+      Mode::Quote => None,
+      Mode::Full => self.full_buffer_size,
+    }
+    .unwrap_or(self.parser_buffer_size)
+  }
+
+  /// Capacity of the largest parser output channel
+  fn largest_parser_buffer(&self) -> usize {
+    [Mode::LTP, Mode::Quote, Mode::Full]
+      .into_iter()
+      .map(|mode| self.parser_buffer_size_for(mode))
+      .max()
+      .unwrap_or(self.parser_buffer_size)
+  }
+
+  /// Check the configuration for values the manager cannot work with
+  pub fn validate(&self) -> Result<(), ConfigError> {
+    let err = |msg: String| Err(ConfigError(msg));
+    if !(1..=3).contains(&self.max_connections) {
+      return err(format!(
+        "max_connections must be between 1 and 3, got {}",
+        self.max_connections
+      ));
+    }
+    if !(1..=3000).contains(&self.max_symbols_per_connection) {
+      return err(format!(
+        "max_symbols_per_connection must be between 1 and 3000, got {}",
+        self.max_symbols_per_connection
+      ));
+    }
+    if self.per_connection_symbol_caps.len() > self.max_connections {
+      return err(format!(
+        "per_connection_symbol_caps has {} entries for {} connections",
+        self.per_connection_symbol_caps.len(),
+        self.max_connections
+      ));
+    }
+    if let Some(cap) = self
+      .per_connection_symbol_caps
+      .iter()
+      .find(|&&cap| cap > 3000)
+    {
+      return err(format!(
+        "per_connection_symbol_caps entries must be at most 3000, got {}",
+        cap
+      ));
+    }
+    if self.connection_buffer_size == 0
+      || self.parser_buffer_size == 0
+      || self.reader_parser_channel_cap == 0
+      || self.ltp_buffer_size == Some(0)
+      || self.full_buffer_size == Some(0)
+    {
+      return err("buffer sizes must be non-zero".to_string());
+    }
+    if self.parser_buffer_size < self.connection_buffer_size {
+      return err(format!(
+        "parser_buffer_size ({}) is smaller than connection_buffer_size ({})",
+        self.parser_buffer_size, self.connection_buffer_size
+      ));
+    }
+    // One consumer falling behind by a full output channel must not stop
+    // every connection
+    if let Some(limit) = self.max_total_buffered_messages {
+      let channel = self.largest_parser_buffer();
+      if limit < channel {
+        return err(format!(
+          "max_total_buffered_messages ({}) is smaller than a parser output \
+           channel ({})",
+          limit, channel
+        ));
+      }
+    }
+    if self
+      .auto_prune_inactive
+      .is_some_and(|grace| grace.is_zero())
+    {
+      return err("auto_prune_inactive must be non-zero".to_string());
+    }
+    if let Some((exchange, _)) = self
+      .market_sessions
+      .iter()
+      .find(|(_, session)| session.open >= session.close)
+    {
+      return err(format!(
+        "market_sessions: {:?} opens at or after it closes",
+        exchange
+      ));
+    }
+    if self.connection_timeout.is_zero() {
+      return err("connection_timeout must be non-zero".to_string());
+    }
+    if self.heartbeat_liveness_threshold.is_zero() {
+      return err("heartbeat_liveness_threshold must be non-zero".to_string());
+    }
+    if self.receive_timeout.is_zero() {
+      return err("receive_timeout must be non-zero".to_string());
+    }
+    Ok(())
+  }
+
+  /// Pull every value [`validate`](Self::validate) rejects into range,
+  /// logging a warning for each one changed
+  pub fn clamped(mut self) -> Self {
+    let defaults = Self::default();
+    let warn = |name: &str, changed: bool| {
+      if changed {
+        log::warn!("KiteManagerConfig: {} out of range, clamped", name);
+      }
+    };
+
+    let n = self.max_connections.clamp(1, 3);
+    warn("max_connections", n != self.max_connections);
+    self.max_connections = n;
+    let n = self.max_symbols_per_connection.clamp(1, 3000);
+    warn(
+      "max_symbols_per_connection",
+      n != self.max_symbols_per_connection,
+    );
+    self.max_symbols_per_connection = n;
+    let caps = &mut self.per_connection_symbol_caps;
+    let over =
+      caps.len() > self.max_connections || caps.iter().any(|&cap| cap > 3000);
+    caps.truncate(self.max_connections);
+    caps.iter_mut().for_each(|cap| *cap = (*cap).min(3000));
+    warn("per_connection_symbol_caps", over);
+
+    for (name, size) in [
+      ("connection_buffer_size", &mut self.connection_buffer_size),
+      ("parser_buffer_size", &mut self.parser_buffer_size),
+      (
+        "reader_parser_channel_cap",
+        &mut self.reader_parser_channel_cap,
+      ),
+    ] {
+      warn(name, *size == 0);
+      *size = (*size).max(1);
+    }
+    for (name, size) in [
+      ("ltp_buffer_size", &mut self.ltp_buffer_size),
+      ("full_buffer_size", &mut self.full_buffer_size),
+    ] {
+      if *size == Some(0) {
+        warn(name, true);
+        *size = None;
+      }
+    }
+    let n = self.parser_buffer_size.max(self.connection_buffer_size);
+    warn("parser_buffer_size", n != self.parser_buffer_size);
+    self.parser_buffer_size = n;
+    let channel = self.largest_parser_buffer();
+    if let Some(limit) = &mut self.max_total_buffered_messages {
+      warn("max_total_buffered_messages", *limit < channel);
+      *limit = (*limit).max(channel);
+    }
+
+    for (name, timeout, default) in [
+      (
+        "connection_timeout",
+        &mut self.connection_timeout,
+        defaults.connection_timeout,
+      ),
+      (
+        "heartbeat_liveness_threshold",
+        &mut self.heartbeat_liveness_threshold,
+        defaults.heartbeat_liveness_threshold,
+      ),
+      (
+        "receive_timeout",
+        &mut self.receive_timeout,
+        defaults.receive_timeout,
+      ),
+    ] {
+      if timeout.is_zero() {
+        warn(name, true);
+        *timeout = default;
+      }
+    }
+    if self
+      .auto_prune_inactive
+      .is_some_and(|grace| grace.is_zero())
+    {
+      warn("auto_prune_inactive", true);
+      self.auto_prune_inactive = None;
+    }
+    let sessions = self.market_sessions.len();
+    self
+      .market_sessions
+      .retain(|_, session| session.open < session.close);
+    warn("market_sessions", self.market_sessions.len() != sessions);
+    self
+  }
+}
+
+/// Regular trading hours of an exchange, in IST
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct MarketSession {
+  pub open: NaiveTime,
+  pub close: NaiveTime,
+  /// Days the session runs (default: Monday to Friday)
+  pub weekdays: Vec<Weekday>,
+  /// Dates the exchange is closed on a weekday
+  pub holidays: HashSet<NaiveDate>,
+}
+
+impl MarketSession {
+  /// Session from `open` to `close`, Monday to Friday, without holidays
+  pub fn new(open: NaiveTime, close: NaiveTime) -> Self {
+    Self {
+      open,
+      close,
+      weekdays: vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+      ],
+      holidays: HashSet::new(),
+    }
+  }
+
+  /// Close the exchange on `dates`, e.g. from the exchange's holiday list
+  pub fn with_holidays(
+    mut self,
+    dates: impl IntoIterator<Item = NaiveDate>,
+  ) -> Self {
+    self.holidays.extend(dates);
+    self
+  }
+
+  /// Run the session on `weekdays` instead of Monday to Friday
+  pub fn with_weekdays(mut self, weekdays: &[Weekday]) -> Self {
+    self.weekdays = weekdays.to_vec();
+    self
+  }
+
+  /// How long the session has been open at `now`, or `None` outside it
+  pub fn open_for(&self, now: DateTime<Utc>) -> Option<Duration> {
+    let ist = now.with_timezone(&FixedOffset::east_opt(5 * 3600 + 30 * 60)?);
+    let (date, time) = (ist.date_naive(), ist.time());
+    let trading =
+      self.weekdays.contains(&date.weekday()) && !self.holidays.contains(&date);
+    (trading && self.open <= time && time < self.close)
+      .then(|| (time - self.open).to_std().ok())
+      .flatten()
+  }
+
+  /// NSE, BSE and their derivative and index segments 09:15-15:30,
+  /// currency 09:00-17:00, MCX 09:00-23:30
+  pub(crate) fn kite_defaults() -> HashMap<Exchange, Self> {
+    let at = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap_or_default();
+    let equity = Self::new(at(9, 15), at(15, 30));
+    let currency = Self::new(at(9, 0), at(17, 0));
+    HashMap::from([
+      (Exchange::NSE, equity.clone()),
+      (Exchange::NFO, equity.clone()),
+      (Exchange::BSE, equity.clone()),
+      (Exchange::BFO, equity.clone()),
+      (Exchange::INDICES, equity),
+      (Exchange::CDS, currency.clone()),
+      (Exchange::BCD, currency),
+      (Exchange::MCX, Self::new(at(9, 0), at(23, 30))),
+    ])
+  }
+}
+
+/// Tokens pruned by `auto_prune_inactive` for not ticking within the grace
+/// period
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct PruneEvent {
+  /// Pruned tokens, sorted
+  pub tokens: Vec<u32>,
+}
+
 /// Connection statistics for monitoring
-#[derive(Debug, Clone, Default)]
+///
+/// Serializes durations as fractional seconds, `last_message_time` as
+/// `last_message_age_secs` (seconds since the last message) and
+/// `last_error` as `{ "age_secs": .., "message": .. }`.
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Default, Serialize)]
+#[non_exhaustive]
 pub struct ConnectionStats {
   pub connection_id: usize,
   pub is_connected: bool,
   pub symbol_count: usize,
   pub messages_received: u64,
+  /// Tick packets parsed successfully
   pub messages_parsed: u64,
+  /// Tick packets (or truncated frames) the parser rejected
+  pub parse_errors: u64,
   pub errors_count: u64,
-  pub last_message_time: Option<std::time::Instant>,
+  #[serde(
+    rename = "last_message_age_secs",
+    serialize_with = "serialize_instant_age"
+  )]
+  pub last_message_time: Option<Instant>,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub average_latency: Duration,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub connection_uptime: Duration,
+  /// Commands queued but not yet written to the socket
+  pub command_queue_depth: usize,
+  /// Most recent error (socket error, dropped message or parse failure)
+  /// and when it happened
+  #[serde(serialize_with = "serialize_last_error")]
+  pub last_error: Option<(Instant, String)>,
+  /// Tick messages dropped to stay within `max_total_buffered_messages`
+  pub messages_shed: u64,
+  /// Packets held back because they arrived before their token's new mode
+  /// took effect (see `mode_hold_back`)
+  pub ticks_held_back: u64,
 }
 
 /// Manager-wide statistics
-#[derive(Debug, Clone, Default)]
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Default, Serialize)]
+#[non_exhaustive]
 pub struct ManagerStats {
   pub total_symbols: usize,
   pub active_connections: usize,
   pub total_messages_received: u64,
   pub total_messages_parsed: u64,
+  pub total_parse_errors: u64,
   pub total_errors: u64,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub uptime: Duration,
   pub connection_stats: Vec<ConnectionStats>,
+  /// How long recent subscribe/unsubscribe/change_mode calls took
+  pub subscription_latency: SubscriptionLatencies,
+  /// Messages currently queued in parser channels, across all connections
+  pub total_buffered_messages: usize,
+  pub total_messages_shed: u64,
+  pub total_ticks_held_back: u64,
+}
+
+/// Serialize an optional `Instant` as seconds elapsed since it
+pub(crate) fn serialize_instant_age<S>(
+  instant: &Option<Instant>,
+  serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+  S: serde::Serializer,
+{
+  match instant {
+    Some(t) => serializer.serialize_some(&t.elapsed().as_secs_f64()),
+    None => serializer.serialize_none(),
+  }
+}
+
+/// Serialize an optional `(Instant, message)` as its age and message
+fn serialize_last_error<S>(
+  error: &Option<(Instant, String)>,
+  serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+  S: serde::Serializer,
+{
+  use serde::ser::SerializeStruct;
+  match error {
+    Some((at, message)) => {
+      let mut s = serializer.serialize_struct("LastError", 2)?;
+      s.serialize_field("age_secs", &at.elapsed().as_secs_f64())?;
+      s.serialize_field("message", message)?;
+      s.end()
+    }
+    None => serializer.serialize_none(),
+  }
 }
 
 /// Channel identifier for output channels
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum ChannelId {
   Connection1 = 0,
   Connection2 = 1,
@@ -109,6 +660,259 @@ impl ChannelId {
   }
 }
 
+/// Kind of subscription operation performed by the manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SubscriptionOpKind {
+  Subscribe,
+  Unsubscribe,
+  ModeChange,
+}
+
+/// A single entry in the manager's subscription operation log
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionOp {
+  /// Wall-clock time the operation was issued
+  #[serde_as(as = "serde_with::TimestampSecondsWithFrac<f64>")]
+  pub timestamp: std::time::SystemTime,
+  pub op: SubscriptionOpKind,
+  pub tokens: Vec<u32>,
+  /// Requested mode (`None` for unsubscribe)
+  pub mode: Option<Mode>,
+  /// Connection the operation was sent on
+  pub channel: ChannelId,
+}
+
+/// What happened to a connection in a [`LifecycleEvent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LifecycleEventKind {
+  /// Socket established
+  Connected,
+  /// Kite sent a closing frame
+  Closed,
+  /// The message loop stopped: socket ended or went silent
+  Disconnected,
+  /// A reconnect restored the socket and its subscriptions
+  Reconnected,
+  /// A reconnect gave up
+  ReconnectFailed,
+}
+
+/// One entry in the manager's connection lifecycle log
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct LifecycleEvent {
+  #[serde_as(as = "serde_with::TimestampSecondsWithFrac<f64>")]
+  pub timestamp: std::time::SystemTime,
+  pub channel: ChannelId,
+  pub kind: LifecycleEventKind,
+  /// Close reason, error text or other context (may be empty)
+  pub detail: String,
+}
+
+/// Bounded lifecycle history shared by a manager and its connections
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LifecycleLog {
+  events: Arc<Mutex<VecDeque<LifecycleEvent>>>,
+  capacity: usize,
+}
+
+impl LifecycleLog {
+  pub(crate) fn new(capacity: usize) -> Self {
+    Self {
+      events: Arc::default(),
+      capacity,
+    }
+  }
+
+  pub(crate) fn record(
+    &self,
+    channel: ChannelId,
+    kind: LifecycleEventKind,
+    detail: impl Into<String>,
+  ) {
+    if self.capacity == 0 {
+      return;
+    }
+    let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+    while events.len() >= self.capacity {
+      events.pop_front();
+    }
+    events.push_back(LifecycleEvent {
+      timestamp: std::time::SystemTime::now(),
+      channel,
+      kind,
+      detail: detail.into(),
+    });
+  }
+
+  pub(crate) fn snapshot(&self) -> Vec<LifecycleEvent> {
+    let events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+    events.iter().cloned().collect()
+  }
+}
+
+/// Messages queued between each connection and its consumers, checked
+/// against `max_total_buffered_messages`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BufferBudget {
+  limit: Option<usize>,
+  slots: Arc<Vec<BufferSlot>>,
+}
+
+/// Queue state of one connection: messages sent to its parser but not yet
+/// taken, plus the parser's output channel once it exists
+#[derive(Debug, Default)]
+struct BufferSlot {
+  input: AtomicUsize,
+  output: OnceLock<broadcast::Sender<TickerMessage>>,
+}
+
+impl BufferBudget {
+  pub(crate) fn new(limit: Option<usize>, connections: usize) -> Self {
+    Self {
+      limit,
+      slots: Arc::new(
+        (0..connections).map(|_| BufferSlot::default()).collect(),
+      ),
+    }
+  }
+
+  /// Count the parser output of connection `index` towards the total
+  pub(crate) fn track_output(
+    &self,
+    index: usize,
+    output: broadcast::Sender<TickerMessage>,
+  ) {
+    if let Some(slot) = self.slots.get(index) {
+      let _ = slot.output.set(output);
+    }
+  }
+
+  /// A message is about to be queued for the parser of connection `index`
+  pub(crate) fn queued(&self, index: usize) {
+    if let Some(slot) = self.slots.get(index) {
+      slot.input.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  /// The parser of connection `index` took a message (or it was never sent)
+  pub(crate) fn dequeued(&self, index: usize) {
+    if let Some(slot) = self.slots.get(index) {
+      let _ =
+        slot
+          .input
+          .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+            n.checked_sub(1)
+          });
+    }
+  }
+
+  pub(crate) fn total(&self) -> usize {
+    self
+      .slots
+      .iter()
+      .map(|slot| {
+        slot.input.load(Ordering::Relaxed)
+          + slot.output.get().map_or(0, broadcast::Sender::len)
+      })
+      .sum()
+  }
+
+  /// Whether connection `index` should drop incoming ticks: the last
+  /// connection sheds from 80% of the budget, every connection at 100%
+  pub(crate) fn should_shed(&self, index: usize) -> bool {
+    let Some(limit) = self.limit else {
+      return false;
+    };
+    let total = self.total();
+    if total >= limit {
+      return true;
+    }
+    let last = self.slots.len().saturating_sub(1);
+    index == last
+      && last > 0
+      && total.saturating_mul(5) >= limit.saturating_mul(4)
+  }
+}
+
+/// Average and maximum duration over the most recent calls of one operation
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct OperationLatency {
+  /// Calls timed since the manager was created
+  pub count: u64,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub avg: Duration,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub max: Duration,
+}
+
+/// Latency of the manager's dynamic subscription operations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct SubscriptionLatencies {
+  pub subscribe: OperationLatency,
+  pub unsubscribe: OperationLatency,
+  pub change_mode: OperationLatency,
+}
+
+/// Samples kept per operation for the rolling average and maximum
+const LATENCY_WINDOW: usize = 100;
+
+/// Rolling window of recent durations of one operation
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LatencyWindow {
+  samples: VecDeque<Duration>,
+  count: u64,
+}
+
+impl LatencyWindow {
+  pub(crate) fn record(&mut self, elapsed: Duration) {
+    if self.samples.len() == LATENCY_WINDOW {
+      self.samples.pop_front();
+    }
+    self.samples.push_back(elapsed);
+    self.count += 1;
+  }
+
+  pub(crate) fn summary(&self) -> OperationLatency {
+    let total: Duration = self.samples.iter().sum();
+    OperationLatency {
+      count: self.count,
+      avg: total
+        .checked_div(self.samples.len() as u32)
+        .unwrap_or_default(),
+      max: self.samples.iter().max().copied().unwrap_or_default(),
+    }
+  }
+}
+
+/// Latency windows of each [`SubscriptionOpKind`]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SubscriptionTimings {
+  subscribe: LatencyWindow,
+  unsubscribe: LatencyWindow,
+  change_mode: LatencyWindow,
+}
+
+impl SubscriptionTimings {
+  pub(crate) fn record(&mut self, op: SubscriptionOpKind, elapsed: Duration) {
+    match op {
+      SubscriptionOpKind::Subscribe => self.subscribe.record(elapsed),
+      SubscriptionOpKind::Unsubscribe => self.unsubscribe.record(elapsed),
+      SubscriptionOpKind::ModeChange => self.change_mode.record(elapsed),
+    }
+  }
+
+  pub(crate) fn summary(&self) -> SubscriptionLatencies {
+    SubscriptionLatencies {
+      subscribe: self.subscribe.summary(),
+      unsubscribe: self.unsubscribe.summary(),
+      change_mode: self.change_mode.summary(),
+    }
+  }
+}
+
 // ============================================================================
 // Multi-API Configuration Types
 // ============================================================================
@@ -143,43 +947,64 @@ pub struct ApiCredentials {
 }
 
 impl ApiCredentials {
-  pub fn new(api_key: impl Into<String>, access_token: impl Into<String>) -> Self {
+  pub fn new(
+    api_key: impl Into<String>,
+    access_token: impl Into<String>,
+  ) -> Self {
     Self {
       api_key: api_key.into(),
       access_token: access_token.into(),
     }
   }
+
+  /// Masked API key for logs and dashboards, e.g. `abcd***`
+  pub fn fingerprint(&self) -> String {
+    api_key_fingerprint(&self.api_key)
+  }
+}
+
+/// Keep the first 4 characters of `api_key` and mask the rest; keys too
+/// short to leave anything hidden are masked entirely
+pub(crate) fn api_key_fingerprint(api_key: &str) -> String {
+  const VISIBLE: usize = 4;
+  if api_key.chars().count() <= VISIBLE {
+    return "***".to_string();
+  }
+  let prefix: String = api_key.chars().take(VISIBLE).collect();
+  format!("{}***", prefix)
 }
 
 /// Strategy for distributing symbols across multiple API keys
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum DistributionStrategy {
   /// Automatically distribute symbols across all API keys using round-robin
+  #[default]
   RoundRobin,
   /// Manually assign symbols to specific API keys
   Manual,
 }
 
-impl Default for DistributionStrategy {
-  fn default() -> Self {
-    Self::RoundRobin
-  }
-}
-
 /// Configuration for multi-API manager
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct MultiApiConfig {
   /// Base configuration for each API key's connections
   pub base_config: KiteManagerConfig,
-  
+
   /// Maximum connections per API key (Kite limit: 3)
   pub max_connections_per_api: usize,
-  
+
   /// Symbol distribution strategy
   pub distribution_strategy: DistributionStrategy,
-  
+
   /// Enable health monitoring across all API keys
   pub enable_health_monitoring: bool,
+
+  /// Allow the same token to be subscribed on several API keys at once.
+  ///
+  /// Useful for redundant streaming: every copy arrives on the unified
+  /// channel tagged with its `ApiKeyId`, so consumers can dedupe or fail over.
+  pub allow_duplicate_across_apis: bool,
 }
 
 impl Default for MultiApiConfig {
@@ -189,6 +1014,7 @@ impl Default for MultiApiConfig {
       max_connections_per_api: 3,
       distribution_strategy: DistributionStrategy::RoundRobin,
       enable_health_monitoring: true,
+      allow_duplicate_across_apis: false,
     }
   }
 }
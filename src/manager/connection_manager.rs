@@ -12,15 +12,32 @@
 //! - **Health Monitoring**: Real-time connection health tracking
 //! - **Error Resilience**: Comprehensive error handling and recovery
 
+use crate::errors::{ConfigError, KiteError};
 use crate::manager::{
-  ChannelId, ConnectionStats, HealthMonitor, HealthSummary, KiteManagerConfig,
-  ManagedConnection, ManagerStats, MessageProcessor, ProcessorStats,
+  api_key_fingerprint, BufferBudget, ChannelId, CircuitBreaker, CommandSlot,
+  ConnectionStats, DashboardConnection, DashboardSnapshot, Diagnostics,
+  DistributionView, HealthEvent, HealthMonitor, HealthSummary,
+  KiteManagerConfig, LastValueCache, LeastLoadedRouter, LifecycleEvent,
+  LifecycleEventKind, LifecycleLog, ManagedConnection, ManagerStats,
+  MarketSession, MessageProcessor, ModeBreakdown, PriorityRoute,
+  ProcessorStats, PruneEvent, ResyncReceiver, RoundRobinRouter, SubscriptionOp,
+  SubscriptionOpKind, SubscriptionState, SubscriptionTimings, SymbolActivity,
+  SymbolRouter, TickSink, TokenActivityMap,
 };
-use crate::models::{Mode, TickerMessage};
-use std::collections::HashMap;
+use crate::models::{
+  Exchange, InstrumentDirectory, Mode, Request, TickMessage, TickerMessage,
+};
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_util::sync::CancellationToken;
 
 /// High-performance multi-connection WebSocket manager for Kite ticker data
 ///
@@ -42,23 +59,73 @@ pub struct KiteTickerManager {
   /// Message processors (one per connection)
   processors: Vec<MessageProcessor>,
 
-  /// Output channels (one per connection)
-  output_channels: Vec<broadcast::Receiver<TickerMessage>>,
+  /// Symbol to connection mapping; shared with the `auto_prune_inactive`
+  /// timer, which unsubscribes and unmaps silent symbols itself
+  symbol_mapping: Arc<std::sync::RwLock<HashMap<u32, ChannelId>>>,
+
+  /// When each currently subscribed symbol was subscribed; shared with
+  /// the `auto_prune_inactive` timer
+  subscribed_at: Arc<std::sync::RwLock<HashMap<u32, Instant>>>,
+
+  /// Tokens the prune timer unsubscribed, with their connection; the
+  /// next operation drops them from that connection's symbol list
+  pruned: Arc<std::sync::Mutex<HashMap<u32, ChannelId>>>,
+
+  /// Serializes state file writes of the manager and the prune timer
+  state_lock: Arc<tokio::sync::Mutex<()>>,
 
-  /// Symbol to connection mapping
-  symbol_mapping: HashMap<u32, ChannelId>,
+  /// Subscriptions requested before `start()`, applied once connected
+  pending_subscriptions: Vec<(Vec<u32>, Mode)>,
 
   /// Health monitor
   health_monitor: Option<HealthMonitor>,
 
-  /// Next connection index for round-robin distribution
-  next_connection_index: usize,
+  /// Chooses the connection for each new symbol (round-robin by default)
+  router: Arc<std::sync::Mutex<dyn SymbolRouter>>,
 
   /// Manager start time for uptime tracking
   #[allow(dead_code)]
   start_time: Instant,
   /// If true, underlying connections operate in raw-only mode (no tick parsing)
   raw_only: bool,
+
+  /// Optional file used to persist and restore subscriptions across restarts
+  state_file: Option<PathBuf>,
+
+  /// Resolves trading symbols for `subscribe_by_symbol`
+  instruments: Option<Arc<InstrumentDirectory>>,
+
+  /// Bounded history of subscribe/unsubscribe/mode-change operations
+  operation_log: VecDeque<SubscriptionOp>,
+
+  /// Bounded history of connects, closes, disconnects and reconnects
+  lifecycle_log: LifecycleLog,
+
+  /// Messages queued across all connections, against
+  /// `max_total_buffered_messages`
+  buffer_budget: BufferBudget,
+
+  /// Recent durations of subscribe/unsubscribe/change_mode calls
+  op_timings: SubscriptionTimings,
+
+  /// Parent token for all background tasks; cancelled on `stop()`
+  cancel_token: CancellationToken,
+
+  /// Latest tick per token, when `enable_last_value_cache` is set
+  last_value_cache: Option<LastValueCache>,
+
+  /// Routes ticks of priority symbols to `priority_rx`
+  priority_route: PriorityRoute,
+  priority_rx: Option<mpsc::UnboundedReceiver<TickMessage>>,
+
+  /// Coverage changes against `min_healthy_connections`
+  health_events: broadcast::Sender<HealthEvent>,
+
+  /// Tokens pruned by `auto_prune_inactive`
+  prune_events: broadcast::Sender<PruneEvent>,
+
+  /// Shared by all connections so an outage cannot trigger a reconnect storm
+  breaker: CircuitBreaker,
 }
 
 /// Builder for `KiteTickerManager` providing a fluent API for configuration.
@@ -68,6 +135,9 @@ pub struct KiteTickerManagerBuilder {
   access_token: String,
   config: KiteManagerConfig,
   raw_only: bool,
+  state_file: Option<PathBuf>,
+  router: Option<Arc<std::sync::Mutex<dyn SymbolRouter>>>,
+  instruments: Option<Arc<InstrumentDirectory>>,
 }
 
 impl KiteTickerManagerBuilder {
@@ -81,6 +151,9 @@ impl KiteTickerManagerBuilder {
       access_token: access_token.into(),
       config: KiteManagerConfig::default(),
       raw_only: false,
+      state_file: None,
+      router: None,
+      instruments: None,
     }
   }
 
@@ -92,6 +165,20 @@ impl KiteTickerManagerBuilder {
     self.config.max_symbols_per_connection = n;
     self
   }
+  /// Override the symbol cap per connection index (see
+  /// [`KiteManagerConfig::per_connection_symbol_caps`])
+  pub fn per_connection_symbol_caps(mut self, caps: Vec<usize>) -> Self {
+    self.config.per_connection_symbol_caps = caps;
+    self
+  }
+  /// Refuse to subscribe tokens outside `tokens`
+  pub fn allowed_tokens(
+    mut self,
+    tokens: impl IntoIterator<Item = u32>,
+  ) -> Self {
+    self.config.allowed_tokens = Some(tokens.into_iter().collect());
+    self
+  }
   pub fn connection_timeout(mut self, d: std::time::Duration) -> Self {
     self.config.connection_timeout = d;
     self
@@ -100,6 +187,10 @@ impl KiteTickerManagerBuilder {
     self.config.health_check_interval = d;
     self
   }
+  pub fn min_healthy_connections(mut self, n: usize) -> Self {
+    self.config.min_healthy_connections = n;
+    self
+  }
   pub fn reconnect_attempts(mut self, attempts: usize) -> Self {
     self.config.max_reconnect_attempts = attempts;
     self
@@ -123,6 +214,10 @@ impl KiteTickerManagerBuilder {
     self.config.heartbeat_liveness_threshold = d;
     self
   }
+  pub fn receive_timeout(mut self, d: std::time::Duration) -> Self {
+    self.config.receive_timeout = d;
+    self
+  }
   pub fn connection_buffer_size(mut self, sz: usize) -> Self {
     self.config.connection_buffer_size = sz;
     self
@@ -131,10 +226,117 @@ impl KiteTickerManagerBuilder {
     self.config.parser_buffer_size = sz;
     self
   }
+  pub fn reader_parser_channel_cap(mut self, cap: usize) -> Self {
+    self.config.reader_parser_channel_cap = cap;
+    self
+  }
+  /// Parser buffer sizes used instead of `parser_buffer_size` on
+  /// connections where LTP or Full ticks dominate
+  pub fn mode_buffer_sizes(mut self, ltp: usize, full: usize) -> Self {
+    self.config.ltp_buffer_size = Some(ltp);
+    self.config.full_buffer_size = Some(full);
+    self
+  }
   pub fn raw_only(mut self, raw: bool) -> Self {
     self.raw_only = raw;
     self
   }
+  pub fn enable_last_value_cache(mut self, enable: bool) -> Self {
+    self.config.enable_last_value_cache = enable;
+    self
+  }
+  pub fn snapshot_on_get_channel(mut self, enable: bool) -> Self {
+    self.config.snapshot_on_get_channel = enable;
+    self
+  }
+  pub fn parser_core_affinity(mut self, cores: Vec<usize>) -> Self {
+    self.config.parser_core_affinity = Some(cores);
+    self
+  }
+  pub fn startup_concurrency(mut self, n: usize) -> Self {
+    self.config.startup_concurrency = n;
+    self
+  }
+  pub fn emit_unknown_packets(mut self, enable: bool) -> Self {
+    self.config.emit_unknown_packets = enable;
+    self
+  }
+  pub fn best_effort_parsing(mut self, enable: bool) -> Self {
+    self.config.best_effort_parsing = enable;
+    self
+  }
+  pub fn coalesce_duplicate_ticks(mut self, enable: bool) -> Self {
+    self.config.coalesce_duplicate_ticks = enable;
+    self
+  }
+  pub fn stamp_requested_mode(mut self, enable: bool) -> Self {
+    self.config.stamp_requested_mode = enable;
+    self
+  }
+  pub fn mode_hold_back(mut self, window: std::time::Duration) -> Self {
+    self.config.mode_hold_back = window;
+    self
+  }
+  pub fn subscribe_confirm_grace(mut self, grace: std::time::Duration) -> Self {
+    self.config.subscribe_confirm_grace = grace;
+    self
+  }
+  pub fn max_total_buffered_messages(mut self, limit: usize) -> Self {
+    self.config.max_total_buffered_messages = Some(limit);
+    self
+  }
+  pub fn auto_prune_inactive(mut self, grace: std::time::Duration) -> Self {
+    self.config.auto_prune_inactive = Some(grace);
+    self
+  }
+  pub fn market_session(
+    mut self,
+    exchange: Exchange,
+    session: MarketSession,
+  ) -> Self {
+    self.config.market_sessions.insert(exchange, session);
+    self
+  }
+  pub fn slow_parse_threshold(mut self, d: std::time::Duration) -> Self {
+    self.config.slow_parse_threshold = Some(d);
+    self
+  }
+  pub fn environment(mut self, env: crate::ticker::Environment) -> Self {
+    self.config.environment = env;
+    self
+  }
+  /// Open the reconnect circuit breaker after `threshold` failures within
+  /// `window`, refusing reconnects for `cooldown`
+  pub fn circuit_breaker(
+    mut self,
+    threshold: usize,
+    window: Duration,
+    cooldown: Duration,
+  ) -> Self {
+    self.config.circuit_breaker_threshold = threshold;
+    self.config.circuit_breaker_window = window;
+    self.config.circuit_breaker_cooldown = cooldown;
+    self
+  }
+  pub fn require_all_connections(mut self, require: bool) -> Self {
+    self.config.require_all_connections = require;
+    self
+  }
+  /// Persist subscriptions to `path` and restore them on `start()`
+  pub fn state_file(mut self, path: impl Into<PathBuf>) -> Self {
+    self.state_file = Some(path.into());
+    self
+  }
+
+  /// Resolve trading symbols with `directory` in
+  /// [`subscribe_by_symbol`](KiteTickerManager::subscribe_by_symbol)
+  pub fn instrument_directory(
+    mut self,
+    directory: InstrumentDirectory,
+  ) -> Self {
+    self.instruments = Some(Arc::new(directory));
+    self
+  }
 
   /// Override entire config (advanced)
   pub fn config(mut self, config: KiteManagerConfig) -> Self {
@@ -142,10 +344,38 @@ impl KiteTickerManagerBuilder {
     self
   }
 
+  /// Use a custom [`SymbolRouter`] to place new symbols on connections
+  ///
+  /// Defaults to [`RoundRobinRouter`]; see also
+  /// [`LeastLoadedRouter`](crate::LeastLoadedRouter).
+  pub fn router(mut self, router: impl SymbolRouter + 'static) -> Self {
+    self.router = Some(Arc::new(std::sync::Mutex::new(router)));
+    self
+  }
+
+  /// Build the manager after validating the configuration (not started yet)
+  pub fn try_build(self) -> Result<KiteTickerManager, ConfigError> {
+    self.config.validate()?;
+    Ok(self.build())
+  }
+
   /// Build the manager (not started yet)
+  ///
+  /// Out-of-range settings are clamped (see
+  /// [`KiteManagerConfig::clamped`]) with a warning; use
+  /// [`try_build`](Self::try_build) to reject them instead.
   pub fn build(self) -> KiteTickerManager {
-    KiteTickerManager::new(self.api_key, self.access_token, self.config)
-      .with_raw_only(self.raw_only)
+    let mut manager =
+      KiteTickerManager::new(self.api_key, self.access_token, self.config)
+        .with_raw_only(self.raw_only);
+    if let Some(router) = self.router {
+      manager.router = router;
+    }
+    manager.instruments = self.instruments;
+    match self.state_file {
+      Some(path) => manager.with_state_file(path),
+      None => manager,
+    }
   }
 }
 
@@ -159,20 +389,19 @@ impl KiteTickerManager {
   ///
   /// * `api_key` - Your Kite Connect API key
   /// * `access_token` - Valid access token from Kite Connect
-  /// * `config` - Manager configuration settings
+  /// * `config` - Manager configuration settings; out-of-range values are
+  ///   clamped (see [`KiteManagerConfig::clamped`])
   ///
   /// # Example
   ///
   /// ```rust,no_run
   /// use kiteticker_async_manager::{KiteTickerManager, KiteManagerConfig, Mode};
   ///
-  /// let config = KiteManagerConfig {
-  ///     max_connections: 3,
-  ///     max_symbols_per_connection: 3000,
-  ///     enable_dedicated_parsers: true,
-  ///     default_mode: Mode::LTP,
-  ///     ..Default::default()
-  /// };
+  /// let mut config = KiteManagerConfig::default();
+  /// config.max_connections = 3;
+  /// config.max_symbols_per_connection = 3000;
+  /// config.enable_dedicated_parsers = true;
+  /// config.default_mode = Mode::LTP;
   ///
   /// let manager = KiteTickerManager::new(
   ///     "your_api_key".to_string(),
@@ -185,35 +414,144 @@ impl KiteTickerManager {
     access_token: String,
     config: KiteManagerConfig,
   ) -> Self {
+    let config = config.clamped();
+    let last_value_cache = config
+      .enable_last_value_cache
+      .then(|| Arc::new(RwLock::new(HashMap::new())));
+    let (priority_tx, priority_rx) = mpsc::unbounded_channel();
+    let lifecycle_log = LifecycleLog::new(config.lifecycle_log_capacity);
+    let buffer_budget = BufferBudget::new(
+      config.max_total_buffered_messages,
+      config.max_connections,
+    );
+    let breaker = CircuitBreaker::new(
+      config.circuit_breaker_threshold,
+      config.circuit_breaker_window,
+      config.circuit_breaker_cooldown,
+    );
     Self {
       config,
       api_key,
       access_token,
       connections: Vec::new(),
       processors: Vec::new(),
-      output_channels: Vec::new(),
-      symbol_mapping: HashMap::new(),
+      symbol_mapping: Arc::default(),
+      subscribed_at: Arc::default(),
+      pruned: Arc::default(),
+      state_lock: Arc::default(),
+      pending_subscriptions: Vec::new(),
       health_monitor: None,
-      next_connection_index: 0,
+      router: Arc::new(std::sync::Mutex::new(RoundRobinRouter::default())),
       start_time: Instant::now(),
       raw_only: false,
+      state_file: None,
+      instruments: None,
+      operation_log: VecDeque::new(),
+      lifecycle_log,
+      buffer_budget,
+      op_timings: SubscriptionTimings::default(),
+      cancel_token: CancellationToken::new(),
+      last_value_cache,
+      priority_route: PriorityRoute {
+        tokens: Arc::default(),
+        sender: priority_tx,
+        active: Arc::default(),
+      },
+      priority_rx: Some(priority_rx),
+      health_events: broadcast::channel(16).0,
+      prune_events: broadcast::channel(16).0,
+      breaker,
     }
   }
 
   /// Set raw-only mode (builder uses this)
+  ///
+  /// Binary frames are then not parsed: the parsed channels
+  /// (`get_channel`, `get_all_channels`) never carry ticks, and raw
+  /// consumers must use `get_all_raw_frame_channels` instead.
   pub fn with_raw_only(mut self, raw: bool) -> Self {
     self.raw_only = raw;
     self
   }
 
+  /// Whether the manager forwards raw frames only, without parsed ticks
+  pub fn is_raw_only(&self) -> bool {
+    self.raw_only
+  }
+
+  /// Parsed channels of a raw-only manager stay silent; say so loudly
+  fn warn_if_raw_only(&self, method: &str) {
+    if self.raw_only {
+      log::warn!(
+        "{} called on a raw-only manager: its parsed channels never carry \
+         ticks; use get_all_raw_frame_channels / get_raw_frame_channel",
+        method
+      );
+    }
+  }
+
+  /// Persist subscriptions to a state file and restore them on `start()`
+  ///
+  /// The file is rewritten after every subscribe, unsubscribe and mode change.
+  /// A missing or corrupt file is logged and the manager starts with no
+  /// subscriptions.
+  pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Self {
+    self.state_file = Some(path.into());
+    self
+  }
+
+  /// Set or replace the directory used by
+  /// [`subscribe_by_symbol`](Self::subscribe_by_symbol), e.g. after
+  /// downloading the day's instruments dump
+  pub fn set_instrument_directory(&mut self, directory: InstrumentDirectory) {
+    self.instruments = Some(Arc::new(directory));
+  }
+
+  /// Whether [`stop()`](Self::stop) was called or the cancellation token
+  /// given to `start_with_cancellation` fired
+  pub fn is_stopped(&self) -> bool {
+    self.cancel_token.is_cancelled()
+  }
+
+  /// Fail with [`KiteError::Closed`] once the manager has been stopped, so
+  /// operations never reach torn-down connections
+  fn ensure_running(&self) -> Result<(), KiteError> {
+    if self.is_stopped() {
+      return Err(KiteError::Closed);
+    }
+    Ok(())
+  }
+
   /// Initialize all connections and start the manager
-  pub async fn start(&mut self) -> Result<(), String> {
+  pub async fn start(&mut self) -> Result<(), KiteError> {
+    self.start_with_cancellation(CancellationToken::new()).await
+  }
+
+  /// Start the manager tied to an external cancellation token.
+  ///
+  /// Cancelling `token` (or any parent of it) makes the socket reader/writer,
+  /// per-connection processing loops, parsers and the health monitor exit
+  /// cooperatively, flushing their pending statistics before they finish.
+  /// The manager works on a child of `token`, so [`stop()`](Self::stop) never
+  /// cancels the caller's token.
+  pub async fn start_with_cancellation(
+    &mut self,
+    token: CancellationToken,
+  ) -> Result<(), KiteError> {
+    self.ensure_running()?;
+    Ok(self.do_start(token).await?)
+  }
+
+  async fn do_start(&mut self, token: CancellationToken) -> Result<(), String> {
+    self.cancel_token = token.child_token();
     log::info!(
       "Starting KiteTickerManager with {} connections",
       self.config.max_connections
     );
 
-    // Create all connections and processors
+    // Create all connections
+    let mut connections = Vec::with_capacity(self.config.max_connections);
+    let mut receivers = Vec::with_capacity(self.config.max_connections);
     for i in 0..self.config.max_connections {
       let channel_id = ChannelId::from_index(i)
         .ok_or_else(|| format!("Invalid connection index: {}", i))?;
@@ -222,76 +560,451 @@ impl KiteTickerManager {
       let (connection_sender, processor_receiver) = mpsc::unbounded_channel();
 
       // Create managed connection
-      let mut connection =
-        ManagedConnection::new(channel_id, connection_sender);
+      connections.push(
+        ManagedConnection::new(channel_id, connection_sender)
+          .with_cancellation_token(self.cancel_token.child_token())
+          .with_priority_route(self.priority_route.clone())
+          .with_lifecycle_log(self.lifecycle_log.clone())
+          .with_buffer_budget(self.buffer_budget.clone()),
+      );
+      receivers.push(processor_receiver);
+    }
 
-      // Connect to WebSocket
-      if self.raw_only {
-        connection
-          .connect_with_raw(
-            &self.api_key,
-            &self.access_token,
-            &self.config,
-            true,
-          )
-          .await
-          .map_err(|e| format!("Failed to connect WebSocket {}: {}", i, e))?;
-      } else {
-        connection
-          .connect(&self.api_key, &self.access_token, &self.config)
-          .await
-          .map_err(|e| format!("Failed to connect WebSocket {}: {}", i, e))?;
+    // Connect WebSockets concurrently; each handshake has its own timeout
+    let (api_key, access_token, config, raw_only) = (
+      &self.api_key,
+      &self.access_token,
+      &self.config,
+      self.raw_only,
+    );
+    let results: Vec<Result<(), String>> =
+      stream::iter(connections.iter_mut().enumerate().map(
+        |(i, connection)| async move {
+          let result = if raw_only {
+            connection
+              .connect_with_raw(api_key, access_token, config, true)
+              .await
+          } else {
+            connection.connect(api_key, access_token, config).await
+          };
+          result
+            .map_err(|e| format!("Failed to connect WebSocket {}: {}", i, e))
+        },
+      ))
+      .buffered(self.config.startup_concurrency.max(1))
+      .collect()
+      .await;
+    let total = results.len();
+    let errors: Vec<String> =
+      results.into_iter().filter_map(Result::err).collect();
+    if !errors.is_empty() {
+      if self.config.require_all_connections || errors.len() == total {
+        return Err(errors.join("; "));
       }
+      log::warn!(
+        "Starting with {}/{} connections: {}",
+        total - errors.len(),
+        total,
+        errors.join("; ")
+      );
+    }
+
+    // Output buffers are fixed once created, so size each one for the mode
+    // that will dominate on that connection
+    let modes = self.expected_modes(&connections);
+
+    // Create processors
+    for (i, (connection, processor_receiver)) in
+      connections.into_iter().zip(receivers).enumerate()
+    {
+      let channel_id = connection.id;
+      let buffer_size = self.config.parser_buffer_size_for(modes[i]);
 
       // Create message processor
-      let (mut processor, output_receiver) = MessageProcessor::new(
-        channel_id,
-        processor_receiver,
-        self.config.parser_buffer_size,
-      );
+      // Receivers are handed out from the sender, so no idle one holds
+      // messages against the buffer budget
+      let (mut processor, _) =
+        MessageProcessor::new(channel_id, processor_receiver, buffer_size);
+      if let Some(cache) = &self.last_value_cache {
+        processor = processor.with_last_value_cache(Arc::clone(cache));
+      }
+      if let Some(core) = self.config.parser_core(i) {
+        processor = processor.with_core_affinity(core);
+      }
+      if self.config.coalesce_duplicate_ticks {
+        processor = processor.with_duplicate_coalescing();
+      }
+      processor = processor.with_buffer_budget(self.buffer_budget.clone());
 
       // Start processor if enabled
       if self.config.enable_dedicated_parsers {
-        processor.start();
+        processor.start_with_cancellation(self.cancel_token.child_token());
         log::info!("Started dedicated parser for connection {}", i);
       }
 
       self.connections.push(connection);
       self.processors.push(processor);
-      self.output_channels.push(output_receiver);
     }
 
-    // Start health monitoring
-    if self.config.health_check_interval.as_secs() > 0 {
-      let connection_stats: Vec<Arc<RwLock<ConnectionStats>>> = self
-        .connections
-        .iter()
-        .map(|c| Arc::clone(&c.stats))
-        .collect();
-
-      let mut health_monitor =
-        HealthMonitor::new(connection_stats, self.config.health_check_interval);
-      health_monitor.start();
-      self.health_monitor = Some(health_monitor);
-
+    // Health monitor always backs get_stats/get_health; only the periodic
+    // logging loop depends on the interval
+    let connection_stats: Vec<Arc<RwLock<ConnectionStats>>> = self
+      .connections
+      .iter()
+      .map(|c| Arc::clone(&c.stats))
+      .collect();
+    let mut health_monitor =
+      HealthMonitor::new(connection_stats, self.config.health_check_interval)
+        .with_minimum_healthy(
+          self.config.min_healthy_connections,
+          self.health_events.clone(),
+        );
+    if !self.config.health_check_interval.is_zero() {
+      health_monitor.start_with_cancellation(self.cancel_token.child_token());
       log::info!("Started health monitor");
     }
+    self.health_monitor = Some(health_monitor);
+    if let Some(check) = self.prune_check() {
+      if self.config.enable_dedicated_parsers && !self.raw_only {
+        spawn_prune_timer(check, self.cancel_token.child_token());
+        log::info!("Started inactive symbol pruning");
+      }
+    }
 
     log::info!(
       "KiteTickerManager started successfully with {} connections",
       self.connections.len()
     );
 
+    self.restore_state().await?;
+
+    for (tokens, mode) in std::mem::take(&mut self.pending_subscriptions) {
+      self.subscribe_symbols(&tokens, Some(mode)).await?;
+    }
+
     Ok(())
   }
 
+  /// Per connection, the mode most of the tokens queued before `start()`
+  /// will use there, else `default_mode`
+  ///
+  /// The queued subscriptions are placed with a copy of the router, the way
+  /// they will be placed once the connections are up.
+  fn expected_modes(&self, connections: &[ManagedConnection]) -> Vec<Mode> {
+    let mut counts = vec![Vec::<(Mode, usize)>::new(); connections.len()];
+    let mut router = match self.router.lock() {
+      Ok(router) => router
+        .clone_router()
+        .unwrap_or_else(|| Box::new(LeastLoadedRouter)),
+      Err(_) => Box::new(LeastLoadedRouter),
+    };
+    let mut batch: HashMap<ChannelId, Vec<u32>> = HashMap::new();
+    for (tokens, mode) in &self.pending_subscriptions {
+      let before: HashMap<ChannelId, usize> =
+        batch.iter().map(|(&c, t)| (c, t.len())).collect();
+      // Tokens that will not fit fail the real subscribe; stop planning
+      let Ok(placed) =
+        self.route_onto(connections, router.as_mut(), tokens, batch.clone())
+      else {
+        break;
+      };
+      batch = placed;
+      for (channel, placed) in &batch {
+        let added = placed.len() - before.get(channel).copied().unwrap_or(0);
+        let counts = &mut counts[channel.to_index()];
+        match counts.iter_mut().find(|(m, _)| m == mode) {
+          Some((_, n)) => *n += added,
+          None => counts.push((*mode, added)),
+        }
+      }
+    }
+    counts
+      .into_iter()
+      .map(|counts| {
+        counts
+          .into_iter()
+          .filter(|&(_, n)| n > 0)
+          .max_by_key(|&(_, n)| n)
+          .map_or(self.config.default_mode, |(mode, _)| mode)
+      })
+      .collect()
+  }
+
+  /// Resubscribe everything recorded in the state file, if one is configured
+  async fn restore_state(&mut self) -> Result<(), String> {
+    let Some(path) = self.state_file.clone() else {
+      return Ok(());
+    };
+    let state = SubscriptionState::load(&path).await;
+    if state.subscriptions.is_empty() {
+      return Ok(());
+    }
+    log::info!(
+      "Restoring {} subscriptions from {}",
+      state.subscriptions.len(),
+      path.display()
+    );
+    // Subscribing persists after every call; hold the file back until the
+    // whole set is restored so a failure cannot leave half of it on disk
+    self.state_file = None;
+    let mut result = Ok(());
+    for (mode, tokens) in state.tokens_by_mode() {
+      result = self.subscribe_symbols(&tokens, Some(mode)).await;
+      if result.is_err() {
+        break;
+      }
+    }
+    self.state_file = Some(path);
+    if result.is_ok() {
+      self.persist_state().await;
+    }
+    Ok(result?)
+  }
+
+  /// Append an entry to the bounded operation log
+  fn record_operation(
+    &mut self,
+    op: SubscriptionOpKind,
+    tokens: Vec<u32>,
+    mode: Option<Mode>,
+    channel: ChannelId,
+  ) {
+    let capacity = self.config.operation_log_capacity;
+    if capacity == 0 {
+      return;
+    }
+    while self.operation_log.len() >= capacity {
+      self.operation_log.pop_front();
+    }
+    self.operation_log.push_back(SubscriptionOp {
+      timestamp: std::time::SystemTime::now(),
+      op,
+      tokens,
+      mode,
+      channel,
+    });
+  }
+
+  /// Write current subscriptions to the state file, if one is configured
+  async fn persist_state(&self) {
+    let Some(path) = self.state_file.as_ref() else {
+      return;
+    };
+    let _guard = self.state_lock.lock().await;
+    let state = SubscriptionState::from_map(&self.get_subscriptions());
+    if let Err(e) = state.save(path).await {
+      log::warn!("Failed to persist subscription state: {}", e);
+    }
+  }
+
+  /// JSON commands that `subscribe_symbols(tokens, mode)` would send,
+  /// without sending anything
+  ///
+  /// Already-subscribed tokens are skipped. The remaining ones are placed by
+  /// a copy of the router (see [`SymbolRouter::clone_router`]) against the
+  /// current load and per-connection caps, and each connection that gets
+  /// tokens contributes a `subscribe` + `mode` pair, in connection order.
+  /// Fails where `subscribe_symbols` would, e.g. when the tokens do not fit.
+  pub fn preview_subscribe(
+    &self,
+    tokens: &[u32],
+    mode: Option<Mode>,
+  ) -> Result<Vec<String>, KiteError> {
+    let mode = mode.unwrap_or(self.config.default_mode);
+    let mut router = self
+      .router
+      .lock()
+      .map_err(|_| "Symbol router lock poisoned".to_string())?
+      .clone_router()
+      .unwrap_or_else(|| Box::new(LeastLoadedRouter));
+    let mut batch: Vec<(ChannelId, Vec<u32>)> = self
+      .route_batch(router.as_mut(), tokens)?
+      .into_iter()
+      .collect();
+    batch.sort_unstable_by_key(|(channel, _)| channel.to_index());
+    Ok(
+      batch
+        .into_iter()
+        .flat_map(|(_, chunk)| {
+          [
+            Request::subscribe(&chunk).to_json_string(),
+            Request::mode(mode, &chunk).to_json_string(),
+          ]
+        })
+        .collect(),
+    )
+  }
+
   /// Subscribe to symbols using round-robin distribution
+  ///
+  /// Called before [`start()`](Self::start), the subscription is queued and
+  /// applied once the connections are up.
   pub async fn subscribe_symbols(
     &mut self,
     symbols: &[u32],
     mode: Option<Mode>,
-  ) -> Result<(), String> {
+  ) -> Result<(), KiteError> {
+    self.subscribe_symbols_assigned(symbols, mode).await?;
+    Ok(())
+  }
+
+  /// Subscribe by trading symbol, e.g. `[("RELIANCE", Exchange::NSE)]`
+  ///
+  /// Symbols are resolved with the configured [`InstrumentDirectory`];
+  /// nothing is subscribed if any of them is unknown.
+  pub async fn subscribe_by_symbol(
+    &mut self,
+    symbols: &[(&str, Exchange)],
+    mode: Option<Mode>,
+  ) -> Result<(), KiteError> {
+    let Some(instruments) = &self.instruments else {
+      return Err(KiteError::Other(
+        "No instrument directory configured; use instrument_directory"
+          .to_string(),
+      ));
+    };
+    let tokens = instruments.resolve(symbols)?;
+    self.subscribe_symbols(&tokens, mode).await
+  }
+
+  /// Subscribe like [`subscribe_symbols`](Self::subscribe_symbols), then
+  /// wait until every token is confirmed live.
+  ///
+  /// A token is confirmed by a packet of it in any mode, including packets
+  /// held back during the mode change. Once `subscribe_confirm_grace` has
+  /// elapsed, a token whose connection has sent any frame since the
+  /// subscribe (a heartbeat, say) is confirmed too, so illiquid tokens do
+  /// not fail it. Catches subscriptions that
+  /// were sent but never went live, e.g. a bad token or a connection that
+  /// silently dropped the request. On timeout it fails with
+  /// [`KiteError::Timeout`] listing the unconfirmed tokens by connection;
+  /// the subscriptions themselves are kept. Needs a started manager.
+  pub async fn subscribe_symbols_confirmed(
+    &mut self,
+    symbols: &[u32],
+    mode: Option<Mode>,
+    timeout: Duration,
+  ) -> Result<(), KiteError> {
+    self.ensure_running()?;
+    if self.connections.is_empty() {
+      return Err(KiteError::Other(
+        "Confirming subscriptions needs a started manager".to_string(),
+      ));
+    }
+    // Listen before subscribing so the first frames cannot be missed
+    let mut frames =
+      stream::select_all(self.connections.iter().filter_map(|connection| {
+        let id = connection.id;
+        let frames = connection.ticker.as_ref()?.subscribe_raw_frames();
+        Some(BroadcastStream::new(frames).map(move |frame| (id, frame)))
+      }));
+    let started = tokio::time::Instant::now();
+    self.subscribe_symbols(symbols, mode).await?;
+
+    // Tokens dropped by `allowed_tokens` were never sent
+    let mut pending: HashMap<u32, ChannelId> = symbols
+      .iter()
+      .filter_map(|t| Some((*t, *self.mapping().get(t)?)))
+      .collect();
+    let grace = started + self.config.subscribe_confirm_grace;
+    let deadline = started + timeout;
+    let mut alive: HashSet<ChannelId> = HashSet::new();
+    while !pending.is_empty() {
+      let now = tokio::time::Instant::now();
+      if now >= grace {
+        pending.retain(|_, channel| !alive.contains(channel));
+      }
+      if pending.is_empty() || now >= deadline {
+        break;
+      }
+      let wake = if now < grace {
+        grace.min(deadline)
+      } else {
+        deadline
+      };
+      match tokio::time::timeout_at(wake, frames.next()).await {
+        Ok(Some((channel, Ok(frame)))) => {
+          alive.insert(channel);
+          for token in crate::parser::packet_tokens(&frame) {
+            pending.remove(&token);
+          }
+        }
+        // Frames skipped while lagging still prove the socket is live
+        Ok(Some((channel, Err(_)))) => {
+          alive.insert(channel);
+        }
+        Ok(None) => break,
+        Err(_) => {}
+      }
+    }
+    if pending.is_empty() {
+      return Ok(());
+    }
+
+    let mut unconfirmed: HashMap<ChannelId, Vec<u32>> = HashMap::new();
+    for (token, channel) in pending {
+      unconfirmed.entry(channel).or_default().push(token);
+    }
+    for tokens in unconfirmed.values_mut() {
+      tokens.sort_unstable();
+    }
+    let error = KiteError::Timeout { unconfirmed };
+    log::warn!("Subscriptions unconfirmed after {:?}: {}", timeout, error);
+    Err(error)
+  }
+
+  /// Subscribe like [`subscribe_symbols`](Self::subscribe_symbols), returning
+  /// the connection each newly subscribed token was assigned to.
+  ///
+  /// Tokens that were already subscribed, or that are missing from
+  /// `allowed_tokens`, are not included in the map.
+  pub async fn subscribe_symbols_assigned(
+    &mut self,
+    symbols: &[u32],
+    mode: Option<Mode>,
+  ) -> Result<HashMap<u32, ChannelId>, KiteError> {
+    self.ensure_running()?;
+    self.apply_pruned().await;
+    let started = (!self.connections.is_empty()).then(Instant::now);
+    let result = self.do_subscribe(symbols, mode).await;
+    if let Some(started) = started {
+      self
+        .op_timings
+        .record(SubscriptionOpKind::Subscribe, started.elapsed());
+    }
+    Ok(result?)
+  }
+
+  async fn do_subscribe(
+    &mut self,
+    symbols: &[u32],
+    mode: Option<Mode>,
+  ) -> Result<HashMap<u32, ChannelId>, String> {
     let mode = mode.unwrap_or(self.config.default_mode);
+    let allowed_storage;
+    let symbols = match &self.config.allowed_tokens {
+      Some(allowed) => {
+        let (ok, rejected): (Vec<u32>, Vec<u32>) =
+          symbols.iter().partition(|t| allowed.contains(t));
+        if !rejected.is_empty() {
+          log::warn!(
+            "Dropping {} tokens not in allowed_tokens: {:?}",
+            rejected.len(),
+            rejected
+          );
+        }
+        allowed_storage = ok;
+        &allowed_storage[..]
+      }
+      None => symbols,
+    };
+
+    if self.connections.is_empty() {
+      // Not started yet: apply these at the end of `start()`
+      log::debug!("Queueing {} symbols until start()", symbols.len());
+      self.pending_subscriptions.push((symbols.to_vec(), mode));
+      return Ok(HashMap::new());
+    }
 
     log::info!(
       "Subscribing to {} symbols with mode: {:?}",
@@ -299,99 +1012,520 @@ impl KiteTickerManager {
       mode
     );
 
-    // Group symbols by connection using round-robin
-    let mut connection_symbols: HashMap<ChannelId, Vec<u32>> = HashMap::new();
-
-    for &symbol in symbols {
-      // Skip if already subscribed
-      if self.symbol_mapping.contains_key(&symbol) {
-        log::debug!("Symbol {} already subscribed", symbol);
-        continue;
+    // Group symbols by connection as chosen by the router
+    let connection_symbols = {
+      let mut router = self
+        .router
+        .lock()
+        .map_err(|_| "Symbol router lock poisoned".to_string())?;
+      self.route_batch(&mut *router, symbols)?
+    };
+    let now = Instant::now();
+    for (&connection_id, tokens) in &connection_symbols {
+      for &symbol in tokens {
+        self.mapping_mut().insert(symbol, connection_id);
       }
-
-      // Find connection with available capacity
-      let connection_id = self.find_available_connection()?;
-
-      // Add to mapping
-      self.symbol_mapping.insert(symbol, connection_id);
-      connection_symbols
-        .entry(connection_id)
-        .or_default()
-        .push(symbol);
     }
+    self
+      .subscribed_at
+      .write()
+      .unwrap_or_else(|e| e.into_inner())
+      .extend(
+        connection_symbols
+          .values()
+          .flat_map(|tokens| tokens.iter().map(|&t| (t, now))),
+      );
 
-    // Subscribe symbols on each connection
-    for (connection_id, symbols) in connection_symbols {
-      let connection = &mut self.connections[connection_id.to_index()];
-      let mode_clone = mode; // Clone for each connection
+    let assigned: HashMap<u32, ChannelId> = connection_symbols
+      .iter()
+      .flat_map(|(&id, tokens)| tokens.iter().map(move |&t| (t, id)))
+      .collect();
 
+    // Subscribe symbols on each connection
+    let mut batches = connection_symbols.into_iter();
+    while let Some((connection_id, symbols)) = batches.next() {
       if !symbols.is_empty() {
-        // Use dynamic subscription if already has symbols, otherwise initial setup
-        if connection.subscribed_symbols.is_empty() {
-          // First-time subscription on this connection: create subscriber
-          connection
-            .subscribe_symbols(&symbols, mode_clone)
-            .await
-            .map_err(|e| {
-              format!(
-                "Failed to subscribe on connection {:?}: {}",
-                connection_id, e
-              )
-            })?;
-
-          // IMPORTANT: Start forwarding messages from the subscriber to the processor
-          connection.start_message_processing().await.map_err(|e| {
-            format!(
-              "Failed to start message processing on connection {:?}: {}",
-              connection_id, e
-            )
-          })?;
-        } else {
-          connection
-            .add_symbols(&symbols, mode_clone)
-            .await
-            .map_err(|e| {
-              format!(
-                "Failed to add symbols on connection {:?}: {}",
-                connection_id, e
-              )
-            })?;
+        if let Err(e) = self
+          .subscribe_on_connection(connection_id, &symbols, mode)
+          .await
+        {
+          // Forget tokens that never reached a connection so the mapping
+          // keeps matching each connection's subscribed symbols
+          let unsent: Vec<u32> =
+            batches.flat_map(|(_, tokens)| tokens).collect();
+          {
+            let mut subscribed_at = self
+              .subscribed_at
+              .write()
+              .unwrap_or_else(|e| e.into_inner());
+            for token in symbols.iter().chain(&unsent) {
+              self.mapping_mut().remove(token);
+              subscribed_at.remove(token);
+            }
+          }
+          // Batches sent before this one did subscribe
+          self.persist_state().await;
+          return Err(e);
         }
-
-        log::info!(
-          "Subscribed {} symbols on connection {:?}",
-          symbols.len(),
-          connection_id
+        self.emit_cached_snapshot(connection_id, &symbols).await;
+        self.record_operation(
+          SubscriptionOpKind::Subscribe,
+          symbols,
+          Some(mode),
+          connection_id,
         );
       }
     }
 
+    self.persist_state().await;
+
     log::info!("Successfully subscribed to {} new symbols", symbols.len());
+    Ok(assigned)
+  }
+
+  /// Subscribe `symbols` on a single connection, starting its message
+  /// processing on first use
+  async fn subscribe_on_connection(
+    &mut self,
+    connection_id: ChannelId,
+    symbols: &[u32],
+    mode: Mode,
+  ) -> Result<(), String> {
+    let connection = &mut self.connections[connection_id.to_index()];
+
+    // Mid-reconnect there is no socket to send to; the reconnect subscribes
+    // these together with the rest once it is back
+    if connection.is_reconnecting() {
+      connection.queue_symbols(symbols, mode).await;
+      return Ok(());
+    }
+
+    // Use dynamic subscription if already has symbols, otherwise initial setup
+    if connection.subscribed_symbols.is_empty() {
+      // First-time subscription on this connection: create subscriber
+      connection
+        .subscribe_symbols(symbols, mode)
+        .await
+        .map_err(|e| {
+          format!(
+            "Failed to subscribe on connection {:?}: {}",
+            connection_id, e
+          )
+        })?;
+
+      // IMPORTANT: Start forwarding messages from the subscriber to the processor
+      connection.start_message_processing().await.map_err(|e| {
+        format!(
+          "Failed to start message processing on connection {:?}: {}",
+          connection_id, e
+        )
+      })?;
+    } else {
+      connection.add_symbols(symbols, mode).await.map_err(|e| {
+        format!(
+          "Failed to add symbols on connection {:?}: {}",
+          connection_id, e
+        )
+      })?;
+    }
+
+    log::info!(
+      "Subscribed {} symbols on connection {:?}",
+      symbols.len(),
+      connection_id
+    );
     Ok(())
   }
 
-  /// Find connection with available capacity using round-robin
-  fn find_available_connection(&mut self) -> Result<ChannelId, String> {
-    let _start_index = self.next_connection_index;
+  /// Re-establish a dropped connection and resubscribe its symbols
+  ///
+  /// Only the socket is replaced: the connection's parser and output
+  /// channel are kept, so receivers from [`get_channel`](Self::get_channel)
+  /// or [`get_all_channels`](Self::get_all_channels) keep delivering ticks
+  /// once the new socket is up.
+  ///
+  /// Retries up to `max_reconnect_attempts` times, `reconnect_delay` apart.
+  /// Failures count towards the reconnect circuit breaker: once it opens,
+  /// a [`TickerMessage::Error`] is published on the connection's channel and
+  /// reconnects on every connection are refused until the cooldown ends,
+  /// after which a single probe attempt decides whether to resume.
+  pub async fn reconnect(
+    &mut self,
+    channel_id: ChannelId,
+  ) -> Result<(), KiteError> {
+    self.ensure_running()?;
+    self.apply_pruned().await;
+    let result = self.do_reconnect(channel_id).await;
+    if let Err(e) = &result {
+      self.lifecycle_log.record(
+        channel_id,
+        LifecycleEventKind::ReconnectFailed,
+        e.clone(),
+      );
+    }
+    Ok(result?)
+  }
 
-    // Try round-robin allocation
-    for _ in 0..self.config.max_connections {
-      let connection = &self.connections[self.next_connection_index];
+  async fn do_reconnect(
+    &mut self,
+    channel_id: ChannelId,
+  ) -> Result<(), String> {
+    let index = channel_id.to_index();
+    if index >= self.connections.len() {
+      return Err(format!("Connection {:?} not found", channel_id));
+    }
+    if self.connections[index]
+      .throttled
+      .swap(false, Ordering::Relaxed)
+    {
+      self.breaker.trip_for(self.config.throttle_cooldown);
+      return Err(format!(
+        "Connection {:?} was throttled by Kite; pausing reconnects for {:?}",
+        channel_id, self.config.throttle_cooldown
+      ));
+    }
+    let attempts = self.config.max_reconnect_attempts.max(1);
+    let mut last_error = String::new();
+    for attempt in 1..=attempts {
+      if !self.breaker.allow_attempt() {
+        return Err(format!(
+          "Reconnect circuit breaker open; retry in {:?}",
+          self.breaker.remaining_cooldown()
+        ));
+      }
+      match self.try_reconnect(channel_id).await {
+        Ok(()) => {
+          self.breaker.record_success();
+          log::info!("Reconnected connection {:?}", channel_id);
+          self.lifecycle_log.record(
+            channel_id,
+            LifecycleEventKind::Reconnected,
+            format!("attempt {}", attempt),
+          );
+          return Ok(());
+        }
+        Err(e) if self.connections[index].handshake_status == Some(429) => {
+          // Handshake rejected with Too Many Requests
+          self.breaker.trip_for(self.config.throttle_cooldown);
+          let message = format!(
+            "Reconnect throttled by Kite ({}); pausing reconnects for {:?}",
+            e, self.config.throttle_cooldown
+          );
+          log::error!("{}", message);
+          let _ = self.connections[index]
+            .message_sender
+            .send(TickerMessage::Throttled(message.clone()));
+          return Err(message);
+        }
+        Err(e) => {
+          log::warn!(
+            "Reconnect attempt {}/{} for connection {:?} failed: {}",
+            attempt,
+            attempts,
+            channel_id,
+            e
+          );
+          last_error = e;
+        }
+      }
+      if self.breaker.record_failure() {
+        let message = format!(
+          "Reconnect circuit breaker opened; pausing reconnects for {:?}",
+          self.config.circuit_breaker_cooldown
+        );
+        log::error!("{}", message);
+        let _ = self.connections[index]
+          .message_sender
+          .send(TickerMessage::Error(message.clone()));
+        return Err(message);
+      }
+      if attempt < attempts {
+        tokio::time::sleep(self.config.reconnect_delay).await;
+      }
+    }
+    Err(last_error)
+  }
+
+  /// One reconnect attempt: replace the socket and restore subscriptions
+  async fn try_reconnect(
+    &mut self,
+    channel_id: ChannelId,
+  ) -> Result<(), String> {
+    // Pruned symbols must not be subscribed again
+    self.apply_pruned().await;
+    let connection = &mut self.connections[channel_id.to_index()];
+    connection.reset(self.cancel_token.child_token()).await;
+    connection.reconnect_socket(&self.config).await?;
+    connection.resubscribe_all().await
+  }
+
+  /// Move every symbol off `channel_id` onto the other connections, keeping
+  /// each symbol's mode, and leave `channel_id` empty for maintenance.
+  ///
+  /// Symbols are subscribed on their new connection before being removed
+  /// from the drained one, so no updates are missed. Fails without changing
+  /// anything if the other connections lack capacity. If a subscribe or the
+  /// final unsubscribe fails, the moves already made are undone so every
+  /// symbol stays on `channel_id`; undoing is best effort and logs a warning
+  /// for any target that could not be unsubscribed.
+  pub async fn drain_connection(
+    &mut self,
+    channel_id: ChannelId,
+  ) -> Result<(), KiteError> {
+    self.ensure_running()?;
+    self.apply_pruned().await;
+    Ok(self.do_drain(channel_id).await?)
+  }
+
+  async fn do_drain(&mut self, channel_id: ChannelId) -> Result<(), String> {
+    let index = channel_id.to_index();
+    let source = self
+      .connections
+      .get(index)
+      .ok_or_else(|| format!("Connection {:?} not found", channel_id))?;
+    let mut symbols: Vec<(u32, Mode)> = source
+      .subscribed_symbols
+      .iter()
+      .map(|(&symbol, &mode)| (symbol, mode))
+      .collect();
+    if symbols.is_empty() {
+      return Ok(());
+    }
+    symbols.sort_unstable_by_key(|&(symbol, _)| symbol);
+
+    // Plan every move up front, filling the least loaded connection first
+    let mut load: Vec<usize> =
+      self.connections.iter().map(|c| c.symbol_count()).collect();
+    let mut moves: Vec<(ChannelId, Mode, Vec<u32>)> = Vec::new();
+    for &(symbol, mode) in &symbols {
+      let target = self
+        .connections
+        .iter()
+        .filter(|c| {
+          let i = c.id.to_index();
+          i != index
+            && c.ticker.is_some()
+            && load[i] < self.config.symbol_cap(i)
+        })
+        .min_by_key(|c| load[c.id.to_index()])
+        .map(|c| c.id)
+        .ok_or_else(|| {
+          format!(
+            "Not enough capacity on other connections to drain {:?}",
+            channel_id
+          )
+        })?;
+      load[target.to_index()] += 1;
+      match moves
+        .iter_mut()
+        .find(|(c, m, _)| *c == target && *m == mode)
+      {
+        Some((_, _, tokens)) => tokens.push(symbol),
+        None => moves.push((target, mode, vec![symbol])),
+      }
+    }
 
-      if connection
-        .can_accept_symbols(1, self.config.max_symbols_per_connection)
+    let mut applied: Vec<(ChannelId, Mode, Vec<u32>)> = Vec::new();
+    for (target, mode, tokens) in moves {
+      if let Err(e) = self.subscribe_on_connection(target, &tokens, mode).await
       {
-        let channel_id = connection.id;
-        self.next_connection_index =
-          (self.next_connection_index + 1) % self.config.max_connections;
-        return Ok(channel_id);
+        self.undo_drain_moves(channel_id, &applied).await;
+        return Err(e);
+      }
+      for &token in &tokens {
+        self.mapping_mut().insert(token, target);
+      }
+      applied.push((target, mode, tokens));
+    }
+
+    let drained: Vec<u32> = symbols.into_iter().map(|(s, _)| s).collect();
+    if let Err(e) = self.connections[index].remove_symbols(&drained).await {
+      self.undo_drain_moves(channel_id, &applied).await;
+      return Err(format!(
+        "Failed to drain connection {:?}: {}",
+        channel_id, e
+      ));
+    }
+    for (target, mode, tokens) in applied {
+      self.record_operation(
+        SubscriptionOpKind::Subscribe,
+        tokens,
+        Some(mode),
+        target,
+      );
+    }
+    log::info!(
+      "Drained {} symbols from connection {:?}",
+      drained.len(),
+      channel_id
+    );
+    self.record_operation(
+      SubscriptionOpKind::Unsubscribe,
+      drained,
+      None,
+      channel_id,
+    );
+    self.persist_state().await;
+    Ok(())
+  }
+
+  /// Take back the subscriptions a failed drain made on other connections
+  /// and map the symbols to `source` again
+  async fn undo_drain_moves(
+    &mut self,
+    source: ChannelId,
+    applied: &[(ChannelId, Mode, Vec<u32>)],
+  ) {
+    for (target, _, tokens) in applied {
+      let connection = &mut self.connections[target.to_index()];
+      if let Err(e) = connection.remove_symbols(tokens).await {
+        log::warn!(
+          "Could not undo drain of {} symbols onto {:?}: {}",
+          tokens.len(),
+          target,
+          e
+        );
       }
+      for &token in tokens {
+        self.mapping_mut().insert(token, source);
+      }
+    }
+  }
 
-      self.next_connection_index =
-        (self.next_connection_index + 1) % self.config.max_connections;
+  /// Replay cached ticks for `symbols` on the connection's output channel
+  async fn emit_cached_snapshot(&self, channel_id: ChannelId, symbols: &[u32]) {
+    let Some(cache) = &self.last_value_cache else {
+      return;
+    };
+    let ticks: Vec<TickMessage> = {
+      let cache = cache.read().await;
+      symbols
+        .iter()
+        .filter_map(|t| cache.get(t).cloned())
+        .collect()
+    };
+    if ticks.is_empty() {
+      return;
+    }
+    if let Some(processor) = self.processors.get(channel_id.to_index()) {
+      log::debug!(
+        "Replaying {} cached ticks on connection {:?}",
+        ticks.len(),
+        channel_id
+      );
+      let _ = processor.output_sender.send(TickerMessage::Snapshot(ticks));
     }
+  }
 
-    Err("All connections are at capacity".to_string())
+  /// With `snapshot_on_get_channel`, hand out a receiver fed by a task that
+  /// first sends a `Snapshot` of the connection's cached ticks, then
+  /// forwards `receiver`; only this receiver sees the snapshot
+  fn with_snapshot(
+    &self,
+    channel_id: ChannelId,
+    mut receiver: broadcast::Receiver<TickerMessage>,
+  ) -> broadcast::Receiver<TickerMessage> {
+    if !self.config.snapshot_on_get_channel {
+      return receiver;
+    }
+    let (Some(cache), Some(connection)) = (
+      &self.last_value_cache,
+      self.connections.get(channel_id.to_index()),
+    ) else {
+      return receiver;
+    };
+    let cache = Arc::clone(cache);
+    let tokens: Vec<u32> =
+      connection.subscribed_symbols.keys().copied().collect();
+    let (tx, forwarded) = broadcast::channel(self.config.parser_buffer_size);
+    let cancel = self.cancel_token.child_token();
+    tokio::spawn(async move {
+      let ticks: Vec<TickMessage> = {
+        let cache = cache.read().await;
+        tokens
+          .iter()
+          .filter_map(|t| cache.get(t).cloned())
+          .collect()
+      };
+      if !ticks.is_empty() {
+        let _ = tx.send(TickerMessage::Snapshot(ticks));
+      }
+      loop {
+        let message = tokio::select! {
+          _ = cancel.cancelled() => break,
+          message = receiver.recv() => message,
+        };
+        match message {
+          // Stops once the caller drops its receiver
+          Ok(message) => {
+            if tx.send(message).is_err() {
+              break;
+            }
+          }
+          Err(broadcast::error::RecvError::Lagged(n)) => {
+            log::warn!(
+              "Snapshot receiver for {:?} lagged by {} messages",
+              channel_id,
+              n
+            );
+          }
+          Err(broadcast::error::RecvError::Closed) => break,
+        }
+      }
+    });
+    forwarded
+  }
+
+  /// Latest cached tick for `token`, if the last-value cache is enabled
+  ///
+  /// Processors publish to the cache once per frame without waiting for the
+  /// lock, so under contention it can trail the stream by a frame (at most
+  /// about a second).
+  pub async fn get_last_value(&self, token: u32) -> Option<TickMessage> {
+    let cache = self.last_value_cache.as_ref()?;
+    cache.read().await.get(&token).cloned()
+  }
+
+  /// Place the not yet subscribed `tokens` on connections with `router`,
+  /// as one batch; fails if any of them does not fit
+  fn route_batch(
+    &self,
+    router: &mut dyn SymbolRouter,
+    tokens: &[u32],
+  ) -> Result<HashMap<ChannelId, Vec<u32>>, String> {
+    self.route_onto(&self.connections, router, tokens, HashMap::new())
+  }
+
+  /// [`route_batch`](Self::route_batch) over `connections`, on top of the
+  /// tokens already placed in `batch`
+  fn route_onto(
+    &self,
+    connections: &[ManagedConnection],
+    router: &mut dyn SymbolRouter,
+    tokens: &[u32],
+    mut batch: HashMap<ChannelId, Vec<u32>>,
+  ) -> Result<HashMap<ChannelId, Vec<u32>>, String> {
+    let mut seen: HashSet<u32> = batch.values().flatten().copied().collect();
+    for &token in tokens {
+      if self.mapping().contains_key(&token) || !seen.insert(token) {
+        log::debug!("Symbol {} already subscribed", token);
+        continue;
+      }
+      let view = DistributionView::new(connections, &batch, &self.config);
+      match router.route(token, &view) {
+        Some(channel) if view.has_capacity(channel) => {
+          batch.entry(channel).or_default().push(token)
+        }
+        Some(channel) => {
+          return Err(format!(
+            "Router chose {:?} for symbol {}, which has no capacity",
+            channel, token
+          ))
+        }
+        None => return Err("All connections are at capacity".to_string()),
+      }
+    }
+    Ok(batch)
   }
 
   /// Get output channel for a specific connection
@@ -399,28 +1533,136 @@ impl KiteTickerManager {
     &mut self,
     channel_id: ChannelId,
   ) -> Option<broadcast::Receiver<TickerMessage>> {
-    if channel_id.to_index() < self.output_channels.len() {
-      Some(self.output_channels[channel_id.to_index()].resubscribe())
-    } else {
-      None
+    self.warn_if_raw_only("get_channel");
+    let receiver = self
+      .processors
+      .get(channel_id.to_index())?
+      .output_sender
+      .subscribe();
+    Some(self.with_snapshot(channel_id, receiver))
+  }
+
+  /// Receiver for a connection's channel that can replay the last-value
+  /// cache after lagging; see [`ResyncReceiver`]. Needs
+  /// `enable_last_value_cache`.
+  pub fn get_resync_channel(
+    &mut self,
+    channel_id: ChannelId,
+  ) -> Result<ResyncReceiver, KiteError> {
+    let Some(cache) = &self.last_value_cache else {
+      return Err(KiteError::Other(
+        "Resync needs enable_last_value_cache".to_string(),
+      ));
+    };
+    let cache = Arc::clone(cache);
+    let tokens: Vec<u32> = self
+      .connections
+      .get(channel_id.to_index())
+      .map(|c| c.subscribed_symbols.keys().copied().collect())
+      .unwrap_or_default();
+    let receiver = self
+      .get_channel(channel_id)
+      .ok_or_else(|| format!("Connection {:?} not found", channel_id))?;
+    Ok(ResyncReceiver::new(channel_id, receiver, cache, tokens))
+  }
+
+  /// Forward the ticks of every connection to `sink`.
+  ///
+  /// Spawns one consumer per connection that awaits `sink.send` for each
+  /// `Ticks` batch; other messages (and priority-symbol ticks) are not
+  /// forwarded. The consumers stop with the manager. Call after `start()`.
+  pub fn attach_sink(&self, sink: Arc<dyn TickSink>) {
+    self.warn_if_raw_only("attach_sink");
+    for (i, processor) in self.processors.iter().enumerate() {
+      let Some(channel_id) = ChannelId::from_index(i) else {
+        continue;
+      };
+      let mut receiver = processor.output_sender.subscribe();
+      let sink = Arc::clone(&sink);
+      let cancel = self.cancel_token.child_token();
+      tokio::spawn(async move {
+        loop {
+          let message = tokio::select! {
+            _ = cancel.cancelled() => break,
+            message = receiver.recv() => message,
+          };
+          match message {
+            Ok(TickerMessage::Ticks(ticks)) => {
+              sink.send(channel_id, &ticks).await;
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(n)) => {
+              log::warn!(
+                "Tick sink for {:?} lagged by {} messages",
+                channel_id,
+                n
+              );
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+          }
+        }
+      });
+    }
+  }
+
+  /// Mark `tokens` as priority symbols, replacing the previous set.
+  ///
+  /// Once [`priority_channel`](Self::priority_channel) has been taken, their
+  /// ticks skip the per-connection processor and broadcast buffer and are
+  /// delivered only through it. Before that, or after its receiver is
+  /// dropped, they stay on the normal channels.
+  pub fn set_priority_symbols(&mut self, tokens: &[u32]) {
+    if let Ok(mut set) = self.priority_route.tokens.write() {
+      *set = tokens.iter().copied().collect();
     }
   }
 
+  /// Take the low-latency channel carrying ticks of priority symbols.
+  ///
+  /// Single consumer: returns `None` after the first call. Routing to it
+  /// starts with this call, so nothing queues up before there is a reader.
+  pub fn priority_channel(
+    &mut self,
+  ) -> Option<mpsc::UnboundedReceiver<TickMessage>> {
+    let receiver = self.priority_rx.take()?;
+    self.priority_route.active.store(true, Ordering::Relaxed);
+    Some(receiver)
+  }
+
   /// Get all output channels
+  ///
+  /// On a raw-only manager these never carry ticks (a warning is logged);
+  /// use [`get_all_raw_frame_channels`](Self::get_all_raw_frame_channels).
   pub fn get_all_channels(
     &mut self,
   ) -> Vec<(ChannelId, broadcast::Receiver<TickerMessage>)> {
+    self.warn_if_raw_only("get_all_channels");
     let mut channels = Vec::new();
 
-    for (i, channel) in self.output_channels.iter().enumerate() {
+    for (i, processor) in self.processors.iter().enumerate() {
       if let Some(channel_id) = ChannelId::from_index(i) {
-        channels.push((channel_id, channel.resubscribe()));
+        let receiver = processor.output_sender.subscribe();
+        let receiver = self.with_snapshot(channel_id, receiver);
+        channels.push((channel_id, receiver));
       }
     }
 
     channels
   }
 
+  /// Read-only access to a connection's underlying client, for
+  /// introspection such as `is_connected`, `receiver_count` or
+  /// `channel_capacity`. Returns None if the connection is not initialized.
+  pub fn connection_client(
+    &self,
+    channel_id: ChannelId,
+  ) -> Option<&crate::ticker::KiteTickerAsync> {
+    self
+      .connections
+      .get(channel_id.to_index())
+      .and_then(|mc| mc.ticker.as_ref())
+  }
+
   /// Get a raw frame receiver (bytes::Bytes per websocket frame) for a connection.
   /// Returns None if the connection is not initialized.
   pub fn get_raw_frame_channel(
@@ -463,22 +1705,198 @@ impl KiteTickerManager {
     out
   }
 
-  /// Get manager statistics
-  pub async fn get_stats(&self) -> Result<ManagerStats, String> {
+  /// Raw frames of all connections merged into one channel, tagged with the
+  /// connection they arrived on; handy for forwarding pipelines.
+  ///
+  /// Frames are forwarded by background tasks that stop when the receiver
+  /// is dropped or the manager stops. A consumer that falls behind loses
+  /// frames (logged) rather than stalling the connections.
+  pub fn get_raw_merged_channel(&self) -> mpsc::Receiver<(ChannelId, Bytes)> {
+    let (tx, rx) = mpsc::channel(self.config.connection_buffer_size.max(1));
+    for (channel, mut frames) in self.get_all_raw_frame_channels() {
+      let tx = tx.clone();
+      let cancel = self.cancel_token.clone();
+      tokio::spawn(async move {
+        loop {
+          let frame = tokio::select! {
+            _ = cancel.cancelled() => break,
+            frame = frames.recv() => frame,
+          };
+          match frame {
+            Ok(frame) => {
+              if tx.send((channel, frame)).await.is_err() {
+                break;
+              }
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+              log::warn!(
+                "Merged raw channel lagged on {:?}, skipped {} frames",
+                channel,
+                skipped
+              );
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+          }
+        }
+      });
+    }
+    rx
+  }
+
+  /// Startup health gate: wait until every connected socket delivers a
+  /// frame (a tick or Kite's heartbeat) and report the ones that stay
+  /// silent for `timeout` as [`KiteError::SilentConnections`]. Connections
+  /// that failed to start are not checked.
+  pub async fn verify_connections(
+    &self,
+    timeout: Duration,
+  ) -> Result<(), KiteError> {
+    self.ensure_running()?;
+    let checks = self.connections.iter().filter_map(|connection| {
+      let mut frames = connection.ticker.as_ref()?.watch_raw_frames();
+      let id = connection.id;
+      Some(async move {
+        let received = tokio::time::timeout(timeout, frames.changed())
+          .await
+          .is_ok_and(|changed| changed.is_ok());
+        (id, received)
+      })
+    });
+    let silent: Vec<ChannelId> = futures_util::future::join_all(checks)
+      .await
+      .into_iter()
+      .filter(|&(_, received)| !received)
+      .map(|(id, _)| id)
+      .collect();
+    if silent.is_empty() {
+      Ok(())
+    } else {
+      log::warn!("Silent connections after {:?}: {:?}", timeout, silent);
+      Err(KiteError::SilentConnections(silent))
+    }
+  }
+
+  /// Get manager statistics, including the rolling latency of the last
+  /// 100 calls of each subscription operation
+  pub async fn get_stats(&self) -> Result<ManagerStats, KiteError> {
     if let Some(health_monitor) = &self.health_monitor {
-      Ok(health_monitor.get_manager_stats().await)
+      let mut stats = health_monitor.get_manager_stats().await;
+      stats.subscription_latency = self.op_timings.summary();
+      stats.total_buffered_messages = self.buffer_budget.total();
+      Ok(stats)
     } else {
-      Err("Health monitor not available".to_string())
+      Err(KiteError::Other("Health monitor not available".to_string()))
     }
   }
 
   /// Get health summary
-  pub async fn get_health(&self) -> Result<HealthSummary, String> {
+  pub async fn get_health(&self) -> Result<HealthSummary, KiteError> {
     if let Some(health_monitor) = &self.health_monitor {
-      Ok(health_monitor.get_health_summary().await)
+      let mut summary = health_monitor.get_health_summary().await;
+      summary.circuit_breaker = self.breaker.state();
+      Ok(summary)
     } else {
-      Err("Health monitor not available".to_string())
+      Err(KiteError::Other("Health monitor not available".to_string()))
+    }
+  }
+
+  /// Coverage changes against `min_healthy_connections`, e.g. to halt
+  /// trading while [`HealthEvent::BelowMinimum`] is in effect.
+  ///
+  /// Evaluated by the periodic health check, so events arrive at most every
+  /// `health_check_interval` and never when it is zero. Subscribe before
+  /// `start()` to catch a shortfall found by the first check.
+  pub fn health_events(&self) -> broadcast::Receiver<HealthEvent> {
+    self.health_events.subscribe()
+  }
+
+  /// Masked API key (first 4 characters + `***`) to identify the account
+  /// without exposing the secret
+  pub fn api_key_fingerprint(&self) -> String {
+    api_key_fingerprint(&self.api_key)
+  }
+
+  /// Bundle config, stats, symbol distribution, health and the most recent
+  /// operations into one serializable snapshot for support requests
+  pub async fn diagnostics(&self) -> Diagnostics {
+    let mut connections = Vec::with_capacity(self.connections.len());
+    for connection in &self.connections {
+      connections.push(connection.stats.read().await.clone());
+    }
+    // Keep the dump small even with a large operation log
+    let skip = self.operation_log.len().saturating_sub(100);
+    Diagnostics {
+      config: self.config.clone(),
+      connection_count: self.connections.len(),
+      connections,
+      symbol_distribution: self.get_symbol_distribution(),
+      processor_stats: self.get_processor_stats().await,
+      health: self.get_health().await.ok(),
+      recent_operations: self
+        .operation_log
+        .iter()
+        .skip(skip)
+        .cloned()
+        .collect(),
+    }
+  }
+
+  /// Per-connection rows, totals and the busiest symbols in one struct, for
+  /// a TUI or web dashboard to poll.
+  ///
+  /// Tick counts come from the dedicated parsers, are refreshed about once
+  /// a second and leave out priority symbols.
+  pub async fn dashboard_snapshot(&self) -> DashboardSnapshot {
+    const TOP_SYMBOLS: usize = 10;
+
+    let mut snapshot = DashboardSnapshot {
+      uptime: self.start_time.elapsed(),
+      ..Default::default()
+    };
+    let mut ticks: HashMap<u32, u64> = HashMap::new();
+    for connection in &self.connections {
+      let stats = connection.stats.read().await.clone();
+      let processor = self.processors.get(connection.id.to_index());
+      let messages_per_second = match processor {
+        Some(p) => p.stats.read().await.messages_per_second,
+        None => 0.0,
+      };
+      if let Some(p) = processor {
+        for (&token, activity) in p.token_activity.read().await.iter() {
+          if self.mapping().contains_key(&token) {
+            *ticks.entry(token).or_default() += activity.ticks;
+          }
+        }
+      }
+
+      snapshot.healthy_connections += usize::from(stats.is_connected);
+      snapshot.total_symbols += stats.symbol_count;
+      snapshot.total_messages += stats.messages_received;
+      snapshot.total_errors += stats.errors_count;
+      snapshot.messages_per_second += messages_per_second;
+      snapshot.connections.push(DashboardConnection {
+        channel: connection.id,
+        healthy: stats.is_connected,
+        symbols: stats.symbol_count,
+        messages_received: stats.messages_received,
+        messages_per_second,
+        errors: stats.errors_count,
+        uptime: stats.connection_uptime,
+        last_error_age: stats.last_error.as_ref().map(|(at, _)| at.elapsed()),
+        last_error: stats.last_error.map(|(_, message)| message),
+      });
     }
+
+    let mut top: Vec<SymbolActivity> = ticks
+      .into_iter()
+      .map(|(token, ticks)| SymbolActivity { token, ticks })
+      .collect();
+    top.sort_unstable_by(|a, b| {
+      b.ticks.cmp(&a.ticks).then(a.token.cmp(&b.token))
+    });
+    top.truncate(TOP_SYMBOLS);
+    snapshot.top_symbols = top;
+    snapshot
   }
 
   /// Get processor statistics for all channels
@@ -486,41 +1904,266 @@ impl KiteTickerManager {
     let mut stats = Vec::new();
 
     for processor in &self.processors {
-      let processor_stats = processor.get_stats().await;
+      let processor_stats = self.processor_stats_with_modes(processor).await;
       stats.push((processor.channel_id, processor_stats));
     }
 
     stats
   }
 
+  /// Processor stats plus the mode breakdown of the matching connection
+  async fn processor_stats_with_modes(
+    &self,
+    processor: &MessageProcessor,
+  ) -> ProcessorStats {
+    let mut stats = processor.get_stats().await;
+    if let Some(connection) =
+      self.connections.get(processor.channel_id.to_index())
+    {
+      stats.symbols_by_mode =
+        ModeBreakdown::from_modes(connection.subscribed_symbols.values());
+    }
+    stats
+  }
+
+  /// Get processor statistics for a single channel
+  pub async fn get_processor_stats_for(
+    &self,
+    channel_id: ChannelId,
+  ) -> Option<ProcessorStats> {
+    let processor = self.processors.get(channel_id.to_index())?;
+    Some(self.processor_stats_with_modes(processor).await)
+  }
+
+  /// Whether `token` is currently subscribed on any connection
+  pub fn is_subscribed(&self, token: u32) -> bool {
+    self.mapping().contains_key(&token)
+  }
+
+  /// Number of subscribed symbols across all connections
+  pub fn subscribed_count(&self) -> usize {
+    self.mapping().len()
+  }
+
   /// Get symbol distribution across connections
   pub fn get_symbol_distribution(&self) -> HashMap<ChannelId, Vec<u32>> {
     let mut distribution: HashMap<ChannelId, Vec<u32>> = HashMap::new();
 
-    for (&symbol, &channel_id) in &self.symbol_mapping {
+    for (&symbol, &channel_id) in self.mapping().iter() {
       distribution.entry(channel_id).or_default().push(symbol);
     }
 
     distribution
   }
 
+  /// Recent connects, closes, disconnects and reconnects of every
+  /// connection, oldest first, for diagnosing flapping connections.
+  ///
+  /// Bounded by `KiteManagerConfig::lifecycle_log_capacity`.
+  pub fn lifecycle_log(&self) -> Vec<LifecycleEvent> {
+    self.lifecycle_log.snapshot()
+  }
+
+  /// History of subscribe/unsubscribe/mode-change operations, oldest first.
+  ///
+  /// Bounded by `KiteManagerConfig::operation_log_capacity`.
+  pub fn operation_log(&self) -> &VecDeque<SubscriptionOp> {
+    &self.operation_log
+  }
+
+  /// Get every subscribed symbol together with its current mode
+  pub fn get_subscriptions(&self) -> HashMap<u32, Mode> {
+    let mut subscriptions = HashMap::with_capacity(self.mapping().len());
+    for connection in &self.connections {
+      subscriptions.extend(
+        connection
+          .subscribed_symbols
+          .iter()
+          .map(|(&symbol, &mode)| (symbol, mode)),
+      );
+    }
+    // Unsubscribed by the prune timer, but not yet dropped by the connection
+    let pruned = self.pruned.lock().unwrap_or_else(|e| e.into_inner());
+    subscriptions.retain(|symbol, _| !pruned.contains_key(symbol));
+    subscriptions
+  }
+
+  /// Time since `token` was subscribed, or `None` if it is not subscribed
+  pub fn get_subscription_age(&self, token: u32) -> Option<Duration> {
+    let subscribed_at =
+      self.subscribed_at.read().unwrap_or_else(|e| e.into_inner());
+    subscribed_at.get(&token).map(Instant::elapsed)
+  }
+
+  /// Unsubscribe every symbol subscribed longer than `max_age` ago.
+  ///
+  /// Returns the tokens that were unsubscribed.
+  pub async fn expire_older_than(
+    &mut self,
+    max_age: Duration,
+  ) -> Result<Vec<u32>, KiteError> {
+    self.ensure_running()?;
+    self.apply_pruned().await;
+    let mut expired: Vec<u32> = self
+      .subscribed_at
+      .read()
+      .unwrap_or_else(|e| e.into_inner())
+      .iter()
+      .filter(|(_, at)| at.elapsed() > max_age)
+      .map(|(&token, _)| token)
+      .collect();
+    if expired.is_empty() {
+      return Ok(expired);
+    }
+    expired.sort_unstable();
+    log::info!(
+      "Expiring {} symbols subscribed more than {:?} ago",
+      expired.len(),
+      max_age
+    );
+    self.unsubscribe_symbols(&expired).await?;
+    Ok(expired)
+  }
+
+  /// Prune tokens that have not ticked for `auto_prune_inactive` now,
+  /// returning them sorted.
+  ///
+  /// `start()` already runs this check on a timer; call it to prune
+  /// without waiting for the next check. A token is only pruned once the
+  /// session of its exchange (see `market_sessions`) has been open for the
+  /// whole grace period, so weekends, holidays and off-peak hours never
+  /// empty the manager. Tokens subscribed within the grace period and
+  /// priority symbols are kept. Pruned tokens are announced on
+  /// [`prune_events`](Self::prune_events), logged and recorded in the
+  /// operation log as an unsubscribe.
+  pub async fn prune_inactive(&mut self) -> Result<Vec<u32>, KiteError> {
+    self.ensure_running()?;
+    let Some(check) = self.prune_check() else {
+      return Ok(Vec::new());
+    };
+    if self.raw_only || !self.config.enable_dedicated_parsers {
+      return Err(KiteError::Other(
+        "Pruning inactive symbols needs parsed channels to see ticks"
+          .to_string(),
+      ));
+    }
+    let pruned = check.run(Utc::now()).await;
+    self.apply_pruned().await;
+    Ok(pruned)
+  }
+
+  /// Tokens pruned by `auto_prune_inactive`, one event per check that
+  /// found any. They are unsubscribed on their connection and removed from
+  /// the state file before the event is sent.
+  pub fn prune_events(&self) -> broadcast::Receiver<PruneEvent> {
+    self.prune_events.subscribe()
+  }
+
+  /// What a prune check reads, or `None` without `auto_prune_inactive`
+  fn prune_check(&self) -> Option<PruneCheck> {
+    Some(PruneCheck {
+      grace: self.config.auto_prune_inactive?,
+      sessions: self.config.market_sessions.clone(),
+      subscribed_at: Arc::clone(&self.subscribed_at),
+      activity: self
+        .processors
+        .iter()
+        .map(|p| Arc::clone(&p.token_activity))
+        .collect(),
+      priority: Arc::clone(&self.priority_route.tokens),
+      mapping: Arc::clone(&self.symbol_mapping),
+      commands: self
+        .connections
+        .iter()
+        .map(|c| Arc::clone(&c.cmd_tx))
+        .collect(),
+      state_file: self.state_file.clone(),
+      state_lock: Arc::clone(&self.state_lock),
+      pruned: Arc::clone(&self.pruned),
+      events: self.prune_events.clone(),
+    })
+  }
+
+  /// Token → connection map, shared with the prune timer
+  fn mapping(&self) -> std::sync::RwLockReadGuard<'_, HashMap<u32, ChannelId>> {
+    self
+      .symbol_mapping
+      .read()
+      .unwrap_or_else(|e| e.into_inner())
+  }
+
+  fn mapping_mut(
+    &self,
+  ) -> std::sync::RwLockWriteGuard<'_, HashMap<u32, ChannelId>> {
+    self
+      .symbol_mapping
+      .write()
+      .unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Drop the tokens the prune timer unsubscribed since the last call
+  /// from their connections, so a reconnect does not subscribe them again
+  async fn apply_pruned(&mut self) {
+    let pruned = std::mem::take(
+      &mut *self.pruned.lock().unwrap_or_else(|e| e.into_inner()),
+    );
+    let mut by_channel: HashMap<ChannelId, Vec<u32>> = HashMap::new();
+    for (token, channel) in pruned {
+      by_channel.entry(channel).or_default().push(token);
+    }
+    for (channel, mut tokens) in by_channel {
+      tokens.sort_unstable();
+      self.connections[channel.to_index()]
+        .forget_symbols(&tokens)
+        .await;
+      self.record_operation(
+        SubscriptionOpKind::Unsubscribe,
+        tokens,
+        None,
+        channel,
+      );
+    }
+  }
+
   /// Unsubscribe from symbols
   pub async fn unsubscribe_symbols(
     &mut self,
     symbols: &[u32],
-  ) -> Result<(), String> {
+  ) -> Result<(), KiteError> {
+    self.ensure_running()?;
+    self.apply_pruned().await;
+    let started = (!self.connections.is_empty()).then(Instant::now);
+    let result = self.do_unsubscribe(symbols).await;
+    if let Some(started) = started {
+      self
+        .op_timings
+        .record(SubscriptionOpKind::Unsubscribe, started.elapsed());
+    }
+    Ok(result?)
+  }
+
+  async fn do_unsubscribe(&mut self, symbols: &[u32]) -> Result<(), String> {
+    if self.connections.is_empty() {
+      for (tokens, _) in &mut self.pending_subscriptions {
+        tokens.retain(|t| !symbols.contains(t));
+      }
+      self
+        .pending_subscriptions
+        .retain(|(tokens, _)| !tokens.is_empty());
+      return Ok(());
+    }
+
     log::info!("Unsubscribing from {} symbols", symbols.len());
 
     // Group symbols by connection
     let mut connection_symbols: HashMap<ChannelId, Vec<u32>> = HashMap::new();
 
     for &symbol in symbols {
-      if let Some(&channel_id) = self.symbol_mapping.get(&symbol) {
+      if let Some(&channel_id) = self.mapping().get(&symbol) {
         connection_symbols
           .entry(channel_id)
           .or_default()
           .push(symbol);
-        self.symbol_mapping.remove(&symbol);
       } else {
         log::debug!("Symbol {} not found in subscriptions", symbol);
       }
@@ -537,15 +2180,34 @@ impl KiteTickerManager {
             channel_id, e
           )
         })?;
+        // Only forget the mapping once the connection dropped the symbols,
+        // so a failed batch stays consistent with `subscribed_symbols`
+        let mut subscribed_at = self
+          .subscribed_at
+          .write()
+          .unwrap_or_else(|e| e.into_inner());
+        for symbol in &symbols {
+          self.mapping_mut().remove(symbol);
+          subscribed_at.remove(symbol);
+        }
+        drop(subscribed_at);
 
         log::info!(
           "Unsubscribed {} symbols from connection {:?}",
           symbols.len(),
           channel_id
         );
+        self.record_operation(
+          SubscriptionOpKind::Unsubscribe,
+          symbols,
+          None,
+          channel_id,
+        );
       }
     }
 
+    self.persist_state().await;
+
     log::info!("Successfully unsubscribed from {} symbols", symbols.len());
     Ok(())
   }
@@ -555,6 +2217,21 @@ impl KiteTickerManager {
     &mut self,
     symbols: &[u32],
     mode: Mode,
+  ) -> Result<(), KiteError> {
+    self.ensure_running()?;
+    self.apply_pruned().await;
+    let started = Instant::now();
+    let result = self.do_change_mode(symbols, mode).await;
+    self
+      .op_timings
+      .record(SubscriptionOpKind::ModeChange, started.elapsed());
+    Ok(result?)
+  }
+
+  async fn do_change_mode(
+    &mut self,
+    symbols: &[u32],
+    mode: Mode,
   ) -> Result<(), String> {
     log::info!("Changing mode for {} symbols to {:?}", symbols.len(), mode);
 
@@ -562,7 +2239,7 @@ impl KiteTickerManager {
     let mut connection_symbols: HashMap<ChannelId, Vec<u32>> = HashMap::new();
 
     for &symbol in symbols {
-      if let Some(&channel_id) = self.symbol_mapping.get(&symbol) {
+      if let Some(&channel_id) = self.mapping().get(&symbol) {
         connection_symbols
           .entry(channel_id)
           .or_default()
@@ -579,19 +2256,43 @@ impl KiteTickerManager {
         continue;
       }
       // Send mode request directly via command sender if available
-      if let Some(ref cmd) = connection.cmd_tx {
+      if let Some(cmd) = connection.command_sender() {
+        // Hints first, so packets still in the old mode are held back
+        let previous: Vec<(u32, Option<Mode>)> = symbols
+          .iter()
+          .map(|&s| (s, connection.subscribed_symbols.insert(s, mode)))
+          .collect();
+        connection.sync_mode_hints();
         let mode_req = crate::models::Request::mode(mode, &symbols).to_string();
-        let _ = cmd.send(tokio_tungstenite::tungstenite::Message::Text(
-          mode_req.into(),
-        ));
-        for &s in &symbols {
-          connection.subscribed_symbols.insert(s, mode);
+        let sent = cmd
+          .send_async(tokio_tungstenite::tungstenite::Message::Text(
+            mode_req.into(),
+          ))
+          .await;
+        if let Err(e) = sent {
+          for (s, previous) in previous {
+            if let Some(previous) = previous {
+              connection.subscribed_symbols.insert(s, previous);
+            }
+          }
+          connection.sync_mode_hints();
+          // Connections handled before this one did change
+          self.persist_state().await;
+          return Err(format!(
+            "Failed to change mode on connection {:?}: {}",
+            channel_id, e
+          ));
         }
         log::info!(
           "Changed mode for {} symbols on connection {:?}",
           symbols.len(),
           channel_id
         );
+      } else if connection.is_reconnecting() {
+        // Applied when the reconnect resubscribes
+        for &s in &symbols {
+          connection.subscribed_symbols.insert(s, mode);
+        }
       } else if let Some(subscriber) = &mut connection.subscriber {
         // fallback (should normally have command sender)
         subscriber.set_mode(&symbols, mode).await.map_err(|e| {
@@ -603,16 +2304,29 @@ impl KiteTickerManager {
         for &s in &symbols {
           connection.subscribed_symbols.insert(s, mode);
         }
+        connection.sync_mode_hints();
       }
+      self.record_operation(
+        SubscriptionOpKind::ModeChange,
+        symbols,
+        Some(mode),
+        channel_id,
+      );
     }
 
+    self.persist_state().await;
+
     log::info!("Successfully changed mode for {} symbols", symbols.len());
     Ok(())
   }
 
   /// Stop the manager and all connections
-  pub async fn stop(&mut self) -> Result<(), String> {
+  ///
+  /// Afterwards every operation (subscribe, unsubscribe, change_mode,
+  /// reconnect, start, ...) fails with [`KiteError::Closed`].
+  pub async fn stop(&mut self) -> Result<(), KiteError> {
     log::info!("Stopping KiteTickerManager");
+    self.cancel_token.cancel();
 
     // Stop health monitor
     if let Some(health_monitor) = &mut self.health_monitor {
@@ -640,3 +2354,152 @@ impl KiteTickerManager {
     Ok(())
   }
 }
+
+/// Inputs of a prune check, shared with the `auto_prune_inactive` timer
+#[derive(Debug, Clone)]
+struct PruneCheck {
+  grace: Duration,
+  sessions: HashMap<Exchange, MarketSession>,
+  subscribed_at: Arc<std::sync::RwLock<HashMap<u32, Instant>>>,
+  activity: Vec<TokenActivityMap>,
+  priority: Arc<std::sync::RwLock<HashSet<u32>>>,
+  mapping: Arc<std::sync::RwLock<HashMap<u32, ChannelId>>>,
+  /// Command sender of each connection, by channel index
+  commands: Vec<CommandSlot>,
+  state_file: Option<PathBuf>,
+  state_lock: Arc<tokio::sync::Mutex<()>>,
+  pruned: Arc<std::sync::Mutex<HashMap<u32, ChannelId>>>,
+  events: broadcast::Sender<PruneEvent>,
+}
+
+impl PruneCheck {
+  /// Unsubscribe the tokens without ticks for the grace period on their
+  /// connections, drop them from the mapping and the state file and
+  /// announce them, returning them sorted
+  async fn run(&self, now: DateTime<Utc>) -> Vec<u32> {
+    let mut tokens = Vec::new();
+    for (channel, silent) in self.silent(now).await {
+      let Some(sender) = self.commands[channel.to_index()]
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+      else {
+        // Reconnecting: the next check tries again
+        continue;
+      };
+      let unsubscribe = Request::unsubscribe(&silent).to_string();
+      if let Err(e) = sender
+        .send_async(tokio_tungstenite::tungstenite::Message::Text(
+          unsubscribe.into(),
+        ))
+        .await
+      {
+        log::warn!("Failed to prune symbols on {:?}: {}", channel, e);
+        continue;
+      }
+      {
+        let mut mapping =
+          self.mapping.write().unwrap_or_else(|e| e.into_inner());
+        let mut subscribed_at = self
+          .subscribed_at
+          .write()
+          .unwrap_or_else(|e| e.into_inner());
+        let mut pruned = self.pruned.lock().unwrap_or_else(|e| e.into_inner());
+        for &token in &silent {
+          mapping.remove(&token);
+          subscribed_at.remove(&token);
+          pruned.insert(token, channel);
+        }
+      }
+      tokens.extend(silent);
+    }
+    if tokens.is_empty() {
+      return tokens;
+    }
+    tokens.sort_unstable();
+    self.forget_in_state_file(&tokens).await;
+    log::info!(
+      "Pruned {} symbols without ticks for {:?}: {:?}",
+      tokens.len(),
+      self.grace,
+      tokens
+    );
+    let _ = self.events.send(PruneEvent {
+      tokens: tokens.clone(),
+    });
+    tokens
+  }
+
+  /// Subscribed tokens without ticks for the grace period, by connection
+  async fn silent(&self, now: DateTime<Utc>) -> HashMap<ChannelId, Vec<u32>> {
+    let mut last_tick: HashMap<u32, Instant> = HashMap::new();
+    for activity in &self.activity {
+      for (&token, activity) in activity.read().await.iter() {
+        last_tick.insert(token, activity.last_tick);
+      }
+    }
+    // The segment is the low byte of the token
+    let session_open = |token: u32| {
+      let exchange = Exchange::from((token & 0xff) as usize);
+      self
+        .sessions
+        .get(&exchange)
+        .and_then(|session| session.open_for(now))
+        .is_some_and(|open| open >= self.grace)
+    };
+    let priority = self.priority.read().unwrap_or_else(|e| e.into_inner());
+    let mapping = self.mapping.read().unwrap_or_else(|e| e.into_inner());
+    let subscribed_at =
+      self.subscribed_at.read().unwrap_or_else(|e| e.into_inner());
+    let mut silent: HashMap<ChannelId, Vec<u32>> = HashMap::new();
+    for (token, at) in subscribed_at.iter() {
+      let Some(&channel) = mapping.get(token) else {
+        continue;
+      };
+      if at.elapsed() > self.grace
+        && !priority.contains(token)
+        && session_open(*token)
+        && last_tick
+          .get(token)
+          .is_none_or(|t| t.elapsed() > self.grace)
+      {
+        silent.entry(channel).or_default().push(*token);
+      }
+    }
+    for tokens in silent.values_mut() {
+      tokens.sort_unstable();
+    }
+    silent
+  }
+
+  /// Remove `tokens` from the state file, if one is configured
+  async fn forget_in_state_file(&self, tokens: &[u32]) {
+    let Some(path) = self.state_file.as_ref() else {
+      return;
+    };
+    let _guard = self.state_lock.lock().await;
+    let mut state = SubscriptionState::load(path).await;
+    state
+      .subscriptions
+      .retain(|entry| !tokens.contains(&entry.instrument_token));
+    if let Err(e) = state.save(path).await {
+      log::warn!("Failed to persist subscription state: {}", e);
+    }
+  }
+}
+
+/// Run `check` a few times per grace period until `cancel` fires
+fn spawn_prune_timer(check: PruneCheck, cancel: CancellationToken) {
+  let period =
+    (check.grace / 4).clamp(Duration::from_millis(10), Duration::from_secs(60));
+  tokio::spawn(async move {
+    let mut timer = tokio::time::interval(period);
+    loop {
+      tokio::select! {
+        _ = cancel.cancelled() => break,
+        _ = timer.tick() => {}
+      }
+      check.run(Utc::now()).await;
+    }
+  });
+}
@@ -1,13 +1,57 @@
-use crate::manager::{ChannelId, ConnectionStats, KiteManagerConfig};
-use crate::models::{Mode, TickerMessage};
-use crate::ticker::KiteTickerAsync;
-use std::collections::HashMap;
+use crate::manager::{
+  BufferBudget, ChannelId, ConnectionStats, KiteManagerConfig,
+  LifecycleEventKind, LifecycleLog,
+};
+use crate::models::{Mode, TickMessage, TickerMessage};
+use crate::ticker::{CommandSender, KiteTickerAsync, KiteTickerConfig};
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
 use tokio::task::JoinHandle;
 use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_util::sync::CancellationToken;
+
+/// Priority tokens and the low-latency channel their ticks are sent to
+#[derive(Debug, Clone)]
+pub(crate) struct PriorityRoute {
+  pub(crate) tokens: Arc<std::sync::RwLock<HashSet<u32>>>,
+  pub(crate) sender: mpsc::UnboundedSender<TickMessage>,
+  /// Set once the receiver has been handed out; until then (and after it is
+  /// dropped) priority ticks stay on the normal path instead of piling up
+  pub(crate) active: Arc<AtomicBool>,
+}
+
+impl PriorityRoute {
+  /// Send priority ticks to the dedicated channel, returning the rest
+  fn route(&self, ticks: Vec<TickMessage>) -> Vec<TickMessage> {
+    if !self.active.load(Ordering::Relaxed) {
+      return ticks;
+    }
+    let Ok(tokens) = self.tokens.read() else {
+      return ticks;
+    };
+    if tokens.is_empty() {
+      return ticks;
+    }
+    let (priority, mut rest): (Vec<_>, Vec<_>) = ticks
+      .into_iter()
+      .partition(|t| tokens.contains(&t.instrument_token));
+    for tick in priority {
+      if let Err(mpsc::error::SendError(tick)) = self.sender.send(tick) {
+        self.active.store(false, Ordering::Relaxed);
+        rest.push(tick);
+      }
+    }
+    rest
+  }
+}
+
+/// Command sender of a connection's current socket, replaced on every
+/// reconnect and shared with the `auto_prune_inactive` timer
+pub(crate) type CommandSlot = Arc<std::sync::RwLock<Option<CommandSender>>>;
 
 /// Represents a single WebSocket connection with its metadata
 #[derive(Debug)]
@@ -18,6 +62,10 @@ pub struct ManagedConnection {
   pub subscribed_symbols: HashMap<u32, Mode>,
   pub stats: Arc<RwLock<ConnectionStats>>,
   pub is_healthy: Arc<AtomicBool>,
+  /// Set when Kite sent a throttle notice; cleared by the next reconnect
+  pub(crate) throttled: Arc<AtomicBool>,
+  /// HTTP status of the last refused handshake (e.g. 429), if any
+  pub(crate) handshake_status: Option<u16>,
   pub last_ping: Arc<AtomicU64>, // Unix timestamp
   pub task_handle: Option<JoinHandle<()>>,
   // Background watcher to update last_ping on any inbound frame (including heartbeats)
@@ -26,10 +74,24 @@ pub struct ManagedConnection {
   // Store credentials for dynamic operations
   api_key: String,
   access_token: String,
-  pub(crate) cmd_tx:
-    Option<mpsc::UnboundedSender<tokio_tungstenite::tungstenite::Message>>,
+  // Raw-only flag of the last connect, reused when reconnecting
+  raw_only: bool,
+  // Set between `reset` and a completed reconnect; subscription changes are
+  // recorded in `subscribed_symbols` and sent once the socket is back
+  pub(crate) reconnecting: bool,
+  pub(crate) cmd_tx: CommandSlot,
   // Liveness threshold for heartbeats/frames
   heartbeat_liveness_threshold: Duration,
+  // Wait for a message before emitting `NoData` and checking liveness
+  receive_timeout: Duration,
+  // Cancelling stops the socket tasks, heartbeat watcher and processing loop
+  pub(crate) cancel_token: CancellationToken,
+  // Ticks for priority tokens skip the processor and go here instead
+  priority: Option<PriorityRoute>,
+  // Connects, closes and disconnects are recorded here when set
+  lifecycle: Option<LifecycleLog>,
+  // Ticks are shed instead of forwarded while this budget is exhausted
+  buffer_budget: Option<BufferBudget>,
 }
 
 impl ManagedConnection {
@@ -49,14 +111,77 @@ impl ManagedConnection {
       subscribed_symbols: HashMap::new(),
       stats: Arc::new(RwLock::new(stats)),
       is_healthy: Arc::new(AtomicBool::new(false)),
+      throttled: Arc::new(AtomicBool::new(false)),
+      handshake_status: None,
       last_ping: Arc::new(AtomicU64::new(0)),
       task_handle: None,
       heartbeat_handle: None,
       message_sender,
       api_key: String::new(),
       access_token: String::new(),
-      cmd_tx: None,
+      raw_only: false,
+      reconnecting: false,
+      cmd_tx: CommandSlot::default(),
       heartbeat_liveness_threshold: Duration::from_secs(10),
+      receive_timeout: Duration::from_secs(30),
+      cancel_token: CancellationToken::new(),
+      priority: None,
+      lifecycle: None,
+      buffer_budget: None,
+    }
+  }
+
+  /// Use `token` to cooperatively stop all background tasks of this connection
+  pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+    self.cancel_token = token;
+    self
+  }
+
+  /// Stop the socket tasks and processing loop, keeping the symbol list, so
+  /// the connection can be established again under `token`
+  pub(crate) async fn reset(&mut self, token: CancellationToken) {
+    self.cancel_token.cancel();
+    for handle in [self.task_handle.take(), self.heartbeat_handle.take()]
+      .into_iter()
+      .flatten()
+    {
+      handle.abort();
+    }
+    self.ticker = None;
+    self.subscriber = None;
+    self.set_command_sender(None);
+    self.reconnecting = true;
+    self.is_healthy.store(false, Ordering::Relaxed);
+    self.cancel_token = token;
+    self.stats.write().await.is_connected = false;
+  }
+
+  /// Deliver ticks of priority tokens through `route`
+  pub(crate) fn with_priority_route(mut self, route: PriorityRoute) -> Self {
+    self.priority = Some(route);
+    self
+  }
+
+  /// Record connects, closes and disconnects in `log`
+  pub(crate) fn with_lifecycle_log(mut self, log: LifecycleLog) -> Self {
+    self.lifecycle = Some(log);
+    self
+  }
+
+  /// Count forwarded messages against `budget` and shed ticks when it asks
+  pub(crate) fn with_buffer_budget(mut self, budget: BufferBudget) -> Self {
+    self.buffer_budget = Some(budget);
+    self
+  }
+
+  /// Record a lifecycle event, if a log is attached
+  pub(crate) fn record_lifecycle(
+    &self,
+    kind: LifecycleEventKind,
+    detail: impl Into<String>,
+  ) {
+    if let Some(log) = &self.lifecycle {
+      log.record(self.id, kind, detail);
     }
   }
 
@@ -70,17 +195,12 @@ impl ManagedConnection {
     // Store credentials for dynamic operations
     self.api_key = api_key.to_string();
     self.access_token = access_token.to_string();
+    self.raw_only = false;
 
     // Connect to WebSocket
-    let ticker = timeout(
-      config.connection_timeout,
-      KiteTickerAsync::connect(api_key, access_token),
-    )
-    .await
-    .map_err(|_| "Connection timeout".to_string())?
-    .map_err(|e| format!("Connection failed: {}", e))?;
+    let ticker = self.open_ticker(config, false).await?;
 
-    self.cmd_tx = ticker.command_sender();
+    self.set_command_sender(ticker.command_sender());
     // Initialize last_ping to now and start heartbeat watcher
     let now_sec = std::time::SystemTime::now()
       .duration_since(std::time::UNIX_EPOCH)
@@ -91,6 +211,7 @@ impl ManagedConnection {
     self.start_heartbeat_watcher();
     // Set configured liveness threshold
     self.heartbeat_liveness_threshold = config.heartbeat_liveness_threshold;
+    self.receive_timeout = config.receive_timeout;
     self.is_healthy.store(true, Ordering::Relaxed);
 
     // Update stats
@@ -99,10 +220,55 @@ impl ManagedConnection {
       stats.is_connected = true;
       stats.connection_uptime = Duration::ZERO;
     }
+    self.record_lifecycle(LifecycleEventKind::Connected, "");
 
     Ok(())
   }
 
+  /// Open a socket with the stored credentials, recording the HTTP status
+  /// if the handshake is refused
+  async fn open_ticker(
+    &mut self,
+    config: &KiteManagerConfig,
+    raw_only: bool,
+  ) -> Result<KiteTickerAsync, String> {
+    self.handshake_status = None;
+    let ticker = timeout(
+      config.connection_timeout,
+      KiteTickerAsync::open(
+        &self.api_key,
+        &self.access_token,
+        self.ticker_config(config, raw_only),
+      ),
+    )
+    .await
+    .map_err(|_| "Connection timeout".to_string())?;
+    ticker.map_err(|e| {
+      self.handshake_status = e.status;
+      format!("Connection failed: {}", e.message)
+    })
+  }
+
+  /// Derive per-socket options from the manager configuration
+  fn ticker_config(
+    &self,
+    config: &KiteManagerConfig,
+    raw_only: bool,
+  ) -> KiteTickerConfig {
+    KiteTickerConfig {
+      raw_only,
+      cancellation: Some(self.cancel_token.clone()),
+      slow_parse_threshold: config.slow_parse_threshold,
+      emit_unknown_packets: config.emit_unknown_packets,
+      best_effort_parsing: config.best_effort_parsing,
+      stamp_requested_mode: config.stamp_requested_mode,
+      mode_hold_back: config.mode_hold_back,
+      environment: config.environment.clone(),
+      parse_channel_capacity: config.reader_parser_channel_cap,
+      ..Default::default()
+    }
+  }
+
   /// Start a background watcher that listens to raw frames and updates `last_ping`.
   fn start_heartbeat_watcher(&mut self) {
     // Drop existing watcher if any
@@ -112,31 +278,28 @@ impl ManagedConnection {
     let Some(ticker) = self.ticker.as_ref() else {
       return;
     };
-    let mut rx = ticker.subscribe_raw_frames();
+    let mut rx = ticker.watch_raw_frames();
     let last_ping = Arc::clone(&self.last_ping);
     let id = self.id;
+    let cancel = self.cancel_token.clone();
     let handle = tokio::spawn(async move {
       loop {
-        match rx.recv().await {
-          Ok(_frame) => {
-            let now = std::time::SystemTime::now()
-              .duration_since(std::time::UNIX_EPOCH)
-              .unwrap_or_default()
-              .as_secs();
-            last_ping.store(now, Ordering::Relaxed);
-          }
-          Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-            log::debug!(
-              "Heartbeat watcher closed for connection {}",
-              id.to_index()
-            );
-            break;
-          }
-          Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
-            // If lagging, just continue; we'll get a fresher frame soon
-            continue;
-          }
+        let changed = tokio::select! {
+          _ = cancel.cancelled() => break,
+          changed = rx.changed() => changed,
+        };
+        if changed.is_err() {
+          log::debug!(
+            "Heartbeat watcher closed for connection {}",
+            id.to_index()
+          );
+          break;
         }
+        let now = std::time::SystemTime::now()
+          .duration_since(std::time::UNIX_EPOCH)
+          .unwrap_or_default()
+          .as_secs();
+        last_ping.store(now, Ordering::Relaxed);
       }
     });
     self.heartbeat_handle = Some(handle);
@@ -152,19 +315,10 @@ impl ManagedConnection {
   ) -> Result<(), String> {
     self.api_key = api_key.to_string();
     self.access_token = access_token.to_string();
-    let ticker = tokio::time::timeout(
-      config.connection_timeout,
-      crate::ticker::KiteTickerAsync::connect_with_options(
-        api_key,
-        access_token,
-        raw_only,
-      ),
-    )
-    .await
-    .map_err(|_| "Connection timeout".to_string())?
-    .map_err(|e| format!("Connection failed: {}", e))?;
+    self.raw_only = raw_only;
+    let ticker = self.open_ticker(config, raw_only).await?;
 
-    self.cmd_tx = ticker.command_sender();
+    self.set_command_sender(ticker.command_sender());
     // Initialize last_ping to now and start heartbeat watcher
     let now_sec = std::time::SystemTime::now()
       .duration_since(std::time::UNIX_EPOCH)
@@ -180,11 +334,79 @@ impl ManagedConnection {
       .store(true, std::sync::atomic::Ordering::Relaxed);
     // Set configured liveness threshold
     self.heartbeat_liveness_threshold = config.heartbeat_liveness_threshold;
+    self.receive_timeout = config.receive_timeout;
     {
       let mut stats = self.stats.write().await;
       stats.is_connected = true;
       stats.connection_uptime = Duration::ZERO;
     }
+    self.record_lifecycle(LifecycleEventKind::Connected, "");
+    Ok(())
+  }
+
+  /// Connect again with the credentials and raw-only flag of the previous
+  /// connect, so raw subscribers and the heartbeat watcher keep working
+  pub(crate) async fn reconnect_socket(
+    &mut self,
+    config: &KiteManagerConfig,
+  ) -> Result<(), String> {
+    let api_key = self.api_key.clone();
+    let access_token = self.access_token.clone();
+    self
+      .connect_with_raw(&api_key, &access_token, config, self.raw_only)
+      .await
+  }
+
+  /// Credentials the next `reconnect_socket` will use
+  pub(crate) fn set_credentials(&mut self, api_key: &str, access_token: &str) {
+    self.api_key = api_key.to_string();
+    self.access_token = access_token.to_string();
+  }
+
+  /// Whether this connection is up, with its subscriptions restored, on
+  /// these credentials
+  pub(crate) fn is_restored_with(
+    &self,
+    api_key: &str,
+    access_token: &str,
+  ) -> bool {
+    self.api_key == api_key
+      && self.access_token == access_token
+      && !self.reconnecting
+      && self.is_connected()
+  }
+
+  /// After `reconnect_socket`, subscribe everything tracked (including
+  /// symbols queued while reconnecting) on the new socket, per mode; the
+  /// first subscription creates the subscriber.
+  ///
+  /// On failure the full set is kept and the connection stays marked as
+  /// reconnecting, so the next attempt restores all of it.
+  pub(crate) async fn resubscribe_all(&mut self) -> Result<(), String> {
+    let wanted = std::mem::take(&mut self.subscribed_symbols);
+    let mut by_mode: Vec<(Mode, Vec<u32>)> = Vec::new();
+    for (&token, &mode) in &wanted {
+      match by_mode.iter_mut().find(|(m, _)| *m == mode) {
+        Some((_, tokens)) => tokens.push(token),
+        None => by_mode.push((mode, vec![token])),
+      }
+    }
+    self.reconnecting = false;
+    for (i, (mode, tokens)) in by_mode.into_iter().enumerate() {
+      let resubscribed = if i == 0 {
+        match self.subscribe_symbols(&tokens, mode).await {
+          Ok(()) => self.start_message_processing().await,
+          Err(e) => Err(e),
+        }
+      } else {
+        self.add_symbols(&tokens, mode).await
+      };
+      if let Err(e) = resubscribed {
+        self.subscribed_symbols = wanted;
+        self.reconnecting = true;
+        return Err(e);
+      }
+    }
     Ok(())
   }
 
@@ -195,12 +417,21 @@ impl ManagedConnection {
     mode: Mode,
   ) -> Result<(), String> {
     if let Some(ticker) = self.ticker.as_mut() {
-      // Use the existing ticker directly
-      let subscriber = ticker.subscribe(symbols, Some(mode)).await?;
-      // Track symbols
+      // Track symbols first so the parser already holds back packets in
+      // Kite's default mode when the first ticks arrive
+      let previous = self.subscribed_symbols.clone();
       for &symbol in symbols {
         self.subscribed_symbols.insert(symbol, mode);
       }
+      ticker.set_mode_hints(&self.subscribed_symbols);
+      let subscriber = match ticker.subscribe(symbols, Some(mode)).await {
+        Ok(subscriber) => subscriber,
+        Err(e) => {
+          self.subscribed_symbols = previous;
+          ticker.set_mode_hints(&self.subscribed_symbols);
+          return Err(e);
+        }
+      };
       self.subscriber = Some(subscriber);
 
       // Update stats
@@ -215,13 +446,42 @@ impl ManagedConnection {
     }
   }
 
+  /// Whether the socket is being replaced; subscription changes made now
+  /// are queued and applied by the reconnect
+  pub fn is_reconnecting(&self) -> bool {
+    self.reconnecting
+  }
+
+  /// Record `symbols` for the reconnect to subscribe once the socket is back
+  pub(crate) async fn queue_symbols(&mut self, symbols: &[u32], mode: Mode) {
+    for &symbol in symbols {
+      self.subscribed_symbols.insert(symbol, mode);
+    }
+    self.stats.write().await.symbol_count = self.subscribed_symbols.len();
+    log::info!(
+      "Queued {} symbols on reconnecting connection {}",
+      symbols.len(),
+      self.id.to_index()
+    );
+  }
+
+  /// Whether a subscription is live, either still held here or already
+  /// moved into the processing task by `start_message_processing`
+  fn has_subscription(&self) -> bool {
+    self.subscriber.is_some() || self.task_handle.is_some()
+  }
+
   /// Dynamically add new symbols to existing subscription
   pub async fn add_symbols(
     &mut self,
     symbols: &[u32],
     mode: Mode,
   ) -> Result<(), String> {
-    if self.subscriber.is_some() {
+    if self.reconnecting {
+      self.queue_symbols(symbols, mode).await;
+      return Ok(());
+    }
+    if self.has_subscription() {
       // Filter to truly new symbols
       let new: Vec<u32> = symbols
         .iter()
@@ -231,18 +491,40 @@ impl ManagedConnection {
       if new.is_empty() {
         return Ok(());
       }
-      if let Some(tx) = &self.cmd_tx {
+      for &s in &new {
+        self.subscribed_symbols.insert(s, mode);
+      }
+      self.sync_mode_hints();
+      if let Some(tx) = self.command_sender() {
         // send subscribe + mode
         let sub = crate::models::Request::subscribe(&new).to_string();
         let mode_msg = crate::models::Request::mode(mode, &new).to_string();
-        let _ =
-          tx.send(tokio_tungstenite::tungstenite::Message::Text(sub.into()));
-        let _ = tx.send(tokio_tungstenite::tungstenite::Message::Text(
-          mode_msg.into(),
-        ));
-      }
-      for &s in &new {
-        self.subscribed_symbols.insert(s, mode);
+        let subscribed = tx.send_async(Message::Text(sub.into())).await;
+        let sent = match &subscribed {
+          Ok(()) => tx.send_async(Message::Text(mode_msg.into())).await,
+          Err(e) => Err(e.clone()),
+        };
+        if let Err(e) = sent {
+          for s in &new {
+            self.subscribed_symbols.remove(s);
+          }
+          self.sync_mode_hints();
+          if subscribed.is_ok() {
+            // Kite already has the tokens; take them back so the stream
+            // matches `subscribed_symbols`
+            let unsub = crate::models::Request::unsubscribe(&new).to_string();
+            if let Err(e) = tx.send_async(Message::Text(unsub.into())).await {
+              log::warn!(
+                "Connection {}: could not unsubscribe {} tokens after a \
+                 failed mode request: {}",
+                self.id.to_index(),
+                new.len(),
+                e
+              );
+            }
+          }
+          return Err(format!("Failed to send subscription: {}", e));
+        }
       }
       let mut stats = self.stats.write().await;
       stats.symbol_count = self.subscribed_symbols.len();
@@ -262,7 +544,13 @@ impl ManagedConnection {
     &mut self,
     symbols: &[u32],
   ) -> Result<(), String> {
-    if self.subscriber.is_some() {
+    if self.reconnecting {
+      // The reconnect resubscribes from `subscribed_symbols`, so dropping
+      // them here is enough
+      self.forget_symbols(symbols).await;
+      return Ok(());
+    }
+    if self.has_subscription() {
       // Only symbols currently subscribed
       let existing: Vec<u32> = symbols
         .iter()
@@ -272,14 +560,16 @@ impl ManagedConnection {
       if existing.is_empty() {
         return Ok(());
       }
-      if let Some(tx) = &self.cmd_tx {
+      if let Some(tx) = self.command_sender() {
         let unsub = crate::models::Request::unsubscribe(&existing).to_string();
-        let _ =
-          tx.send(tokio_tungstenite::tungstenite::Message::Text(unsub.into()));
+        tx.send_async(Message::Text(unsub.into()))
+          .await
+          .map_err(|e| format!("Failed to send unsubscription: {}", e))?;
       }
       for s in &existing {
         self.subscribed_symbols.remove(s);
       }
+      self.sync_mode_hints();
       let mut stats = self.stats.write().await;
       stats.symbol_count = self.subscribed_symbols.len();
       log::info!(
@@ -293,15 +583,52 @@ impl ManagedConnection {
     }
   }
 
+  /// Drop `symbols` from the tracked set without telling the server, for
+  /// symbols already unsubscribed on the socket
+  pub(crate) async fn forget_symbols(&mut self, symbols: &[u32]) {
+    for s in symbols {
+      self.subscribed_symbols.remove(s);
+    }
+    self.sync_mode_hints();
+    self.stats.write().await.symbol_count = self.subscribed_symbols.len();
+  }
+
+  /// Sender for commands to the current socket, if connected
+  pub(crate) fn command_sender(&self) -> Option<CommandSender> {
+    self
+      .cmd_tx
+      .read()
+      .unwrap_or_else(|e| e.into_inner())
+      .clone()
+  }
+
+  fn set_command_sender(&self, tx: Option<CommandSender>) {
+    *self.cmd_tx.write().unwrap_or_else(|e| e.into_inner()) = tx;
+  }
+
+  /// Share the subscribed modes with the parser so it can settle packets
+  /// whose length is ambiguous
+  pub(crate) fn sync_mode_hints(&self) {
+    if let Some(ticker) = &self.ticker {
+      ticker.set_mode_hints(&self.subscribed_symbols);
+    }
+  }
+
   /// Start message processing for the subscriber
   pub async fn start_message_processing(&mut self) -> Result<(), String> {
     if let Some(subscriber) = self.subscriber.take() {
       let message_sender = self.message_sender.clone();
       let stats = Arc::clone(&self.stats);
       let is_healthy = Arc::clone(&self.is_healthy);
+      let throttled = Arc::clone(&self.throttled);
       let last_ping = Arc::clone(&self.last_ping);
       let connection_id = self.id;
       let threshold = self.heartbeat_liveness_threshold;
+      let receive_timeout = self.receive_timeout;
+      let cancel = self.cancel_token.clone();
+      let priority = self.priority.clone();
+      let lifecycle = self.lifecycle.clone();
+      let buffer_budget = self.buffer_budget.clone();
 
       let handle = tokio::spawn(async move {
         Self::message_processing_loop(
@@ -309,9 +636,15 @@ impl ManagedConnection {
           message_sender,
           stats,
           is_healthy,
+          throttled,
           connection_id,
           last_ping,
           threshold,
+          receive_timeout,
+          cancel,
+          priority,
+          lifecycle,
+          buffer_budget,
         )
         .await;
       });
@@ -324,18 +657,38 @@ impl ManagedConnection {
   }
 
   /// Message processing loop for this connection
+  #[allow(clippy::too_many_arguments)]
   async fn message_processing_loop(
     mut subscriber: crate::ticker::KiteTickerSubscriber,
     message_sender: mpsc::UnboundedSender<TickerMessage>,
     stats: Arc<RwLock<ConnectionStats>>,
     is_healthy: Arc<AtomicBool>,
+    throttled: Arc<AtomicBool>,
     connection_id: ChannelId,
     last_ping: Arc<AtomicU64>,
     heartbeat_threshold: Duration,
+    receive_timeout: Duration,
+    cancel: CancellationToken,
+    priority: Option<PriorityRoute>,
+    lifecycle: Option<LifecycleLog>,
+    buffer_budget: Option<BufferBudget>,
   ) {
+    let record = |kind, detail: String| {
+      if let Some(log) = &lifecycle {
+        log.record(connection_id, kind, detail);
+      }
+    };
     let mut last_message_time = Instant::now();
+    // `NoData` already sent for the current silence
+    let mut silence_reported = false;
     let mut last_stats_flush = Instant::now();
     let mut pending_messages: u64 = 0;
+    // Parse counts already folded into `stats` (the ticker's are cumulative)
+    let mut reported_parse = (0, 0);
+    let mut reported_held_back = 0;
+    let mut pending_error: Option<(Instant, String)> = None;
+    let mut pending_shed: u64 = 0;
+    let index = connection_id.to_index();
 
     log::info!(
       "Starting message processing loop for connection {}",
@@ -343,9 +696,45 @@ impl ManagedConnection {
     );
 
     loop {
-      match timeout(Duration::from_secs(30), subscriber.next_message()).await {
+      let next = tokio::select! {
+        _ = cancel.cancelled() => {
+          log::info!(
+            "Connection {} cancelled",
+            connection_id.to_index()
+          );
+          break;
+        }
+        next = timeout(receive_timeout, subscriber.next_message()) => next,
+      };
+      match next {
         Ok(Ok(Some(message))) => {
           last_message_time = Instant::now();
+          silence_reported = false;
+
+          // Priority ticks bypass the processor and its broadcast buffer
+          let message = match (&priority, message) {
+            (Some(route), TickerMessage::Ticks(ticks)) => {
+              let rest = route.route(ticks);
+              if rest.is_empty() {
+                pending_messages += 1;
+                continue;
+              }
+              TickerMessage::Ticks(rest)
+            }
+            (_, message) => message,
+          };
+
+          if let TickerMessage::Throttled(notice) = &message {
+            log::warn!(
+              "Connection {} throttled by Kite: {}",
+              connection_id.to_index(),
+              notice
+            );
+            throttled.store(true, Ordering::Relaxed);
+          }
+          if let TickerMessage::ClosingMessage(close) = &message {
+            record(LifecycleEventKind::Closed, close.to_string());
+          }
 
           // Debug: Print incoming message
           if log::log_enabled!(log::Level::Debug) {
@@ -389,12 +778,54 @@ impl ManagedConnection {
             let mut stats = stats.write().await;
             stats.messages_received += pending_messages;
             stats.last_message_time = Some(last_message_time);
+            stats.command_queue_depth = subscriber.command_queue_depth();
+            let (parsed, errors) = subscriber.parse_counts();
+            stats.messages_parsed += parsed - reported_parse.0;
+            stats.parse_errors += errors - reported_parse.1;
+            if errors > reported_parse.1 {
+              stats.last_error = Some((
+                Instant::now(),
+                format!(
+                  "{} tick packet(s) failed to parse",
+                  errors - reported_parse.1
+                ),
+              ));
+            }
+            if let Some(error) = pending_error.take() {
+              stats.last_error = Some(error);
+            }
+            reported_parse = (parsed, errors);
+            let held_back = subscriber.held_back_count();
+            stats.ticks_held_back += held_back - reported_held_back;
+            reported_held_back = held_back;
+            stats.messages_shed += pending_shed;
+            pending_shed = 0;
             pending_messages = 0;
             last_stats_flush = Instant::now();
           }
 
+          if let Some(budget) = &buffer_budget {
+            if matches!(message, TickerMessage::Ticks(_))
+              && budget.should_shed(index)
+            {
+              if pending_shed == 0 {
+                log::warn!(
+                  "Connection {}: buffered message budget exhausted, \
+                   shedding ticks",
+                  index
+                );
+              }
+              pending_shed += 1;
+              continue;
+            }
+            budget.queued(index);
+          }
+
           // Forward message to parser (non-blocking)
           if message_sender.send(message).is_err() {
+            if let Some(budget) = &buffer_budget {
+              budget.dequeued(index);
+            }
             log::warn!(
               "Connection {}: Parser channel full, dropping message",
               connection_id.to_index()
@@ -403,26 +834,69 @@ impl ManagedConnection {
             // Update error stats
             let mut stats = stats.write().await;
             stats.errors_count += 1;
+            stats.last_error = Some((
+              Instant::now(),
+              "Parser channel full, message dropped".to_string(),
+            ));
           }
         }
         Ok(Ok(None)) => {
           log::info!("Connection {} closed", connection_id.to_index());
+          record(
+            LifecycleEventKind::Disconnected,
+            "socket closed".to_string(),
+          );
           is_healthy.store(false, Ordering::Relaxed);
           break;
         }
         Ok(Err(e)) => {
           log::error!("Connection {} error: {}", connection_id.to_index(), e);
 
-          // Update error stats
+          // Update error stats; a throttled error is kept for the next flush
+          pending_error = Some((Instant::now(), e));
           if last_stats_flush.elapsed() >= Duration::from_millis(250) {
             let mut stats = stats.write().await;
             stats.errors_count += 1;
+            stats.last_error = pending_error.take();
             last_stats_flush = Instant::now();
           }
 
           // Continue trying to receive messages
         }
         Err(_) => {
+          // With nothing subscribed there is nothing to wait for; the
+          // connection is idle, not stale
+          if stats.read().await.symbol_count == 0 {
+            log::debug!(
+              "Connection {} idle with no subscribed symbols",
+              connection_id.to_index()
+            );
+            last_message_time = Instant::now();
+            continue;
+          }
+          // Tell consumers data has stopped, even if heartbeats still flow:
+          // that is what a market-data outage looks like. Once per silence,
+          // so a closed market does not repeat it every timeout
+          let silent_for = last_message_time.elapsed();
+          if !silence_reported {
+            silence_reported = true;
+            log::warn!(
+              "Connection {}: no data for {:?}",
+              connection_id.to_index(),
+              silent_for
+            );
+            if let Some(budget) = &buffer_budget {
+              budget.queued(index);
+            }
+            if message_sender
+              .send(TickerMessage::NoData(silent_for))
+              .is_err()
+            {
+              if let Some(budget) = &buffer_budget {
+                budget.dequeued(index);
+              }
+            }
+          }
           // Timeout waiting for parsed messages; consult heartbeat/frames
           let now_sec = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -442,6 +916,13 @@ impl ManagedConnection {
               connection_id.to_index(),
               heartbeat_threshold,
             );
+            record(
+              LifecycleEventKind::Disconnected,
+              format!(
+                "no frames or heartbeats within {:?}",
+                heartbeat_threshold
+              ),
+            );
             is_healthy.store(false, Ordering::Relaxed);
             break;
           }
@@ -449,9 +930,13 @@ impl ManagedConnection {
       }
     }
 
-    // Update connection status
+    // Update connection status, flushing any unreported message count
     {
       let mut stats = stats.write().await;
+      stats.messages_received += pending_messages;
+      if pending_messages > 0 {
+        stats.last_message_time = Some(last_message_time);
+      }
       stats.is_connected = false;
     }
     is_healthy.store(false, Ordering::Relaxed);
@@ -471,6 +956,14 @@ impl ManagedConnection {
     self.subscribed_symbols.len()
   }
 
+  /// Whether the socket's reader and writer are still running
+  pub fn is_connected(&self) -> bool {
+    self
+      .ticker
+      .as_ref()
+      .is_some_and(KiteTickerAsync::is_connected)
+  }
+
   /// Check if connection is healthy
   pub fn is_healthy(&self) -> bool {
     self.is_healthy.load(Ordering::Relaxed)
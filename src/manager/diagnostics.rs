@@ -0,0 +1,69 @@
+use crate::manager::{
+  ChannelId, ConnectionStats, HealthSummary, KiteManagerConfig, ProcessorStats,
+  SubscriptionOp,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Point-in-time snapshot of a manager, for attaching to bug reports
+///
+/// Serialize it with `serde_json::to_string_pretty(&manager.diagnostics().await)`.
+/// Credentials are never included.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+  pub config: KiteManagerConfig,
+  pub connection_count: usize,
+  pub connections: Vec<ConnectionStats>,
+  pub symbol_distribution: HashMap<ChannelId, Vec<u32>>,
+  pub processor_stats: Vec<(ChannelId, ProcessorStats)>,
+  /// `None` when health monitoring is not running
+  pub health: Option<HealthSummary>,
+  /// Most recent entries of the subscription operation log, oldest first
+  pub recent_operations: Vec<SubscriptionOp>,
+}
+
+/// One-call data source for a monitoring UI (TUI or web dashboard)
+///
+/// Built by [`KiteTickerManager::dashboard_snapshot`](crate::KiteTickerManager::dashboard_snapshot);
+/// cheap enough to poll every second.
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DashboardSnapshot {
+  pub connections: Vec<DashboardConnection>,
+  pub healthy_connections: usize,
+  pub total_symbols: usize,
+  pub total_messages: u64,
+  pub total_errors: u64,
+  /// Sum of the connections' parser throughput
+  pub messages_per_second: f64,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub uptime: Duration,
+  /// Subscribed tokens with the most ticks since start, busiest first
+  pub top_symbols: Vec<SymbolActivity>,
+}
+
+/// One connection's row in a [`DashboardSnapshot`]
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardConnection {
+  pub channel: ChannelId,
+  pub healthy: bool,
+  pub symbols: usize,
+  pub messages_received: u64,
+  pub messages_per_second: f64,
+  pub errors: u64,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
+  pub uptime: Duration,
+  pub last_error: Option<String>,
+  /// Time since `last_error`
+  #[serde_as(as = "Option<serde_with::DurationSecondsWithFrac<f64>>")]
+  pub last_error_age: Option<Duration>,
+}
+
+/// Ticks received for one token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SymbolActivity {
+  pub token: u32,
+  pub ticks: u64,
+}
@@ -1,9 +1,11 @@
-use crate::manager::{ConnectionStats, ManagerStats};
+use crate::manager::{BreakerState, ConnectionStats, ManagerStats};
+use serde::Serialize;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
 
 /// Health monitor for tracking connection and system health
 #[derive(Debug)]
@@ -12,6 +14,19 @@ pub struct HealthMonitor {
   pub manager_start_time: Instant,
   pub monitoring_task: Option<JoinHandle<()>>,
   pub health_check_interval: Duration,
+  /// Healthy connections below which `HealthEvent::BelowMinimum` fires
+  /// (0 = never)
+  pub min_healthy_connections: usize,
+  events: Option<broadcast::Sender<HealthEvent>>,
+}
+
+/// Change in coverage reported by the health monitor's periodic check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum HealthEvent {
+  /// Fewer than `minimum` connections are healthy
+  BelowMinimum { healthy: usize, minimum: usize },
+  /// At least `minimum` connections are healthy again
+  MinimumRestored { healthy: usize, minimum: usize },
 }
 
 impl HealthMonitor {
@@ -25,20 +40,44 @@ impl HealthMonitor {
       manager_start_time: Instant::now(),
       monitoring_task: None,
       health_check_interval,
+      min_healthy_connections: 0,
+      events: None,
     }
   }
 
+  /// Publish a [`HealthEvent`] on `events` whenever the healthy count
+  /// crosses `minimum`
+  pub fn with_minimum_healthy(
+    mut self,
+    minimum: usize,
+    events: broadcast::Sender<HealthEvent>,
+  ) -> Self {
+    self.min_healthy_connections = minimum;
+    self.events = Some(events);
+    self
+  }
+
   /// Start the health monitoring task
   pub fn start(&mut self) {
+    self.start_with_cancellation(CancellationToken::new());
+  }
+
+  /// Start the monitoring task, exiting cleanly once `cancel` fires
+  pub fn start_with_cancellation(&mut self, cancel: CancellationToken) {
     let connection_stats = self.connection_stats.clone();
     let health_check_interval = self.health_check_interval;
     let manager_start_time = self.manager_start_time;
+    let minimum = self.min_healthy_connections;
+    let events = self.events.clone();
 
     let handle = tokio::spawn(async move {
       Self::monitoring_loop(
         connection_stats,
         health_check_interval,
         manager_start_time,
+        minimum,
+        events,
+        cancel,
       )
       .await;
     });
@@ -51,14 +90,21 @@ impl HealthMonitor {
     connection_stats: Vec<Arc<RwLock<ConnectionStats>>>,
     health_check_interval: Duration,
     manager_start_time: Instant,
+    minimum: usize,
+    events: Option<broadcast::Sender<HealthEvent>>,
+    cancel: CancellationToken,
   ) {
+    let mut below_minimum = false;
     log::info!(
       "Health monitor started with interval: {:?}",
       health_check_interval
     );
 
     loop {
-      sleep(health_check_interval).await;
+      tokio::select! {
+        _ = cancel.cancelled() => break,
+        _ = sleep(health_check_interval) => {}
+      }
 
       // Collect health information
       let mut healthy_connections = 0;
@@ -110,6 +156,34 @@ impl HealthMonitor {
           connection_stats.len()
         );
       }
+
+      if minimum > 0 && (healthy_connections < minimum) != below_minimum {
+        below_minimum = !below_minimum;
+        let event = if below_minimum {
+          log::error!(
+            "Only {}/{} required connections are healthy",
+            healthy_connections,
+            minimum
+          );
+          HealthEvent::BelowMinimum {
+            healthy: healthy_connections,
+            minimum,
+          }
+        } else {
+          log::info!(
+            "{} connections healthy again (minimum {})",
+            healthy_connections,
+            minimum
+          );
+          HealthEvent::MinimumRestored {
+            healthy: healthy_connections,
+            minimum,
+          }
+        };
+        if let Some(events) = &events {
+          let _ = events.send(event);
+        }
+      }
     }
   }
 
@@ -131,7 +205,11 @@ impl HealthMonitor {
 
       manager_stats.total_symbols += stats.symbol_count;
       manager_stats.total_messages_received += stats.messages_received;
+      manager_stats.total_messages_parsed += stats.messages_parsed;
+      manager_stats.total_parse_errors += stats.parse_errors;
       manager_stats.total_errors += stats.errors_count;
+      manager_stats.total_messages_shed += stats.messages_shed;
+      manager_stats.total_ticks_held_back += stats.ticks_held_back;
       manager_stats.connection_stats.push(stats.clone());
     }
 
@@ -155,6 +233,11 @@ impl HealthMonitor {
       summary.total_symbols += stats.symbol_count;
       summary.total_messages += stats.messages_received;
       summary.total_errors += stats.errors_count;
+      if let Some((at, _)) = &stats.last_error {
+        let age = at.elapsed();
+        summary.last_error_age =
+          Some(summary.last_error_age.map_or(age, |a| a.min(age)));
+      }
 
       // Calculate message rate (messages per second over last minute)
       if let Some(last_msg_time) = stats.last_message_time {
@@ -178,7 +261,12 @@ impl HealthMonitor {
 }
 
 /// Health summary for quick status checks
-#[derive(Debug, Clone, Default)]
+///
+/// Serializable for health endpoints, e.g.
+/// `serde_json::to_string(&manager.get_health().await?)`.
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Default, Serialize)]
+#[non_exhaustive]
 pub struct HealthSummary {
   pub healthy_connections: usize,
   pub unhealthy_connections: Vec<usize>,
@@ -186,13 +274,31 @@ pub struct HealthSummary {
   pub total_messages: u64,
   pub total_errors: u64,
   pub active_message_flows: usize,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub uptime: Duration,
+  /// State of the reconnect circuit breaker
+  pub circuit_breaker: BreakerState,
+  /// Time since the most recent error on any connection
+  #[serde_as(as = "Option<serde_with::DurationSecondsWithFrac<f64>>")]
+  pub last_error_age: Option<Duration>,
 }
 
 impl HealthSummary {
-  /// Check if the system is healthy
+  /// How long an error keeps the system from reporting "healthy"
+  pub const RECENT_ERROR_WINDOW: Duration = Duration::from_secs(60);
+
+  /// Check if the system is healthy: every connection up and no error
+  /// within [`RECENT_ERROR_WINDOW`](Self::RECENT_ERROR_WINDOW)
   pub fn is_healthy(&self) -> bool {
-    self.unhealthy_connections.is_empty() && self.total_errors == 0
+    self.unhealthy_connections.is_empty() && !self.has_recent_errors()
+  }
+
+  /// Whether any connection reported an error within
+  /// [`RECENT_ERROR_WINDOW`](Self::RECENT_ERROR_WINDOW)
+  pub fn has_recent_errors(&self) -> bool {
+    self
+      .last_error_age
+      .is_some_and(|age| age < Self::RECENT_ERROR_WINDOW)
   }
 
   /// Check if the system is degraded (some connections unhealthy)
@@ -200,11 +306,31 @@ impl HealthSummary {
     !self.unhealthy_connections.is_empty() && self.healthy_connections > 0
   }
 
+  /// Whether at least `n` connections are healthy
+  pub fn meets_minimum(&self, n: usize) -> bool {
+    self.healthy_connections >= n
+  }
+
   /// Check if the system is critical (all connections unhealthy)
   pub fn is_critical(&self) -> bool {
     self.healthy_connections == 0
   }
 
+  /// Status label: "healthy", "degraded" or "critical".
+  ///
+  /// A system with all connections up reports "degraded" for
+  /// [`RECENT_ERROR_WINDOW`](Self::RECENT_ERROR_WINDOW) after an error, then
+  /// "healthy" again; `total_errors` keeps counting either way.
+  pub fn status_str(&self) -> &'static str {
+    if self.is_critical() {
+      "critical"
+    } else if self.is_healthy() {
+      "healthy"
+    } else {
+      "degraded"
+    }
+  }
+
   /// Get health percentage (0-100)
   pub fn health_percentage(&self) -> f64 {
     let total_connections =
@@ -1,9 +1,27 @@
-use crate::manager::ChannelId;
-use crate::models::TickerMessage;
+use crate::manager::{serialize_instant_age, BufferBudget, ChannelId};
+use crate::models::{Mode, Tick, TickDirection, TickMessage, TickerMessage};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Latest tick per instrument token, shared by all processors of a manager
+pub type LastValueCache = Arc<RwLock<HashMap<u32, TickMessage>>>;
+
+/// Activity per instrument token, updated with the processor stats
+pub type TokenActivityMap = Arc<RwLock<HashMap<u32, TokenActivity>>>;
+
+/// Ticks forwarded for one token and when the last of them was seen
+///
+/// `last_tick` is taken at the processor's periodic stats update, so it
+/// trails the actual tick by up to a second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenActivity {
+  pub ticks: u64,
+  pub last_tick: Instant,
+}
 
 /// High-performance message processor with dedicated parsing task
 #[derive(Debug)]
@@ -12,17 +30,56 @@ pub struct MessageProcessor {
   pub input_receiver: Option<mpsc::UnboundedReceiver<TickerMessage>>,
   pub output_sender: broadcast::Sender<TickerMessage>,
   pub stats: Arc<RwLock<ProcessorStats>>,
+  pub token_activity: TokenActivityMap,
   pub task_handle: Option<JoinHandle<()>>,
+  pub last_value_cache: Option<LastValueCache>,
+  pub core_affinity: Option<usize>,
+  pub coalesce_duplicates: bool,
+  pub(crate) buffer_budget: Option<BufferBudget>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[serde_with::serde_as]
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[non_exhaustive]
 pub struct ProcessorStats {
   pub messages_processed: u64,
   pub messages_per_second: f64,
+  #[serde_as(as = "serde_with::DurationSecondsWithFrac<f64>")]
   pub processing_latency_avg: std::time::Duration,
+  #[serde(
+    rename = "last_processed_age_secs",
+    serialize_with = "serialize_instant_age"
+  )]
   pub last_processed_time: Option<Instant>,
   pub queue_size: usize,
   pub errors_count: u64,
+  /// Quote/Full ticks dropped as exact repeats of the previous tick
+  pub duplicates_dropped: u64,
+  /// Subscribed symbols per mode on this connection, filled in by the
+  /// manager so load can be read next to throughput
+  pub symbols_by_mode: ModeBreakdown,
+}
+
+/// Number of symbols subscribed in each mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ModeBreakdown {
+  pub ltp: usize,
+  pub quote: usize,
+  pub full: usize,
+}
+
+impl ModeBreakdown {
+  /// Count `modes`, e.g. the values of a connection's subscription map
+  pub fn from_modes<'a>(modes: impl IntoIterator<Item = &'a Mode>) -> Self {
+    modes.into_iter().fold(Self::default(), |mut acc, mode| {
+      match mode {
+        Mode::LTP => acc.ltp += 1,
+        Mode::Quote => acc.quote += 1,
+        Mode::Full => acc.full += 1,
+      }
+      acc
+    })
+  }
 }
 
 impl MessageProcessor {
@@ -40,14 +97,51 @@ impl MessageProcessor {
       input_receiver: Some(input_receiver),
       output_sender,
       stats,
+      token_activity: TokenActivityMap::default(),
       task_handle: None,
+      last_value_cache: None,
+      core_affinity: None,
+      coalesce_duplicates: false,
+      buffer_budget: None,
     };
 
     (processor, output_receiver)
   }
 
+  /// Record the latest tick of every token into `cache` as messages pass
+  pub fn with_last_value_cache(mut self, cache: LastValueCache) -> Self {
+    self.last_value_cache = Some(cache);
+    self
+  }
+
+  /// Run the processing loop on its own thread pinned to `core` (needs the
+  /// `core_affinity` feature; ignored with a warning otherwise)
+  pub fn with_core_affinity(mut self, core: usize) -> Self {
+    self.core_affinity = Some(core);
+    self
+  }
+
+  /// Drop Quote/Full ticks identical in every field to the previous tick of
+  /// the same token; messages left without ticks are not forwarded
+  pub fn with_duplicate_coalescing(mut self) -> Self {
+    self.coalesce_duplicates = true;
+    self
+  }
+
+  /// Report taken input and the output channel's backlog to `budget`
+  pub(crate) fn with_buffer_budget(mut self, budget: BufferBudget) -> Self {
+    budget.track_output(self.channel_id.to_index(), self.output_sender.clone());
+    self.buffer_budget = Some(budget);
+    self
+  }
+
   /// Start the dedicated processing task
   pub fn start(&mut self) {
+    self.start_with_cancellation(CancellationToken::new());
+  }
+
+  /// Start the processing task, exiting cleanly once `cancel` fires
+  pub fn start_with_cancellation(&mut self, cancel: CancellationToken) {
     // Only start if not already running
     if self.task_handle.is_some() {
       log::warn!(
@@ -66,11 +160,38 @@ impl MessageProcessor {
     let channel_id = self.channel_id;
     let output_sender = self.output_sender.clone();
     let stats = Arc::clone(&self.stats);
+    let token_activity = Arc::clone(&self.token_activity);
+    let last_value_cache = self.last_value_cache.clone();
+    let coalesce_duplicates = self.coalesce_duplicates;
+    let buffer_budget = self.buffer_budget.clone();
 
-    let handle = tokio::spawn(async move {
-      Self::processing_loop(channel_id, input_receiver, output_sender, stats)
-        .await;
-    });
+    let processing = move |cancel| {
+      Self::processing_loop(
+        channel_id,
+        input_receiver,
+        output_sender,
+        stats,
+        token_activity,
+        last_value_cache,
+        coalesce_duplicates,
+        buffer_budget,
+        cancel,
+      )
+    };
+    let handle = match self.core_affinity {
+      #[cfg(feature = "core_affinity")]
+      Some(core) => spawn_pinned(channel_id, core, cancel, processing),
+      #[cfg(not(feature = "core_affinity"))]
+      Some(core) => {
+        log::warn!(
+          "Channel {:?}: parser pinning to core {} needs the `core_affinity` feature; running unpinned",
+          channel_id,
+          core
+        );
+        tokio::spawn(processing(cancel))
+      }
+      None => tokio::spawn(processing(cancel)),
+    };
 
     self.task_handle = Some(handle);
     log::info!(
@@ -80,41 +201,116 @@ impl MessageProcessor {
   }
 
   /// High-performance message processing loop
+  ///
+  /// Single consumer, so messages are forwarded in the order received
+  #[allow(clippy::too_many_arguments)]
   async fn processing_loop(
     channel_id: ChannelId,
     mut input_receiver: mpsc::UnboundedReceiver<TickerMessage>,
     output_sender: broadcast::Sender<TickerMessage>,
     stats: Arc<RwLock<ProcessorStats>>,
+    token_activity: TokenActivityMap,
+    last_value_cache: Option<LastValueCache>,
+    coalesce_duplicates: bool,
+    buffer_budget: Option<BufferBudget>,
+    cancel: CancellationToken,
   ) {
     let mut last_stats_update = Instant::now();
     let mut messages_since_last_update = 0u64;
+    let mut duplicates_since_last_update = 0u64;
+    let mut activity_since_last_update: HashMap<u32, u64> = HashMap::new();
+    // Previous Quote/Full tick per token, only kept when coalescing
+    let mut previous_ticks: HashMap<u32, Tick> = HashMap::new();
+    // Previous price and cumulative volume per token, so direction and
+    // volume delta are derived without touching the shared cache
+    let mut last_seen: HashMap<u32, (Option<f64>, Option<u32>)> =
+      HashMap::new();
+    // Ticks not yet published to the shared last-value cache
+    let mut unpublished: HashMap<u32, TickMessage> = HashMap::new();
 
     log::info!("Started message processor for channel {:?}", channel_id);
 
-    while let Some(message) = input_receiver.recv().await {
+    loop {
+      let message = tokio::select! {
+        _ = cancel.cancelled() => break,
+        message = input_receiver.recv() => message,
+      };
+      let Some(message) = message else { break };
+      if let Some(budget) = &buffer_budget {
+        budget.dequeued(channel_id.to_index());
+      }
       let processing_start = Instant::now();
 
       // Process the message (currently just forwarding, but can add logic here)
-      let processed_message = Self::process_message(message, channel_id);
+      let mut processed_message = Self::process_message(message, channel_id);
 
-      // Send to output channel (non-blocking)
-      match output_sender.send(processed_message) {
-        Ok(receiver_count) => {
-          // Successfully sent to all receivers
-          if receiver_count == 0 {
-            log::debug!("Channel {:?}: No active receivers", channel_id);
+      if let (true, TickerMessage::Ticks(ticks)) =
+        (coalesce_duplicates, &mut processed_message)
+      {
+        let before = ticks.len();
+        ticks.retain(|tick| {
+          if tick.content.mode == Mode::LTP {
+            return true;
+          }
+          match previous_ticks.get(&tick.instrument_token) {
+            Some(previous) if *previous == tick.content => false,
+            _ => {
+              previous_ticks
+                .insert(tick.instrument_token, tick.content.clone());
+              true
+            }
           }
+        });
+        duplicates_since_last_update += (before - ticks.len()) as u64;
+        if ticks.is_empty() {
+          continue;
         }
-        Err(_) => {
-          // This shouldn't happen with broadcast channels unless buffer is full
-          log::warn!("Channel {:?}: Failed to send message", channel_id);
+      }
 
-          let mut stats = stats.write().await;
-          stats.errors_count += 1;
-          continue;
+      if let TickerMessage::Ticks(ticks) = &processed_message {
+        for tick in ticks {
+          *activity_since_last_update
+            .entry(tick.instrument_token)
+            .or_default() += 1;
+        }
+      }
+
+      if let (Some(cache), TickerMessage::Ticks(ticks)) =
+        (&last_value_cache, &mut processed_message)
+      {
+        for tick in ticks.iter_mut() {
+          let token = tick.instrument_token;
+          let current = (tick.content.last_price, tick.content.volume_traded);
+          let previous = last_seen.insert(token, current).or_else(|| {
+            // First tick here; the token may have moved from another
+            // connection
+            let cache = cache.try_read().ok()?;
+            let previous = &cache.get(&token)?.content;
+            Some((previous.last_price, previous.volume_traded))
+          });
+          tick.tick_direction = TickDirection::between(
+            previous.and_then(|(price, _)| price),
+            tick.content.last_price,
+          );
+          tick.volume_delta = previous
+            .and_then(|(_, volume)| volume)
+            .zip(tick.content.volume_traded)
+            .and_then(|(prev, cur)| cur.checked_sub(prev));
+          unpublished.insert(tick.instrument_token, tick.clone());
+        }
+        // One lock per frame, never awaited here: while readers hold the
+        // cache the ticks ride along with the next frame
+        if let Ok(mut cache) = cache.try_write() {
+          cache.extend(unpublished.drain());
         }
       }
 
+      // Send to output channel (non-blocking)
+      // Fails only when nobody holds a receiver; the message is dropped
+      if output_sender.send(processed_message).is_err() {
+        log::debug!("Channel {:?}: No active receivers", channel_id);
+      }
+
       let processing_time = processing_start.elapsed();
       messages_since_last_update += 1;
 
@@ -122,6 +318,7 @@ impl MessageProcessor {
       if last_stats_update.elapsed() >= std::time::Duration::from_secs(1) {
         let mut stats_guard = stats.write().await;
         stats_guard.messages_processed += messages_since_last_update;
+        stats_guard.duplicates_dropped += duplicates_since_last_update;
         stats_guard.last_processed_time = Some(Instant::now());
         stats_guard.queue_size = input_receiver.len();
 
@@ -143,16 +340,72 @@ impl MessageProcessor {
         };
 
         drop(stats_guard);
+        Self::publish_last_values(&last_value_cache, &mut unpublished).await;
+        Self::flush_token_activity(
+          &token_activity,
+          &mut activity_since_last_update,
+        )
+        .await;
 
         // Reset counters
         last_stats_update = Instant::now();
         messages_since_last_update = 0;
+        duplicates_since_last_update = 0;
       }
     }
 
+    // Flush counts not yet reported by the periodic update
+    if messages_since_last_update > 0 || duplicates_since_last_update > 0 {
+      let mut stats_guard = stats.write().await;
+      stats_guard.messages_processed += messages_since_last_update;
+      stats_guard.duplicates_dropped += duplicates_since_last_update;
+      stats_guard.last_processed_time = Some(Instant::now());
+      stats_guard.queue_size = input_receiver.len();
+    }
+    Self::publish_last_values(&last_value_cache, &mut unpublished).await;
+    Self::flush_token_activity(
+      &token_activity,
+      &mut activity_since_last_update,
+    )
+    .await;
+
     log::info!("Message processor for channel {:?} stopped", channel_id);
   }
 
+  /// Move ticks still held back by a busy cache into it
+  async fn publish_last_values(
+    cache: &Option<LastValueCache>,
+    unpublished: &mut HashMap<u32, TickMessage>,
+  ) {
+    if let (Some(cache), false) = (cache, unpublished.is_empty()) {
+      cache.write().await.extend(unpublished.drain());
+    }
+  }
+
+  /// Add `pending` into the shared per-token activity and clear it
+  async fn flush_token_activity(
+    token_activity: &TokenActivityMap,
+    pending: &mut HashMap<u32, u64>,
+  ) {
+    if pending.is_empty() {
+      return;
+    }
+    let now = Instant::now();
+    let mut counts = token_activity.write().await;
+    for (token, n) in pending.drain() {
+      counts
+        .entry(token)
+        .and_modify(|activity| {
+          activity.ticks += n;
+          activity.last_tick = now;
+        })
+        .or_insert(TokenActivity {
+          ticks: n,
+          last_tick: now,
+        });
+    }
+  }
+
   /// Process individual message (can be extended for custom logic)
   fn process_message(
     message: TickerMessage,
@@ -209,4 +462,84 @@ impl MessageProcessor {
   }
 }
 
+/// Run `processing` on a dedicated thread pinned to `core`.
+///
+/// The returned handle finishes with the thread; aborting it cancels the
+/// loop, so `stop` works the same as for an unpinned task. If the thread or
+/// its runtime cannot be created, the loop runs unpinned in the returned
+/// task instead, so the connection's parser never stalls.
+#[cfg(feature = "core_affinity")]
+fn spawn_pinned<F, Fut>(
+  channel_id: ChannelId,
+  core: usize,
+  cancel: CancellationToken,
+  processing: F,
+) -> JoinHandle<()>
+where
+  F: FnOnce(CancellationToken) -> Fut + Send + 'static,
+  Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+  let thread_cancel = cancel.child_token();
+  let loop_cancel = thread_cancel.clone();
+  // Taken by whichever side ends up running the loop
+  let slot = Arc::new(std::sync::Mutex::new(Some(processing)));
+  let take = |slot: &std::sync::Mutex<Option<F>>| {
+    slot.lock().unwrap_or_else(|e| e.into_inner()).take()
+  };
+  let thread_slot = Arc::clone(&slot);
+  // `true` once the loop has run on the pinned thread
+  let (done_tx, done_rx) = tokio::sync::oneshot::channel::<bool>();
+  let spawned = std::thread::Builder::new()
+    .name(format!("kite-parser-{}", channel_id.to_index() + 1))
+    .spawn(move || {
+      // Pinning to a core the machine lacks aborts inside core_affinity
+      let exists = core_affinity::get_core_ids()
+        .is_some_and(|ids| ids.iter().any(|c| c.id == core));
+      if !exists
+        || !core_affinity::set_for_current(core_affinity::CoreId { id: core })
+      {
+        log::warn!(
+          "Channel {:?}: failed to pin parser to core {}",
+          channel_id,
+          core
+        );
+      }
+      match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+      {
+        Ok(runtime) => {
+          if let Some(processing) = take(&thread_slot) {
+            runtime.block_on(processing(loop_cancel));
+          }
+          let _ = done_tx.send(true);
+        }
+        Err(e) => {
+          log::error!(
+            "Channel {:?}: failed to build parser runtime: {}",
+            channel_id,
+            e
+          );
+          let _ = done_tx.send(false);
+        }
+      }
+    });
+  if let Err(e) = spawned {
+    log::error!(
+      "Channel {:?}: failed to spawn pinned parser thread: {}",
+      channel_id,
+      e
+    );
+  }
+  tokio::spawn(async move {
+    let _guard = thread_cancel.clone().drop_guard();
+    if done_rx.await != Ok(true) {
+      if let Some(processing) = take(&slot) {
+        log::warn!("Channel {:?}: running parser unpinned", channel_id);
+        processing(thread_cancel).await;
+      }
+    }
+  })
+}
+
 use std::time::Duration;
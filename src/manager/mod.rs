@@ -1,13 +1,27 @@
+pub mod circuit_breaker;
 pub mod config;
 pub mod connection_manager;
 pub mod connection_pool;
+pub mod diagnostics;
 pub mod health_monitor;
 pub mod message_processor;
 pub mod multi_api_manager;
+pub mod resync;
+pub mod router;
+pub mod sink;
+pub mod state_file;
+pub mod watchlist;
 
+pub use circuit_breaker::*;
 pub use config::*;
 pub use connection_manager::*;
 pub use connection_pool::*;
+pub use diagnostics::*;
 pub use health_monitor::*;
 pub use message_processor::*;
 pub use multi_api_manager::*;
+pub use resync::*;
+pub use router::*;
+pub use sink::*;
+pub use state_file::*;
+pub use watchlist::*;
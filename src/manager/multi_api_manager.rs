@@ -18,9 +18,12 @@ use crate::manager::{
   MultiApiConfig, MultiApiStats,
 };
 use crate::models::{Mode, TickerMessage};
+use futures_util::{stream, Stream, StreamExt};
 use std::collections::HashMap;
 use std::time::Instant;
 use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
 
 /// Connection group for a single API key
 #[derive(Debug)]
@@ -45,17 +48,19 @@ impl ApiConnectionGroup {
     }
   }
 
-  /// Find connection with available capacity using round-robin
+  /// Find connection with available capacity using round-robin, honouring
+  /// `per_connection_symbol_caps`
   fn find_available_connection(
     &mut self,
-    max_symbols_per_connection: usize,
+    config: &KiteManagerConfig,
   ) -> Option<usize> {
     let start_index = self.next_connection_index;
 
     for _ in 0..self.connections.len() {
-      let connection = &self.connections[self.next_connection_index];
+      let index = self.next_connection_index;
+      let connection = &self.connections[index];
 
-      if connection.can_accept_symbols(1, max_symbols_per_connection) {
+      if connection.can_accept_symbols(1, config.symbol_cap(index)) {
         let result = self.next_connection_index;
         self.next_connection_index =
           (self.next_connection_index + 1) % self.connections.len();
@@ -142,11 +147,19 @@ pub struct MultiApiKiteTickerManager {
   config: MultiApiConfig,
   api_groups: HashMap<ApiKeyId, ApiConnectionGroup>,
   
-  // Unified output channel across all API keys
-  unified_output_tx: broadcast::Sender<(ApiKeyId, TickerMessage)>,
+  // Unified output channel across all API keys. Held weakly: the forwarder
+  // tasks own the senders, so the channel closes once every source is gone.
+  unified_output_tx: broadcast::WeakSender<(ApiKeyId, TickerMessage)>,
+
+  // Same stream, additionally tagged with the originating connection
+  unified_detailed_tx:
+    broadcast::WeakSender<(ApiKeyId, ChannelId, TickerMessage)>,
+
+  // Strong senders kept until `start()` hands them to the forwarders
+  unified_senders: Option<UnifiedSenders>,
   
-  // Global symbol mapping: symbol -> API key
-  symbol_to_api: HashMap<u32, ApiKeyId>,
+  // Global symbol mapping: symbol -> API keys (several when duplicates are allowed)
+  symbol_to_api: HashMap<u32, Vec<ApiKeyId>>,
   
   // Round-robin index for API key selection
   next_api_index: usize,
@@ -155,6 +168,17 @@ pub struct MultiApiKiteTickerManager {
   start_time: Instant,
 }
 
+#[derive(Debug)]
+struct UnifiedSenders {
+  output: broadcast::Sender<(ApiKeyId, TickerMessage)>,
+  detailed: broadcast::Sender<(ApiKeyId, ChannelId, TickerMessage)>,
+}
+
+/// Receiver whose sender is already gone, so `recv` reports `Closed`
+fn closed_receiver<T: Clone>() -> broadcast::Receiver<T> {
+  broadcast::channel(1).1
+}
+
 /// Builder for `MultiApiKiteTickerManager` providing a fluent API for configuration.
 #[derive(Debug, Clone)]
 pub struct MultiApiKiteTickerManagerBuilder {
@@ -226,6 +250,12 @@ impl MultiApiKiteTickerManagerBuilder {
     self
   }
 
+  /// Allow the same token on multiple API keys for redundant streaming
+  pub fn allow_duplicate_across_apis(mut self, allow: bool) -> Self {
+    self.config.allow_duplicate_across_apis = allow;
+    self
+  }
+
   /// Set default subscription mode
   pub fn default_mode(mut self, mode: Mode) -> Self {
     self.config.base_config.default_mode = mode;
@@ -257,6 +287,8 @@ impl MultiApiKiteTickerManager {
   ) -> Self {
     let (unified_output_tx, _) =
       broadcast::channel(config.base_config.parser_buffer_size);
+    let (unified_detailed_tx, _) =
+      broadcast::channel(config.base_config.parser_buffer_size);
 
     let api_key_order: Vec<ApiKeyId> = api_credentials.keys().cloned().collect();
 
@@ -268,7 +300,12 @@ impl MultiApiKiteTickerManager {
     Self {
       config,
       api_groups,
-      unified_output_tx,
+      unified_output_tx: unified_output_tx.downgrade(),
+      unified_detailed_tx: unified_detailed_tx.downgrade(),
+      unified_senders: Some(UnifiedSenders {
+        output: unified_output_tx,
+        detailed: unified_detailed_tx,
+      }),
       symbol_to_api: HashMap::new(),
       next_api_index: 0,
       api_key_order,
@@ -287,10 +324,14 @@ impl MultiApiKiteTickerManager {
       self.api_groups.len()
     );
 
-    // Clone the unified output sender before iterating
-    let unified_tx = self.unified_output_tx.clone();
+    if self.unified_senders.is_none() {
+      return Err("MultiApiKiteTickerManager already started".to_string());
+    }
 
-    for (api_key_id, group) in &mut self.api_groups {
+    // Create every account's connections up front so they can all connect
+    // concurrently; startup then doesn't grow with the number of accounts
+    let mut pending = Vec::new();
+    for api_key_id in self.api_groups.keys() {
       log::info!(
         "Initializing {} connections for API key: {}",
         self.config.max_connections_per_api,
@@ -305,52 +346,110 @@ impl MultiApiKiteTickerManager {
         let (connection_sender, processor_receiver) = mpsc::unbounded_channel();
 
         // Create managed connection
-        let mut connection =
-          ManagedConnection::new(channel_id, connection_sender);
-
-        // Connect to WebSocket
-        connection
-          .connect(
-            &group.credentials.api_key,
-            &group.credentials.access_token,
-            &self.config.base_config,
-          )
-          .await
-          .map_err(|e| {
-            format!(
-              "Failed to connect WebSocket {} for API key {}: {}",
-              i, api_key_id.0, e
+        let connection = ManagedConnection::new(channel_id, connection_sender);
+        pending.push((api_key_id.clone(), connection, processor_receiver));
+      }
+    }
+
+    // Connect WebSockets concurrently; each handshake has its own timeout
+    let (api_groups, base_config) =
+      (&self.api_groups, &self.config.base_config);
+    let results: Vec<Result<(), String>> =
+      stream::iter(pending.iter_mut().map(|(api_key_id, connection, _)| {
+        let credentials = &api_groups[&*api_key_id].credentials;
+        async move {
+          let i = connection.id.to_index();
+          connection
+            .connect(
+              &credentials.api_key,
+              &credentials.access_token,
+              base_config,
             )
-          })?;
+            .await
+            .map_err(|e| {
+              format!(
+                "Failed to connect WebSocket {} for API key {}: {}",
+                i, api_key_id.0, e
+              )
+            })?;
+          // Don't report success for a socket the server already dropped
+          if !connection.is_connected() {
+            return Err(format!(
+              "WebSocket {} for API key {} closed right after connecting",
+              i, api_key_id.0
+            ));
+          }
+          Ok(())
+        }
+      }))
+      .buffered(base_config.startup_concurrency.max(1))
+      .collect()
+      .await;
+    let errors: Vec<String> =
+      results.into_iter().filter_map(Result::err).collect();
+    if !errors.is_empty() {
+      // Close the sockets that did open; left behind they would count
+      // against Kite's per-key connection limit when start() is retried
+      for (_, connection, _) in &mut pending {
+        connection.reset(CancellationToken::new()).await;
+      }
+      return Err(errors.join("; "));
+    }
 
-        // Create message processor with unified output
-        let (mut processor, output_receiver) = MessageProcessor::new(
-          channel_id,
-          processor_receiver,
-          self.config.base_config.parser_buffer_size,
-        );
+    // Move the unified senders into the forwarders; once they all exit the
+    // unified channels close
+    let UnifiedSenders {
+      output: unified_tx,
+      detailed: detailed_tx,
+    } = self
+      .unified_senders
+      .take()
+      .ok_or_else(|| "MultiApiKiteTickerManager already started".to_string())?;
+
+    for (api_key_id, connection, processor_receiver) in pending {
+      let channel_id = connection.id;
+      let i = channel_id.to_index();
+      let Some(group) = self.api_groups.get_mut(&api_key_id) else {
+        continue;
+      };
 
-        // Start processor if enabled
-        if self.config.base_config.enable_dedicated_parsers {
-          processor.start();
-          log::info!(
-            "Started dedicated parser for API key {} connection {}",
-            api_key_id.0,
-            i
-          );
-        }
+      // Create message processor with unified output
+      let (mut processor, output_receiver) = MessageProcessor::new(
+        channel_id,
+        processor_receiver,
+        self.config.base_config.parser_buffer_size,
+      );
+      if let Some(core) = self.config.base_config.parser_core(i) {
+        processor = processor.with_core_affinity(core);
+      }
+      if self.config.base_config.coalesce_duplicate_ticks {
+        processor = processor.with_duplicate_coalescing();
+      }
 
-        // Forward messages from this processor to unified channel
-        Self::spawn_message_forwarder_static(
-          unified_tx.clone(),
-          api_key_id.clone(),
-          output_receiver,
+      // Start processor if enabled
+      if self.config.base_config.enable_dedicated_parsers {
+        processor.start();
+        log::info!(
+          "Started dedicated parser for API key {} connection {}",
+          api_key_id.0,
+          i
         );
-
-        group.connections.push(connection);
-        group.processors.push(processor);
       }
 
+      // Forward messages from this processor to unified channel
+      Self::spawn_message_forwarder_static(
+        unified_tx.clone(),
+        detailed_tx.clone(),
+        api_key_id.clone(),
+        channel_id,
+        output_receiver,
+      );
+
+      group.connections.push(connection);
+      group.processors.push(processor);
+    }
+
+    for (api_key_id, group) in &self.api_groups {
       log::info!(
         "Initialized {} connections for API key: {}",
         group.connections.len(),
@@ -367,15 +466,28 @@ impl MultiApiKiteTickerManager {
   }
 
   /// Spawn a task to forward messages from a processor to the unified channel (static version)
+  ///
+  /// The task ends when the processor's output closes, dropping its share of
+  /// the unified senders.
   fn spawn_message_forwarder_static(
     tx: broadcast::Sender<(ApiKeyId, TickerMessage)>,
+    detailed_tx: broadcast::Sender<(ApiKeyId, ChannelId, TickerMessage)>,
     api_key_id: ApiKeyId,
+    channel_id: ChannelId,
     mut receiver: broadcast::Receiver<TickerMessage>,
   ) {
     tokio::spawn(async move {
       loop {
         match receiver.recv().await {
           Ok(msg) => {
+            // Only pay for the extra clone when someone is listening
+            if detailed_tx.receiver_count() > 0 {
+              let _ = detailed_tx.send((
+                api_key_id.clone(),
+                channel_id,
+                msg.clone(),
+              ));
+            }
             // Forward to unified channel with API key identifier
             let _ = tx.send((api_key_id.clone(), msg));
           }
@@ -441,6 +553,10 @@ impl MultiApiKiteTickerManager {
   }
 
   /// Subscribe symbols to a specific API key
+  ///
+  /// A token already subscribed on another API key is rejected unless
+  /// `allow_duplicate_across_apis` is enabled, in which case it is streamed on
+  /// both and each copy is tagged with its `ApiKeyId` on the unified channel.
   pub async fn subscribe_symbols_to_api(
     &mut self,
     api_key_id: impl Into<ApiKeyId>,
@@ -478,6 +594,23 @@ impl MultiApiKiteTickerManager {
     symbol: u32,
     mode: Mode,
   ) -> Result<(), String> {
+    if let Some(owners) = self.symbol_to_api.get(&symbol) {
+      if owners.contains(api_key_id) {
+        log::debug!(
+          "Symbol {} already subscribed on API key {}",
+          symbol,
+          api_key_id.0
+        );
+        return Ok(());
+      }
+      if !self.config.allow_duplicate_across_apis {
+        return Err(format!(
+          "Symbol {} already subscribed on API key {}",
+          symbol, owners[0].0
+        ));
+      }
+    }
+
     let group = self
       .api_groups
       .get_mut(api_key_id)
@@ -485,9 +618,7 @@ impl MultiApiKiteTickerManager {
 
     // Find available connection
     let connection_index = group
-      .find_available_connection(
-        self.config.base_config.max_symbols_per_connection,
-      )
+      .find_available_connection(&self.config.base_config)
       .ok_or_else(|| {
         format!(
           "All connections at capacity for API key: {}",
@@ -530,7 +661,11 @@ impl MultiApiKiteTickerManager {
     group
       .subscribed_symbols
       .insert(symbol, (connection_index, mode));
-    self.symbol_to_api.insert(symbol, api_key_id.clone());
+    self
+      .symbol_to_api
+      .entry(symbol)
+      .or_default()
+      .push(api_key_id.clone());
 
     Ok(())
   }
@@ -549,9 +684,7 @@ impl MultiApiKiteTickerManager {
       if let Some(group) = self.api_groups.get_mut(api_key_id) {
         // Check if this API key has capacity
         let has_capacity = group
-          .find_available_connection(
-            self.config.base_config.max_symbols_per_connection,
-          )
+          .find_available_connection(&self.config.base_config)
           .is_some();
 
         if has_capacity {
@@ -582,7 +715,7 @@ impl MultiApiKiteTickerManager {
     let mut api_symbols: HashMap<ApiKeyId, Vec<u32>> = HashMap::new();
 
     for &symbol in symbols {
-      if let Some(api_key_id) = self.symbol_to_api.get(&symbol) {
+      for api_key_id in self.symbol_to_api.get(&symbol).into_iter().flatten() {
         api_symbols
           .entry(api_key_id.clone())
           .or_default()
@@ -616,7 +749,12 @@ impl MultiApiKiteTickerManager {
           // Update group mappings
           for symbol in symbols {
             group.subscribed_symbols.remove(&symbol);
-            self.symbol_to_api.remove(&symbol);
+            if let Some(owners) = self.symbol_to_api.get_mut(&symbol) {
+              owners.retain(|id| id != &api_key_id);
+              if owners.is_empty() {
+                self.symbol_to_api.remove(&symbol);
+              }
+            }
           }
         }
       }
@@ -639,7 +777,7 @@ impl MultiApiKiteTickerManager {
       HashMap::new();
 
     for &symbol in symbols {
-      if let Some(api_key_id) = self.symbol_to_api.get(&symbol) {
+      for api_key_id in self.symbol_to_api.get(&symbol).into_iter().flatten() {
         if let Some(group) = self.api_groups.get(api_key_id) {
           if let Some((conn_idx, _)) = group.subscribed_symbols.get(&symbol) {
             api_symbols
@@ -658,18 +796,19 @@ impl MultiApiKiteTickerManager {
       if let Some(group) = self.api_groups.get_mut(&api_key_id) {
         for (conn_idx, symbols) in conn_symbols {
           if let Some(connection) = group.connections.get_mut(conn_idx) {
-            if let Some(ref cmd) = connection.cmd_tx {
-              let mode_req =
-                crate::models::Request::mode(mode, &symbols).to_string();
-              let _ = cmd.send(
-                tokio_tungstenite::tungstenite::Message::Text(mode_req.into()),
-              );
-
-              // Update local tracking
+            if let Some(cmd) = connection.command_sender() {
+              // Update local tracking (and hints) before the request goes out
               for &symbol in &symbols {
                 connection.subscribed_symbols.insert(symbol, mode);
                 group.subscribed_symbols.insert(symbol, (conn_idx, mode));
               }
+              connection.sync_mode_hints();
+
+              let mode_req =
+                crate::models::Request::mode(mode, &symbols).to_string();
+              let _ = cmd.send(tokio_tungstenite::tungstenite::Message::Text(
+                mode_req.into(),
+              ));
             }
           }
         }
@@ -682,11 +821,46 @@ impl MultiApiKiteTickerManager {
 
   /// Get the unified output channel that receives messages from all API keys
   ///
-  /// Messages are tuples of (ApiKeyId, TickerMessage)
+  /// Messages are tuples of (ApiKeyId, TickerMessage). `recv` returns
+  /// `RecvError::Closed` once every connection's processor has shut down
+  /// (after `stop()`).
   pub fn get_unified_channel(
     &self,
   ) -> broadcast::Receiver<(ApiKeyId, TickerMessage)> {
-    self.unified_output_tx.subscribe()
+    self
+      .unified_output_tx
+      .upgrade()
+      .map_or_else(closed_receiver, |tx| tx.subscribe())
+  }
+
+  /// Stream over the unified channel, for combinator-style consumption
+  ///
+  /// A consumer that falls behind the broadcast buffer gets one `Err` with
+  /// the number of skipped messages and the stream carries on. Dropping the
+  /// stream unsubscribes it; it ends once all sources have closed (after
+  /// `stop()` or when the manager is dropped).
+  pub fn unified_stream(
+    &self,
+  ) -> impl Stream<Item = Result<(ApiKeyId, TickerMessage), String>> + Send + 'static
+  {
+    BroadcastStream::new(self.get_unified_channel()).map(|item| {
+      item.map_err(|BroadcastStreamRecvError::Lagged(skipped)| {
+        format!("Unified stream lagged, skipped {} messages", skipped)
+      })
+    })
+  }
+
+  /// Get the unified output channel with per-connection granularity
+  ///
+  /// Messages are tuples of (ApiKeyId, ChannelId, TickerMessage), which helps
+  /// pin down which connection of which account produced a message.
+  pub fn get_unified_channel_detailed(
+    &self,
+  ) -> broadcast::Receiver<(ApiKeyId, ChannelId, TickerMessage)> {
+    self
+      .unified_detailed_tx
+      .upgrade()
+      .map_or_else(closed_receiver, |tx| tx.subscribe())
   }
 
   /// Get output channel for a specific API key and connection
@@ -731,6 +905,71 @@ impl MultiApiKiteTickerManager {
     stats
   }
 
+  /// Swap in new credentials for one API key (e.g. after its daily token
+  /// refresh) and reconnect only that key's connections, restoring their
+  /// subscriptions. Other API keys keep streaming undisturbed.
+  ///
+  /// Before `start()` this only records the credentials.
+  ///
+  /// Every connection of the key is moved to the new credentials, even if
+  /// one of them fails to come back; none is left on an old socket. A
+  /// failed connection stays down with its subscriptions kept, and the
+  /// error names it. Calling again with the same credentials retries only
+  /// the connections that are not restored yet.
+  pub async fn update_credentials(
+    &mut self,
+    api_key_id: impl Into<ApiKeyId>,
+    new_api_key: impl Into<String>,
+    new_access_token: impl Into<String>,
+  ) -> Result<(), String> {
+    let api_key_id = api_key_id.into();
+    let group = self
+      .api_groups
+      .get_mut(&api_key_id)
+      .ok_or_else(|| format!("API key not found: {}", api_key_id.0))?;
+    group.credentials = ApiCredentials::new(new_api_key, new_access_token);
+    let (api_key, access_token) =
+      (&group.credentials.api_key, &group.credentials.access_token);
+
+    let mut failed = Vec::new();
+    for (i, connection) in group.connections.iter_mut().enumerate() {
+      if connection.is_restored_with(api_key, access_token) {
+        continue;
+      }
+      connection.set_credentials(api_key, access_token);
+      connection.reset(CancellationToken::new()).await;
+      let restored =
+        match connection.reconnect_socket(&self.config.base_config).await {
+          Ok(()) => connection.resubscribe_all().await,
+          Err(e) => Err(e),
+        };
+      if let Err(e) = restored {
+        log::warn!(
+          "API key {} connection {} did not come back with new \
+           credentials: {}",
+          api_key_id.0,
+          i,
+          e
+        );
+        failed.push(format!("WebSocket {}: {}", i, e));
+      }
+    }
+    if !failed.is_empty() {
+      return Err(format!(
+        "Failed to reconnect for API key {} with new credentials: {}",
+        api_key_id.0,
+        failed.join("; ")
+      ));
+    }
+
+    log::info!(
+      "Rotated credentials for API key {} ({} connections)",
+      api_key_id.0,
+      group.connections.len()
+    );
+    Ok(())
+  }
+
   /// Get statistics for a specific API key
   pub async fn get_api_stats(
     &self,
@@ -771,6 +1010,20 @@ impl MultiApiKiteTickerManager {
     self.api_key_order.clone()
   }
 
+  /// Masked API key (first 4 characters + `***`) of every configured account
+  pub fn api_key_fingerprints(&self) -> Vec<(ApiKeyId, String)> {
+    self
+      .api_key_order
+      .iter()
+      .filter_map(|id| {
+        self
+          .api_groups
+          .get(id)
+          .map(|group| (id.clone(), group.credentials.fingerprint()))
+      })
+      .collect()
+  }
+
   /// Stop the manager and all connections
   pub async fn stop(&mut self) -> Result<(), String> {
     log::info!("Stopping MultiApiKiteTickerManager");
@@ -778,10 +1031,12 @@ impl MultiApiKiteTickerManager {
     for (api_key_id, group) in &mut self.api_groups {
       log::info!("Stopping connections for API key: {}", api_key_id.0);
 
-      // Stop all processors
+      // Stop all processors, then drop them so their forwarders see the
+      // output close and release the unified senders
       for processor in &mut group.processors {
         processor.stop().await;
       }
+      group.processors.clear();
 
       // Stop all connections
       for connection in &mut group.connections {
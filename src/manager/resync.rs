@@ -0,0 +1,105 @@
+//! # Resynchronising Receivers
+//!
+//! A broadcast receiver that falls behind loses ticks (`RecvError::Lagged`).
+//! [`ResyncReceiver`] wraps a connection's channel and, after a lag, can
+//! replay the last-value cache for every token of the connection so a
+//! consumer that keeps per-symbol state (e.g. order books) starts from
+//! current values again.
+//!
+//! ```rust,no_run
+//! # use kiteticker_async_manager::{ChannelId, KiteTickerManager};
+//! # use tokio::sync::broadcast::error::RecvError;
+//! # async fn run(manager: &mut KiteTickerManager) -> Result<(), String> {
+//! let mut receiver = manager.get_resync_channel(ChannelId::Connection1)?;
+//! loop {
+//!   match receiver.recv().await {
+//!     Ok(message) => { /* apply ticks or snapshot */ }
+//!     Err(RecvError::Lagged(_)) => {
+//!       receiver.resync().await;
+//!     }
+//!     Err(RecvError::Closed) => break,
+//!   }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::manager::{ChannelId, LastValueCache};
+use crate::models::{TickMessage, TickerMessage};
+use std::collections::HashSet;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Channel receiver that can replay cached ticks after falling behind
+#[derive(Debug)]
+pub struct ResyncReceiver {
+  channel_id: ChannelId,
+  receiver: broadcast::Receiver<TickerMessage>,
+  cache: LastValueCache,
+  /// Tokens subscribed when the receiver was created plus every token seen
+  /// on the channel since
+  tokens: HashSet<u32>,
+  /// Snapshot queued by `resync`, returned by the next `recv`
+  pending: Option<TickerMessage>,
+}
+
+impl ResyncReceiver {
+  pub(crate) fn new(
+    channel_id: ChannelId,
+    receiver: broadcast::Receiver<TickerMessage>,
+    cache: LastValueCache,
+    tokens: impl IntoIterator<Item = u32>,
+  ) -> Self {
+    Self {
+      channel_id,
+      receiver,
+      cache,
+      tokens: tokens.into_iter().collect(),
+      pending: None,
+    }
+  }
+
+  /// Connection this receiver listens to
+  pub fn channel_id(&self) -> ChannelId {
+    self.channel_id
+  }
+
+  /// Next message; a snapshot queued by [`resync`](Self::resync) comes
+  /// first. Lags are returned as-is so the caller decides when to resync.
+  pub async fn recv(&mut self) -> Result<TickerMessage, RecvError> {
+    let message = match self.pending.take() {
+      Some(snapshot) => snapshot,
+      None => self.receiver.recv().await?,
+    };
+    if let TickerMessage::Ticks(ticks) | TickerMessage::Snapshot(ticks) =
+      &message
+    {
+      self.tokens.extend(ticks.iter().map(|t| t.instrument_token));
+    }
+    Ok(message)
+  }
+
+  /// Queue a [`TickerMessage::Snapshot`] of the cached tick of every token
+  /// on this connection, delivered by the next `recv` to this receiver
+  /// only. Returns the number of ticks queued.
+  ///
+  /// Tokens unsubscribed since they were last seen are included until the
+  /// cache forgets them.
+  pub async fn resync(&mut self) -> usize {
+    let ticks: Vec<TickMessage> = {
+      let cache = self.cache.read().await;
+      self
+        .tokens
+        .iter()
+        .filter_map(|t| cache.get(t).cloned())
+        .collect()
+    };
+    let count = ticks.len();
+    log::debug!(
+      "Resyncing {} cached ticks on connection {:?}",
+      count,
+      self.channel_id
+    );
+    self.pending = (count > 0).then_some(TickerMessage::Snapshot(ticks));
+    count
+  }
+}
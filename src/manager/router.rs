@@ -0,0 +1,141 @@
+//! # Symbol Routing
+//!
+//! Decides which connection a newly subscribed symbol is placed on. The
+//! manager ships with round-robin (the default) and least-loaded routers;
+//! custom routers (e.g. grouping by exchange or sector) implement
+//! [`SymbolRouter`] and are installed with
+//! [`KiteTickerManagerBuilder::router`](crate::KiteTickerManagerBuilder::router).
+
+use crate::manager::{ChannelId, KiteManagerConfig, ManagedConnection};
+use std::collections::HashMap;
+
+/// Read-only view of the current symbol distribution, passed to routers
+#[derive(Debug)]
+pub struct DistributionView<'a> {
+  connections: &'a [ManagedConnection],
+  /// Symbols already routed in the current batch but not yet subscribed
+  pending: &'a HashMap<ChannelId, Vec<u32>>,
+  config: &'a KiteManagerConfig,
+}
+
+impl<'a> DistributionView<'a> {
+  /// View over `connections`, counting `pending` as already placed.
+  ///
+  /// The manager builds one per subscription batch; build your own to unit
+  /// test a [`SymbolRouter`].
+  pub fn new(
+    connections: &'a [ManagedConnection],
+    pending: &'a HashMap<ChannelId, Vec<u32>>,
+    config: &'a KiteManagerConfig,
+  ) -> Self {
+    Self {
+      connections,
+      pending,
+      config,
+    }
+  }
+
+  /// Number of connections managed (connected or not)
+  pub fn connection_count(&self) -> usize {
+    self.connections.len()
+  }
+
+  /// Symbols on `channel`, including those routed earlier in this batch
+  pub fn symbol_count(&self, channel: ChannelId) -> usize {
+    let subscribed = self
+      .connections
+      .get(channel.to_index())
+      .map_or(0, |c| c.symbol_count());
+    subscribed + self.pending.get(&channel).map_or(0, Vec::len)
+  }
+
+  /// Most symbols `channel` may carry
+  pub fn capacity(&self, channel: ChannelId) -> usize {
+    self.config.symbol_cap(channel.to_index())
+  }
+
+  /// Whether `channel` is connected, or reconnecting and queuing
+  /// subscriptions, and can take another symbol
+  pub fn has_capacity(&self, channel: ChannelId) -> bool {
+    self.connections.get(channel.to_index()).is_some_and(|c| {
+      (c.ticker.is_some() || c.is_reconnecting())
+        && self.symbol_count(channel) < self.capacity(channel)
+    })
+  }
+
+  /// Connections that can take another symbol, in index order
+  pub fn available(&self) -> impl Iterator<Item = ChannelId> + '_ {
+    self
+      .connections
+      .iter()
+      .map(|c| c.id)
+      .filter(|&id| self.has_capacity(id))
+  }
+}
+
+/// Chooses the connection for each newly subscribed symbol
+///
+/// Returning `None`, or a connection without capacity, fails the
+/// subscription for that symbol.
+pub trait SymbolRouter: Send + std::fmt::Debug {
+  fn route(
+    &mut self,
+    token: u32,
+    current: &DistributionView<'_>,
+  ) -> Option<ChannelId>;
+
+  /// Copy of this router in its current state, so
+  /// [`preview_subscribe`](crate::KiteTickerManager::preview_subscribe) can
+  /// place symbols without moving the real router on. Routers returning
+  /// `None` (the default) are previewed with [`LeastLoadedRouter`].
+  fn clone_router(&self) -> Option<Box<dyn SymbolRouter>> {
+    None
+  }
+}
+
+/// Cycle through connections, skipping those without capacity (default)
+#[derive(Debug, Clone, Default)]
+pub struct RoundRobinRouter {
+  next: usize,
+}
+
+impl SymbolRouter for RoundRobinRouter {
+  fn route(
+    &mut self,
+    _token: u32,
+    current: &DistributionView<'_>,
+  ) -> Option<ChannelId> {
+    let count = current.connection_count();
+    for _ in 0..count {
+      let index = self.next % count;
+      self.next = (index + 1) % count;
+      let channel = ChannelId::from_index(index)?;
+      if current.has_capacity(channel) {
+        return Some(channel);
+      }
+    }
+    None
+  }
+
+  fn clone_router(&self) -> Option<Box<dyn SymbolRouter>> {
+    Some(Box::new(self.clone()))
+  }
+}
+
+/// Place each symbol on the connection with the fewest symbols
+#[derive(Debug, Clone, Default)]
+pub struct LeastLoadedRouter;
+
+impl SymbolRouter for LeastLoadedRouter {
+  fn route(
+    &mut self,
+    _token: u32,
+    current: &DistributionView<'_>,
+  ) -> Option<ChannelId> {
+    current.available().min_by_key(|&c| current.symbol_count(c))
+  }
+
+  fn clone_router(&self) -> Option<Box<dyn SymbolRouter>> {
+    Some(Box::new(self.clone()))
+  }
+}
@@ -0,0 +1,68 @@
+//! # Tick Sinks
+//!
+//! A uniform way to push ticks somewhere else (Kafka, Redis, files).
+//! Implement [`TickSink`], or wrap a closure in [`FnSink`], and install it
+//! with [`KiteTickerManager::attach_sink`](crate::KiteTickerManager::attach_sink).
+//!
+//! ```rust,no_run
+//! # use kiteticker_async_manager::{FnSink, KiteTickerManager};
+//! # use std::sync::Arc;
+//! # fn run(manager: &KiteTickerManager) {
+//! manager.attach_sink(Arc::new(FnSink::new(|channel, ticks| {
+//!   println!("{:?}: {} ticks", channel, ticks.len());
+//! })));
+//! # }
+//! ```
+
+use crate::manager::ChannelId;
+use crate::models::TickMessage;
+use futures_util::future::BoxFuture;
+use std::fmt;
+
+/// Destination for the ticks of every connection
+///
+/// `send` is awaited before the next batch of the same connection is
+/// delivered, so a slow sink makes its consumer lag (and skip batches)
+/// rather than slowing the connection itself.
+pub trait TickSink: Send + Sync {
+  /// Deliver one batch of ticks received on `channel`
+  fn send<'a>(
+    &'a self,
+    channel: ChannelId,
+    ticks: &'a [TickMessage],
+  ) -> BoxFuture<'a, ()>;
+}
+
+/// [`TickSink`] calling a synchronous closure for each batch
+pub struct FnSink<F> {
+  f: F,
+}
+
+impl<F> FnSink<F>
+where
+  F: Fn(ChannelId, &[TickMessage]) + Send + Sync,
+{
+  pub fn new(f: F) -> Self {
+    Self { f }
+  }
+}
+
+impl<F> TickSink for FnSink<F>
+where
+  F: Fn(ChannelId, &[TickMessage]) + Send + Sync,
+{
+  fn send<'a>(
+    &'a self,
+    channel: ChannelId,
+    ticks: &'a [TickMessage],
+  ) -> BoxFuture<'a, ()> {
+    (self.f)(channel, ticks);
+    Box::pin(std::future::ready(()))
+  }
+}
+
+impl<F> fmt::Debug for FnSink<F> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FnSink").finish_non_exhaustive()
+  }
+}
@@ -0,0 +1,100 @@
+//! # Subscription State File
+//!
+//! Persists the manager's subscription set to disk so it can be restored after
+//! a full process restart. The file is a small JSON document listing every
+//! subscribed instrument token together with its mode.
+
+use crate::models::Mode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A single persisted subscription entry
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionEntry {
+  pub instrument_token: u32,
+  pub mode: Mode,
+}
+
+/// Snapshot of all subscriptions held by a manager
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubscriptionState {
+  pub subscriptions: Vec<SubscriptionEntry>,
+}
+
+impl SubscriptionState {
+  /// Build a snapshot from a token -> mode map (sorted by token for stable output)
+  pub fn from_map(map: &HashMap<u32, Mode>) -> Self {
+    let mut subscriptions: Vec<SubscriptionEntry> = map
+      .iter()
+      .map(|(&instrument_token, &mode)| SubscriptionEntry {
+        instrument_token,
+        mode,
+      })
+      .collect();
+    subscriptions.sort_by_key(|e| e.instrument_token);
+    Self { subscriptions }
+  }
+
+  /// Group tokens by mode, preserving mode order LTP, Quote, Full
+  pub fn tokens_by_mode(&self) -> Vec<(Mode, Vec<u32>)> {
+    let mut grouped: Vec<(Mode, Vec<u32>)> = Vec::new();
+    for mode in [Mode::LTP, Mode::Quote, Mode::Full] {
+      let tokens: Vec<u32> = self
+        .subscriptions
+        .iter()
+        .filter(|e| e.mode == mode)
+        .map(|e| e.instrument_token)
+        .collect();
+      if !tokens.is_empty() {
+        grouped.push((mode, tokens));
+      }
+    }
+    grouped
+  }
+
+  /// Load a snapshot from disk.
+  ///
+  /// A missing or unreadable/corrupt file is logged and treated as empty.
+  pub async fn load(path: &Path) -> Self {
+    let contents = match tokio::fs::read(path).await {
+      Ok(c) => c,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+        log::info!("No subscription state file at {}", path.display());
+        return Self::default();
+      }
+      Err(e) => {
+        log::warn!(
+          "Failed to read subscription state file {}: {}",
+          path.display(),
+          e
+        );
+        return Self::default();
+      }
+    };
+    match serde_json::from_slice::<SubscriptionState>(&contents) {
+      Ok(state) => state,
+      Err(e) => {
+        log::warn!(
+          "Ignoring corrupt subscription state file {}: {}",
+          path.display(),
+          e
+        );
+        Self::default()
+      }
+    }
+  }
+
+  /// Write the snapshot to disk, replacing the previous file atomically
+  pub async fn save(&self, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    tokio::fs::write(&tmp, json)
+      .await
+      .map_err(|e| format!("Failed to write {:?}: {}", tmp, e))?;
+    tokio::fs::rename(&tmp, path)
+      .await
+      .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+  }
+}
@@ -0,0 +1,257 @@
+//! # Watchlists
+//!
+//! Named groups of tokens (e.g. `"nifty50"`, `"banknifty_options"`) on top
+//! of a [`KiteTickerManager`]. Groups may overlap: a token stays subscribed
+//! while any group still holds it, in the highest mode any of them asks for.
+//!
+//! ```rust,no_run
+//! # use kiteticker_async_manager::{KiteTickerManager, Mode, WatchlistManager};
+//! # async fn run(manager: KiteTickerManager) -> Result<(), String> {
+//! let mut watchlists = WatchlistManager::new(manager);
+//! watchlists.add_group("nifty50", &[256265, 408065], Mode::Quote).await?;
+//! watchlists.add_group("movers", &[408065, 738561], Mode::Full).await?;
+//! // 408065 is still held by "movers" and stays subscribed (in Full)
+//! watchlists.remove_group("nifty50").await?;
+//! assert!(watchlists.manager().is_subscribed(408065));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::errors::KiteError;
+use crate::manager::KiteTickerManager;
+use crate::models::Mode;
+use std::collections::{HashMap, HashSet};
+
+/// Tokens of one named group and the mode it wants them in
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchlistGroup {
+  pub tokens: HashSet<u32>,
+  pub mode: Mode,
+}
+
+/// Named, possibly overlapping token groups mapped onto a manager's
+/// subscriptions
+#[derive(Debug)]
+pub struct WatchlistManager {
+  manager: KiteTickerManager,
+  groups: HashMap<String, WatchlistGroup>,
+}
+
+impl WatchlistManager {
+  /// Manage groups on `manager`; tokens it already subscribed are left alone
+  /// unless a group later claims and releases them
+  pub fn new(manager: KiteTickerManager) -> Self {
+    Self {
+      manager,
+      groups: HashMap::new(),
+    }
+  }
+
+  /// Add a new group; errors if `name` already exists
+  pub async fn add_group(
+    &mut self,
+    name: &str,
+    tokens: &[u32],
+    mode: Mode,
+  ) -> Result<(), KiteError> {
+    if self.groups.contains_key(name) {
+      return Err(KiteError::Other(format!(
+        "Watchlist group '{}' already exists; use replace_group",
+        name
+      )));
+    }
+    self.replace_group(name, tokens, mode).await
+  }
+
+  /// Remove a group, unsubscribing tokens no other group holds
+  ///
+  /// On failure the group is kept and the manager's subscriptions are
+  /// rolled back.
+  pub async fn remove_group(&mut self, name: &str) -> Result<(), KiteError> {
+    let before = self.wanted_modes();
+    let Some(removed) = self.groups.remove(name) else {
+      return Err(KiteError::Other(format!(
+        "Unknown watchlist group '{}'",
+        name
+      )));
+    };
+    if let Err(e) = self.apply(&before).await {
+      self.groups.insert(name.to_string(), removed);
+      return Err(e);
+    }
+    Ok(())
+  }
+
+  /// Create or overwrite a group, subscribing only the difference
+  ///
+  /// On failure the previous group (if any) is restored and what was
+  /// already subscribed for the new one is unsubscribed again.
+  pub async fn replace_group(
+    &mut self,
+    name: &str,
+    tokens: &[u32],
+    mode: Mode,
+  ) -> Result<(), KiteError> {
+    let before = self.wanted_modes();
+    let group = WatchlistGroup {
+      tokens: tokens.iter().copied().collect(),
+      mode,
+    };
+    let previous = self.groups.insert(name.to_string(), group);
+    if let Err(e) = self.apply(&before).await {
+      match previous {
+        Some(previous) => self.groups.insert(name.to_string(), previous),
+        None => self.groups.remove(name),
+      };
+      return Err(e);
+    }
+    Ok(())
+  }
+
+  /// Group `name`, if it exists
+  pub fn group(&self, name: &str) -> Option<&WatchlistGroup> {
+    self.groups.get(name)
+  }
+
+  /// Names of all groups
+  pub fn group_names(&self) -> impl Iterator<Item = &str> {
+    self.groups.keys().map(String::as_str)
+  }
+
+  /// Number of groups holding `token`
+  pub fn ref_count(&self, token: u32) -> usize {
+    self
+      .groups
+      .values()
+      .filter(|g| g.tokens.contains(&token))
+      .count()
+  }
+
+  /// The underlying manager, e.g. for `get_channel`
+  pub fn manager(&self) -> &KiteTickerManager {
+    &self.manager
+  }
+
+  /// Mutable access to the underlying manager. Subscribing group tokens
+  /// directly bypasses the reference counts.
+  pub fn manager_mut(&mut self) -> &mut KiteTickerManager {
+    &mut self.manager
+  }
+
+  /// Give the manager back, leaving its subscriptions as they are
+  pub fn into_inner(self) -> KiteTickerManager {
+    self.manager
+  }
+
+  /// Highest mode each token is wanted in across all groups
+  fn wanted_modes(&self) -> HashMap<u32, Mode> {
+    let mut wanted: HashMap<u32, Mode> = HashMap::new();
+    for group in self.groups.values() {
+      for &token in &group.tokens {
+        wanted
+          .entry(token)
+          .and_modify(|m| {
+            if group.mode > *m {
+              *m = group.mode;
+            }
+          })
+          .or_insert(group.mode);
+      }
+    }
+    wanted
+  }
+
+  /// Bring the manager from `before` to the current groups' wanted state
+  ///
+  /// If a step fails, the steps already applied are undone so the manager
+  /// is back at `before`; undoing is best effort and logs what it cannot
+  /// restore.
+  async fn apply(
+    &mut self,
+    before: &HashMap<u32, Mode>,
+  ) -> Result<(), KiteError> {
+    let after = self.wanted_modes();
+    // Mode has no Hash, so batch per mode in a small list
+    let mut subscribe: Vec<(Mode, Vec<u32>)> = Vec::new();
+    let mut change: Vec<(Mode, Vec<u32>)> = Vec::new();
+    for (&token, &mode) in &after {
+      let batches = match before.get(&token) {
+        None => &mut subscribe,
+        Some(&old) if old != mode => &mut change,
+        Some(_) => continue,
+      };
+      match batches.iter_mut().find(|(m, _)| *m == mode) {
+        Some((_, tokens)) => tokens.push(token),
+        None => batches.push((mode, vec![token])),
+      }
+    }
+    let unsubscribe: Vec<u32> = before
+      .keys()
+      .filter(|t| !after.contains_key(t))
+      .copied()
+      .collect();
+
+    let mut subscribed: Vec<u32> = Vec::new();
+    let mut changed: Vec<u32> = Vec::new();
+    let mut result = Ok(());
+    for (mode, tokens) in subscribe {
+      result = self.manager.subscribe_symbols(&tokens, Some(mode)).await;
+      if result.is_err() {
+        break;
+      }
+      subscribed.extend(tokens);
+    }
+    if result.is_ok() {
+      for (mode, tokens) in change {
+        result = self.manager.change_mode(&tokens, mode).await;
+        if result.is_err() {
+          break;
+        }
+        changed.extend(tokens);
+      }
+    }
+    if result.is_ok() && !unsubscribe.is_empty() {
+      result = self.manager.unsubscribe_symbols(&unsubscribe).await;
+    }
+    if result.is_err() {
+      self.undo(before, &subscribed, &changed).await;
+    }
+    result
+  }
+
+  /// Unsubscribe `subscribed` and put `changed` back in their `before` mode
+  async fn undo(
+    &mut self,
+    before: &HashMap<u32, Mode>,
+    subscribed: &[u32],
+    changed: &[u32],
+  ) {
+    let mut restore: Vec<(Mode, Vec<u32>)> = Vec::new();
+    for token in changed {
+      let mode = before[token];
+      match restore.iter_mut().find(|(m, _)| *m == mode) {
+        Some((_, tokens)) => tokens.push(*token),
+        None => restore.push((mode, vec![*token])),
+      }
+    }
+    for (mode, tokens) in restore {
+      if let Err(e) = self.manager.change_mode(&tokens, mode).await {
+        log::warn!(
+          "Watchlist rollback could not restore {:?} for {:?}: {}",
+          mode,
+          tokens,
+          e
+        );
+      }
+    }
+    if !subscribed.is_empty() {
+      if let Err(e) = self.manager.unsubscribe_symbols(subscribed).await {
+        log::warn!(
+          "Watchlist rollback could not unsubscribe {:?}: {}",
+          subscribed,
+          e
+        );
+      }
+    }
+  }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::errors::ConfigError;
+use crate::TickMessage;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// OHLCV bar for a single instrument over `[start, end)`
+///
+/// `start`/`end` are offsets from the Unix epoch. `volume` is the increase in
+/// the day's cumulative traded volume within the window.
+///
+pub struct Candle {
+  pub token: u32,
+  pub open: f64,
+  pub high: f64,
+  pub low: f64,
+  pub close: f64,
+  pub volume: u64,
+  pub start: Duration,
+  pub end: Duration,
+}
+
+impl Candle {
+  fn new(token: u32, price: f64, start: Duration, interval: Duration) -> Self {
+    Candle {
+      token,
+      open: price,
+      high: price,
+      low: price,
+      close: price,
+      volume: 0,
+      start,
+      end: start + interval,
+    }
+  }
+}
+
+#[derive(Debug)]
+struct CandleState {
+  current: Candle,
+  /// Cumulative volume at the end of the previous window
+  base_volume: Option<u32>,
+  /// Latest cumulative volume seen
+  last_volume: Option<u32>,
+}
+
+#[derive(Debug)]
+///
+/// Aggregates ticks into fixed-interval candles per instrument
+///
+/// Ticks are bucketed by exchange timestamp (falling back to last traded
+/// time, then wall clock). A candle is emitted once a tick for a later window
+/// arrives or [`flush`](Self::flush) passes its end. Windows with no ticks are
+/// emitted as flat candles carrying forward the previous close, up to
+/// [`max_gap_candles`](Self::with_max_gap_candles) per gap; longer gaps
+/// (overnight, a halted instrument) skip the rest.
+///
+/// ```rust
+/// # use kiteticker_async_manager::CandleAggregator;
+/// # use std::time::Duration;
+/// let mut agg = CandleAggregator::new(Duration::from_secs(60)).unwrap();
+/// # let ticks: Vec<kiteticker_async_manager::TickMessage> = Vec::new();
+/// for tick in &ticks {
+///   for candle in agg.push(tick) {
+///     println!("{} O:{} H:{} L:{} C:{}", candle.token, candle.open, candle.high, candle.low, candle.close);
+///   }
+/// }
+/// ```
+///
+pub struct CandleAggregator {
+  interval: Duration,
+  max_gap_candles: usize,
+  states: HashMap<u32, CandleState>,
+}
+
+impl CandleAggregator {
+  /// Default cap on flat candles emitted for a single gap
+  pub const DEFAULT_MAX_GAP_CANDLES: usize = 1_000;
+
+  /// Create an aggregator producing candles of `interval` length (e.g. 1s, 1m)
+  ///
+  /// Fails if `interval` is zero.
+  pub fn new(interval: Duration) -> Result<Self, ConfigError> {
+    if interval.is_zero() {
+      return Err(ConfigError("candle interval must be non-zero".into()));
+    }
+    Ok(Self {
+      interval,
+      max_gap_candles: Self::DEFAULT_MAX_GAP_CANDLES,
+      states: HashMap::new(),
+    })
+  }
+
+  /// Emit at most `max` flat candles for a gap; `0` disables gap filling
+  pub fn with_max_gap_candles(mut self, max: usize) -> Self {
+    self.max_gap_candles = max;
+    self
+  }
+
+  /// Candle interval
+  pub fn interval(&self) -> Duration {
+    self.interval
+  }
+
+  /// Feed a tick, returning any candles completed by it
+  pub fn push(&mut self, tick: &TickMessage) -> Vec<Candle> {
+    let at = tick
+      .content
+      .exchange_timestamp
+      .or(tick.content.last_traded_timestamp)
+      .unwrap_or_else(|| {
+        SystemTime::now()
+          .duration_since(UNIX_EPOCH)
+          .unwrap_or_default()
+      });
+    self.push_at(tick, at)
+  }
+
+  /// Feed a tick with an explicit timestamp (offset from the Unix epoch)
+  pub fn push_at(&mut self, tick: &TickMessage, at: Duration) -> Vec<Candle> {
+    let Some(price) = tick.content.last_price else {
+      return Vec::new();
+    };
+    let token = tick.instrument_token;
+    let volume = tick.content.volume_traded;
+    let start = self.window_start(at);
+    let interval = self.interval;
+    let mut out = Vec::new();
+
+    let state = self.states.entry(token).or_insert_with(|| CandleState {
+      current: Candle::new(token, price, start, interval),
+      base_volume: volume,
+      last_volume: volume,
+    });
+
+    if start < state.current.start {
+      // Late tick for a window that is already closed
+      return out;
+    }
+    if start > state.current.start {
+      Self::roll(state, start, interval, self.max_gap_candles, &mut out);
+      state.current = Candle::new(token, price, start, interval);
+    }
+
+    let candle = &mut state.current;
+    candle.high = candle.high.max(price);
+    candle.low = candle.low.min(price);
+    candle.close = price;
+    if volume.is_some() {
+      state.last_volume = volume;
+    }
+    if let (Some(base), Some(last)) = (state.base_volume, state.last_volume) {
+      candle.volume = last.saturating_sub(base) as u64;
+    }
+    out
+  }
+
+  /// Close every window ending at or before `now`, returning the candles.
+  ///
+  /// Instruments stay tracked: their next window opens at the last close.
+  pub fn flush(&mut self, now: Duration) -> Vec<Candle> {
+    let start = self.window_start(now);
+    let interval = self.interval;
+    let mut out = Vec::new();
+    for state in self.states.values_mut() {
+      if state.current.start < start {
+        let close = state.current.close;
+        Self::roll(state, start, interval, self.max_gap_candles, &mut out);
+        state.current =
+          Candle::new(state.current.token, close, start, interval);
+      }
+    }
+    out.sort_by_key(|c| (c.start, c.token));
+    out
+  }
+
+  /// Emit the current candle plus up to `max_gap` flat candles before
+  /// `next_start`
+  fn roll(
+    state: &mut CandleState,
+    next_start: Duration,
+    interval: Duration,
+    max_gap: usize,
+    out: &mut Vec<Candle>,
+  ) {
+    let close = state.current.close;
+    let token = state.current.token;
+    let mut gap_start = state.current.end;
+    out.push(state.current.clone());
+    for _ in 0..max_gap {
+      if gap_start >= next_start {
+        break;
+      }
+      out.push(Candle::new(token, close, gap_start, interval));
+      gap_start += interval;
+    }
+    state.base_volume = state.last_volume;
+  }
+
+  fn window_start(&self, at: Duration) -> Duration {
+    let step = self.interval.as_nanos();
+    let start = at.as_nanos() / step * step;
+    Duration::new(
+      (start / 1_000_000_000) as u64,
+      (start % 1_000_000_000) as u32,
+    )
+  }
+}
@@ -1,12 +1,17 @@
 use crate::Exchange;
 use serde::{Deserialize, Serialize};
 
-use crate::parser::{price, value, value_short};
+use crate::parser::{price, scale_price, value, value_short};
+use crate::{DepthItemRaw, DepthRaw};
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 ///
 /// Market depth packet structure
 ///
+/// Bytes 64..184 of a Full packet hold ten 12-byte entries, the five bids
+/// first, then the five asks. Each entry is quantity (`u32`), price (`i32`,
+/// paise), orders (`u16`) and two bytes of padding. This is the layout
+/// pykiteconnect's `_parse_binary` decodes.
 pub struct Depth {
   pub buy: [DepthItem; 5],
   pub sell: [DepthItem; 5],
@@ -30,11 +35,38 @@ impl Depth {
         depth.sell[i] = parse_depth_item(bs, start);
       }
 
+      #[cfg(debug_assertions)]
+      if !depth.is_well_formed() {
+        log::warn!("Malformed market depth decoded: {:?}", depth);
+      }
+
       Some(depth)
     } else {
       None
     }
   }
+
+  /// Check the decoded levels for values no exchange sends.
+  ///
+  /// A level is corrupt if its price is negative or not finite, or if it
+  /// quotes a quantity without a price. Anything else is market data: zero
+  /// order counts (BSE does not report them), a price without quantity,
+  /// out-of-order levels and a crossed book (best bid above best ask, as
+  /// happens in auctions) are all well-formed.
+  pub fn is_well_formed(&self) -> bool {
+    !self.buy.iter().chain(&self.sell).any(DepthItem::is_corrupt)
+  }
+
+  /// Build from a zero-copy depth view, matching `Depth::from` byte parsing
+  pub(crate) fn from_raw(raw: &DepthRaw, exchange: &Exchange) -> Self {
+    Depth {
+      buy: raw.buy.each_ref().map(|d| DepthItem::from_raw(d, exchange)),
+      sell: raw
+        .sell
+        .each_ref()
+        .map(|d| DepthItem::from_raw(d, exchange)),
+    }
+  }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -55,4 +87,24 @@ impl DepthItem {
       orders: value_short(&bs[8..=9]).unwrap(),
     })
   }
+
+  /// No quantity, orders or price at this level
+  pub fn is_empty(&self) -> bool {
+    self.qty == 0 && self.orders == 0 && self.price == 0.0
+  }
+
+  /// Negative or non-finite price, or a quantity without a price
+  fn is_corrupt(&self) -> bool {
+    !self.price.is_finite()
+      || self.price < 0.0
+      || (self.qty > 0 && self.price == 0.0)
+  }
+
+  pub(crate) fn from_raw(raw: &DepthItemRaw, exchange: &Exchange) -> Self {
+    DepthItem {
+      qty: raw.qty.get(),
+      price: scale_price(raw.price.get(), exchange),
+      orders: raw.orders.get(),
+    }
+  }
 }
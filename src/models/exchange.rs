@@ -1,7 +1,15 @@
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 #[derive(
-  Debug, Clone, Copy, Default, PartialEq, Serialize_repr, Deserialize_repr,
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  Serialize_repr,
+  Deserialize_repr,
 )]
 #[repr(u8)]
 ///
@@ -32,6 +40,14 @@ impl Exchange {
   pub(crate) fn is_tradable(&self) -> bool {
     !matches!(self, Self::INDICES)
   }
+
+  /// Whether instruments on this segment are derivatives (futures/options)
+  pub fn is_derivative(&self) -> bool {
+    matches!(
+      self,
+      Self::NFO | Self::CDS | Self::BFO | Self::BCD | Self::MCX | Self::MCXSX
+    )
+  }
 }
 
 impl From<usize> for Exchange {
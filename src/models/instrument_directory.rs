@@ -0,0 +1,153 @@
+use crate::Exchange;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+
+/// Trading symbol to instrument token lookup, loaded from Kite's
+/// instruments dump (`https://api.kite.trade/instruments`)
+///
+/// Symbols are keyed by the `exchange` column, so `("NIFTY 50", NSE)`
+/// resolves the index; index rows are also reachable as
+/// `Exchange::INDICES`.
+///
+/// ```rust
+/// use kiteticker_async_manager::{Exchange, InstrumentDirectory};
+///
+/// let csv = "instrument_token,exchange_token,tradingsymbol,name,last_price,\
+/// expiry,strike,tick_size,lot_size,instrument_type,segment,exchange\n\
+/// 738561,2885,RELIANCE,RELIANCE INDUSTRIES,0,,0,0.05,1,EQ,NSE,NSE\n\
+/// 256265,1001,NIFTY 50,NIFTY 50,0,,0,0,0,EQ,INDICES,NSE\n";
+/// let directory = InstrumentDirectory::from_csv(csv.as_bytes()).unwrap();
+/// assert_eq!(directory.token("RELIANCE", Exchange::NSE), Some(738561));
+/// assert_eq!(directory.token("NIFTY 50", Exchange::INDICES), Some(256265));
+/// assert!(directory.resolve(&[("RELIANC", Exchange::NSE)]).is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentDirectory {
+  tokens: HashMap<(Exchange, String), u32>,
+}
+
+impl InstrumentDirectory {
+  /// Parse an instruments CSV; the header must contain `instrument_token`,
+  /// `tradingsymbol` and `exchange`. Rows on exchanges this crate does not
+  /// know are skipped.
+  pub fn from_csv<R: Read>(reader: R) -> Result<Self, String> {
+    let mut lines = BufReader::new(reader).lines();
+    let header = lines
+      .next()
+      .ok_or("Instruments CSV is empty")?
+      .map_err(|e| format!("Failed to read instruments CSV: {}", e))?;
+    let header = split_csv_line(&header);
+    let column = |name: &str| {
+      header
+        .iter()
+        .position(|h| h.trim() == name)
+        .ok_or(format!("Instruments CSV has no '{}' column", name))
+    };
+    let token_col = column("instrument_token")?;
+    let symbol_col = column("tradingsymbol")?;
+    let exchange_col = column("exchange")?;
+    let segment_col = column("segment").ok();
+
+    let mut directory = Self::default();
+    for (i, line) in lines.enumerate() {
+      let line =
+        line.map_err(|e| format!("Failed to read instruments CSV: {}", e))?;
+      if line.trim().is_empty() {
+        continue;
+      }
+      let fields = split_csv_line(&line);
+      let field = |col: usize| fields.get(col).map(|f| f.trim());
+      let (Some(token), Some(symbol), Some(exchange)) =
+        (field(token_col), field(symbol_col), field(exchange_col))
+      else {
+        return Err(format!("Instruments CSV line {} is truncated", i + 2));
+      };
+      let token: u32 = token.parse().map_err(|_| {
+        format!("Instruments CSV line {}: bad token '{}'", i + 2, token)
+      })?;
+      if let Some(exchange) = parse_exchange(exchange) {
+        directory.insert(symbol, exchange, token);
+      }
+      if segment_col.and_then(field) == Some("INDICES") {
+        directory.insert(symbol, Exchange::INDICES, token);
+      }
+    }
+    Ok(directory)
+  }
+
+  /// Add or overwrite one symbol
+  pub fn insert(&mut self, symbol: &str, exchange: Exchange, token: u32) {
+    self.tokens.insert((exchange, symbol.to_string()), token);
+  }
+
+  /// Token of `symbol` on `exchange`
+  pub fn token(&self, symbol: &str, exchange: Exchange) -> Option<u32> {
+    self.tokens.get(&(exchange, symbol.to_string())).copied()
+  }
+
+  /// Tokens of all `symbols`, in order; errors listing every unknown one
+  pub fn resolve(
+    &self,
+    symbols: &[(&str, Exchange)],
+  ) -> Result<Vec<u32>, String> {
+    let mut tokens = Vec::with_capacity(symbols.len());
+    let mut unknown = Vec::new();
+    for &(symbol, exchange) in symbols {
+      match self.token(symbol, exchange) {
+        Some(token) => tokens.push(token),
+        None => unknown.push(format!("{}:{}", String::from(exchange), symbol)),
+      }
+    }
+    if unknown.is_empty() {
+      Ok(tokens)
+    } else {
+      Err(format!("Unknown trading symbols: {}", unknown.join(", ")))
+    }
+  }
+
+  /// Number of (symbol, exchange) entries
+  pub fn len(&self) -> usize {
+    self.tokens.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.tokens.is_empty()
+  }
+}
+
+/// Exchange names used in the instruments dump; unlike
+/// `From<String>`, unknown names are rejected rather than mapped to NSE
+fn parse_exchange(name: &str) -> Option<Exchange> {
+  Some(match name {
+    "NSE" => Exchange::NSE,
+    "NFO" => Exchange::NFO,
+    "CDS" => Exchange::CDS,
+    "BSE" => Exchange::BSE,
+    "BFO" => Exchange::BFO,
+    "BCD" => Exchange::BCD,
+    "MCX" => Exchange::MCX,
+    "MCXSX" => Exchange::MCXSX,
+    _ => return None,
+  })
+}
+
+/// Split one CSV line, honouring double-quoted fields and `""` escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+  let mut fields = Vec::new();
+  let mut field = String::new();
+  let mut quoted = false;
+  let mut chars = line.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '"' if quoted && chars.peek() == Some(&'"') => {
+        field.push('"');
+        chars.next();
+      }
+      '"' => quoted = !quoted,
+      ',' if !quoted => fields.push(std::mem::take(&mut field)),
+      _ => field.push(c),
+    }
+  }
+  fields.push(field);
+  fields
+}
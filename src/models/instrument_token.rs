@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::Exchange;
+
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Hash,
+  PartialOrd,
+  Ord,
+  Serialize,
+  Deserialize,
+)]
+#[serde(transparent)]
+#[repr(transparent)]
+///
+/// Kite instrument token, kept distinct from other `u32` quantities
+///
+/// The subscribe APIs and `TickMessage::instrument_token` still use plain
+/// `u32`; convert with `From`/`Into` at the boundary.
+///
+pub struct InstrumentToken(pub u32);
+
+impl InstrumentToken {
+  /// Exchange segment encoded in the low byte of the token
+  pub fn exchange(&self) -> Exchange {
+    ((self.0 & 0xFF) as usize).into()
+  }
+}
+
+impl From<u32> for InstrumentToken {
+  fn from(value: u32) -> Self {
+    Self(value)
+  }
+}
+
+impl From<InstrumentToken> for u32 {
+  fn from(value: InstrumentToken) -> Self {
+    value.0
+  }
+}
+
+impl PartialEq<u32> for InstrumentToken {
+  fn eq(&self, other: &u32) -> bool {
+    self.0 == *other
+  }
+}
+
+impl fmt::Display for InstrumentToken {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.0.fmt(f)
+  }
+}
@@ -1,5 +1,8 @@
+mod candle;
 mod depth;
 mod exchange;
+mod instrument_directory;
+mod instrument_token;
 mod mode;
 mod ohlc;
 mod order;
@@ -7,10 +10,14 @@ mod request;
 mod text_message;
 mod tick;
 mod tick_message;
+mod tick_rate_limiter;
 pub(crate) mod tick_raw;
 mod ticker_message;
+pub use self::candle::{Candle, CandleAggregator};
 pub use self::depth::{Depth, DepthItem};
 pub use self::exchange::Exchange;
+pub use self::instrument_directory::InstrumentDirectory;
+pub use self::instrument_token::InstrumentToken;
 pub use self::mode::Mode;
 pub use self::ohlc::OHLC;
 pub use self::order::{
@@ -18,8 +25,9 @@ pub use self::order::{
 };
 pub use self::request::Request;
 pub use self::text_message::TextMessage;
-pub use self::tick::Tick;
+pub use self::tick::{DedupKey, Tick};
 // Keep raw types crate-visible; crate root will re-export for external users
 // Keep internal uses explicit; public re-exports are done at crate root
-pub use self::tick_message::TickMessage;
-pub use self::ticker_message::TickerMessage;
+pub use self::tick_message::{TickDirection, TickMessage};
+pub use self::tick_rate_limiter::{RateLimitedReceiver, TickRateLimiter};
+pub use self::ticker_message::{CloseInfo, TickerMessage};
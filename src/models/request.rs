@@ -98,6 +98,13 @@ impl<'a> Request<'a> {
       RequestData::InstrumentTokens(instrument_tokens),
     )
   }
+
+  ///
+  /// JSON text sent over the websocket, e.g. `{"a":"subscribe","v":[408065]}`
+  ///
+  pub fn to_json_string(&self) -> String {
+    self.to_string()
+  }
 }
 
 impl<'a> fmt::Display for Request<'a> {
@@ -1,13 +1,27 @@
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::{
   errors::ParseTickError,
-  parser::{price, value},
-  Depth, Exchange, Mode, OHLC,
+  parser::{price, scale_price, value},
+  Depth, DepthItem, Exchange, Mode, TickRaw, OHLC,
 };
+use zerocopy::IntoBytes;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// Identity of a tick across redundant streams, from [`Tick::dedup_key`] or
+/// [`TickMessage::dedup_key`](crate::TickMessage::dedup_key)
+///
+pub struct DedupKey {
+  pub instrument_token: u32,
+  pub exchange_timestamp: Option<Duration>,
+  /// Last price as the exchange's scaled integer (paise for most segments)
+  pub last_price: Option<i64>,
+}
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 ///
 /// Quote packet structure
 ///
@@ -34,6 +48,11 @@ pub struct Tick {
 
   pub net_change: Option<f64>,
   pub depth: Option<Depth>,
+
+  /// Decoded by [`Tick::parse_best_effort`] from a damaged packet: the
+  /// header fields are present but `depth` was dropped
+  #[serde(default)]
+  pub partial: bool,
 }
 
 impl Tick {
@@ -66,6 +85,125 @@ impl Tick {
 }
 
 impl Tick {
+  /// Open interest, only for derivative segments.
+  ///
+  /// Returns `None` for equity/index instruments; the decoder already leaves
+  /// `oi` unset for them, this also covers hand-built ticks.
+  pub fn open_interest(&self) -> Option<u32> {
+    self.oi.filter(|_| self.exchange.is_derivative())
+  }
+
+  /// Day high of open interest, only for derivative segments
+  pub fn open_interest_day_high(&self) -> Option<u32> {
+    self.oi_day_high.filter(|_| self.exchange.is_derivative())
+  }
+
+  /// Day low of open interest, only for derivative segments
+  pub fn open_interest_day_low(&self) -> Option<u32> {
+    self.oi_day_low.filter(|_| self.exchange.is_derivative())
+  }
+
+  /// Whether the exchange timestamp is older than `max_age` at `now`.
+  ///
+  /// Unlike local receive time this ignores network delay. Returns `false`
+  /// when the packet carries no exchange timestamp (LTP/Quote modes).
+  pub fn is_stale(&self, max_age: Duration, now: SystemTime) -> bool {
+    let Some(ts) = self.exchange_timestamp else {
+      return false;
+    };
+    now
+      .duration_since(UNIX_EPOCH + ts)
+      .is_ok_and(|age| age > max_age)
+  }
+
+  /// Identity used to spot the same tick arriving on redundant streams:
+  /// token, exchange timestamp and last price.
+  ///
+  /// The price is compared as the exchange's scaled integer (paise for most
+  /// segments) so float noise cannot split duplicates.
+  pub fn dedup_key(&self) -> DedupKey {
+    DedupKey {
+      instrument_token: self.instrument_token,
+      exchange_timestamp: self.exchange_timestamp,
+      last_price: self
+        .last_price
+        .map(|p| (p * self.exchange.divisor()).round() as i64),
+    }
+  }
+
+  /// Serialize in the shape of one instrument of Kite's `/quote` REST
+  /// response (nested `ohlc`, `depth.buy`/`depth.sell`, REST field names),
+  /// so websocket and REST consumers can share a decoder.
+  ///
+  /// Timestamps use Kite's `YYYY-MM-DD HH:MM:SS` in IST. Fields the packet's
+  /// mode did not carry are `null`.
+  pub fn to_kite_quote_json(&self) -> serde_json::Value {
+    let ist = chrono::FixedOffset::east_opt(5 * 3600 + 30 * 60)
+      .expect("IST offset is in range");
+    let timestamp = |ts: Option<Duration>| {
+      ts.and_then(|d| i64::try_from(d.as_secs()).ok())
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|t| {
+          t.with_timezone(&ist)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+        })
+    };
+    let levels = |items: &[DepthItem; 5]| {
+      items
+        .iter()
+        .map(|item| {
+          serde_json::json!({
+            "price": item.price,
+            "quantity": item.qty,
+            "orders": item.orders,
+          })
+        })
+        .collect::<Vec<_>>()
+    };
+    serde_json::json!({
+      "instrument_token": self.instrument_token,
+      "timestamp": timestamp(self.exchange_timestamp),
+      "last_trade_time": timestamp(self.last_traded_timestamp),
+      "last_price": self.last_price,
+      "last_quantity": self.last_traded_qty,
+      "buy_quantity": self.total_buy_qty,
+      "sell_quantity": self.total_sell_qty,
+      "volume": self.volume_traded,
+      "average_price": self.avg_traded_price,
+      "oi": self.oi,
+      "oi_day_high": self.oi_day_high,
+      "oi_day_low": self.oi_day_low,
+      "net_change": self.net_change,
+      "ohlc": self.ohlc,
+      "depth": self.depth.as_ref().map(|d| serde_json::json!({
+        "buy": levels(&d.buy),
+        "sell": levels(&d.sell),
+      })),
+    })
+  }
+}
+
+impl Tick {
+  /// Bytes 44 - 64 of a tradable Full packet
+  fn set_full_header(&mut self, bs: &[u8]) {
+    // 44 - 48 bytes : last traded timestamp
+    self.last_traded_timestamp =
+      value(&bs[0..4]).map(|x| Duration::from_secs(x.into()));
+
+    // 48 - 60 bytes : oi, oi day high, oi day low. Every tradable segment
+    // shares this layout, but the OI slots only carry data for
+    // derivatives; leave them unset for cash segments.
+    if self.exchange.is_derivative() {
+      self.oi = value(&bs[4..8]);
+      self.oi_day_high = value(&bs[8..12]);
+      self.oi_day_low = value(&bs[12..16]);
+    }
+    // 60 - 64 bytes : exchange time
+    self.exchange_timestamp =
+      value(&bs[16..20]).map(|x| Duration::from_secs(x.into()));
+  }
+
   pub(crate) fn from_bytes(input: &[u8]) -> Self {
     let mut tick = Tick::default();
     // Parse LTP fields (first 8 bytes)
@@ -116,19 +254,7 @@ impl Tick {
       tick.mode = Mode::Full;
       tick.set_change();
 
-      // 44 - 48 bytes : last traded timestamp
-      tick.last_traded_timestamp =
-        value(&bs[0..4]).map(|x| Duration::from_secs(x.into()));
-
-      // 48 - 52 bytes : oi
-      tick.oi = value(&bs[4..8]);
-      // 52 - 56 bytes : oi day high
-      tick.oi_day_high = value(&bs[8..12]);
-      // 56 - 60 bytes : oi day low
-      tick.oi_day_low = value(&bs[12..16]);
-      // 60 - 64 bytes : exchange time
-      tick.exchange_timestamp =
-        value(&bs[16..20]).map(|x| Duration::from_secs(x.into()));
+      tick.set_full_header(&bs[0..20]);
       // 64 - 184 bytes : market depth
       tick.depth = Depth::from(&bs[20..140], &tick.exchange);
     }
@@ -137,6 +263,123 @@ impl Tick {
   }
 }
 
+impl Tick {
+  /// Re-decode `input` using the token's subscribed `mode` as a hint.
+  ///
+  /// A packet longer than that mode's layout (e.g. 44 bytes for an index
+  /// token subscribed in Quote) decodes only the subscribed layout. A packet
+  /// too short for it was sent before Kite applied the mode command (a
+  /// subscription starts in Quote), so `None` tells the parser to hold it
+  /// back rather than emit a tick in the wrong mode.
+  pub(crate) fn apply_mode_hint(
+    self,
+    input: &[u8],
+    mode: Mode,
+  ) -> Option<Tick> {
+    if self.mode == mode {
+      return Some(self);
+    }
+    let len = match (mode, self.is_index) {
+      (Mode::LTP, _) => 8,
+      (Mode::Quote, true) => 28,
+      (Mode::Quote, false) => 44,
+      (Mode::Full, true) => 32,
+      (Mode::Full, false) => 184,
+    };
+    (input.len() > len).then(|| Tick::from_bytes(&input[..len]))
+  }
+
+  /// Materialize an owned `Tick` from a zero-copy 184-byte `TickRaw` view.
+  ///
+  /// Produces the same result as `Tick::try_from` on the underlying bytes
+  /// (identical price scaling and depth ordering) without re-slicing them.
+  pub fn from_raw(raw: &TickRaw) -> Tick {
+    let h = &raw.header;
+    let mut tick = Tick {
+      instrument_token: h.instrument_token.get(),
+      ..Default::default()
+    };
+    tick.exchange = ((tick.instrument_token & 0xFF) as usize).into();
+    if !tick.exchange.is_tradable() {
+      // Index packets use a different layout over the same bytes; keep a
+      // single source of truth for it.
+      return Tick::from_bytes(raw.as_bytes());
+    }
+    let exchange = tick.exchange;
+
+    tick.mode = Mode::Full;
+    tick.is_tradable = true;
+    tick.last_price = Some(scale_price(h.last_price.get(), &exchange));
+    tick.last_traded_qty = Some(h.last_traded_qty.get());
+    tick.avg_traded_price =
+      Some(scale_price(h.avg_traded_price.get(), &exchange));
+    tick.volume_traded = Some(h.volume_traded.get());
+    tick.total_buy_qty = Some(h.total_buy_qty.get());
+    tick.total_sell_qty = Some(h.total_sell_qty.get());
+    tick.ohlc = OHLC::from(&h.ohlc_be, &exchange);
+    tick.set_change();
+    tick.last_traded_timestamp =
+      Some(Duration::from_secs(h.last_traded_ts.get().into()));
+    if exchange.is_derivative() {
+      tick.oi = Some(h.oi.get());
+      tick.oi_day_high = Some(h.oi_day_high.get());
+      tick.oi_day_low = Some(h.oi_day_low.get());
+    }
+    tick.exchange_timestamp =
+      Some(Duration::from_secs(h.exchange_ts.get().into()));
+    tick.depth = Some(Depth::from_raw(&raw.depth, &exchange));
+    tick
+  }
+}
+
+impl Tick {
+  /// Decode like `Tick::try_from`, salvaging the header of damaged Full
+  /// packets instead of failing.
+  ///
+  /// A tradable packet sized between the Quote (44 bytes) and Full (184
+  /// bytes) layouts keeps every header field that fits, and a Full packet
+  /// whose depth fails [`Depth::is_well_formed`] keeps its header. Both come
+  /// back with `depth: None` and `partial` set.
+  ///
+  /// ```rust
+  /// # use kiteticker_async_manager::Tick;
+  /// let mut packet = [0u8; 184];
+  /// packet[0..4].copy_from_slice(&408065u32.to_be_bytes());
+  /// packet[4..8].copy_from_slice(&152035i32.to_be_bytes());
+  /// // First bid level: quantity without price or orders
+  /// packet[64..68].copy_from_slice(&u32::MAX.to_be_bytes());
+  ///
+  /// let tick = Tick::parse_best_effort(&packet).unwrap();
+  /// assert_eq!(tick.last_price, Some(1520.35));
+  /// assert!(tick.partial && tick.depth.is_none());
+  /// ```
+  pub fn parse_best_effort(input: &[u8]) -> Result<Tick, ParseTickError> {
+    match Tick::try_from(input) {
+      Ok(mut tick) => {
+        if tick.depth.as_ref().is_some_and(|d| !d.is_well_formed()) {
+          tick.depth = None;
+          tick.partial = true;
+        }
+        Ok(tick)
+      }
+      Err(e) if input.len() > 44 && input.len() < 184 => {
+        let mut tick = Tick::from_bytes(&input[..44]);
+        if tick.is_index {
+          return Err(e);
+        }
+        if let Some(bs) = input.get(44..64) {
+          tick.mode = Mode::Full;
+          tick.set_change();
+          tick.set_full_header(bs);
+        }
+        tick.partial = true;
+        Ok(tick)
+      }
+      Err(e) => Err(e),
+    }
+  }
+}
+
 impl TryFrom<&[u8]> for Tick {
   type Error = ParseTickError;
   fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
@@ -1,20 +1,103 @@
-use crate::Tick;
+use crate::{DedupKey, InstrumentToken, Mode, Tick};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 ///
 /// Parsed quote packet
 ///
+/// Equality compares every field. To drop duplicates from redundant
+/// connections, key a `HashSet` on [`dedup_key`](Self::dedup_key) (token,
+/// exchange timestamp, last price) instead:
+///
+/// ```rust
+/// # use kiteticker_async_manager::{Tick, TickMessage};
+/// # use std::{collections::HashSet, time::Duration};
+/// let tick = |volume| {
+///   let mut content = Tick::default();
+///   content.instrument_token = 408065;
+///   content.last_price = Some(1520.35);
+///   content.exchange_timestamp = Some(Duration::from_secs(1_700_000_000));
+///   content.volume_traded = Some(volume);
+///   TickMessage::new(408065, content)
+/// };
+/// let mut seen = HashSet::new();
+/// assert!(seen.insert(tick(100).dedup_key()));
+/// assert!(!seen.insert(tick(100).dedup_key()));
+/// assert_ne!(tick(100), tick(200));
+/// ```
+///
 pub struct TickMessage {
   pub instrument_token: u32,
   pub content: Tick,
+  /// Move of `last_price` versus the previous tick for this token.
+  ///
+  /// Kite packets don't carry this; it is only filled in by the manager when
+  /// the last-value cache is enabled.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub tick_direction: Option<TickDirection>,
+  /// Volume traded since the previous tick for this token, derived from the
+  /// cumulative `volume_traded`.
+  ///
+  /// Only filled in when the last-value cache is enabled; `None` for the
+  /// first tick and when the cumulative volume drops (day rollover).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub volume_delta: Option<u32>,
+  /// Mode the token was subscribed in when the packet arrived.
+  ///
+  /// Only filled in with `stamp_requested_mode`. It can differ from
+  /// `content.mode` (decoded from the packet length) right after a mode
+  /// change, while Kite still sends the previous layout.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub requested_mode: Option<Mode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Direction of the last price relative to the previous tick
+///
+pub enum TickDirection {
+  Up,
+  Down,
+  Unchanged,
+}
+
+impl TickDirection {
+  /// Compare `current` against `previous`; `None` if either price is missing
+  pub fn between(previous: Option<f64>, current: Option<f64>) -> Option<Self> {
+    let (prev, cur) = (previous?, current?);
+    Some(if cur > prev {
+      TickDirection::Up
+    } else if cur < prev {
+      TickDirection::Down
+    } else {
+      TickDirection::Unchanged
+    })
+  }
 }
 
 impl TickMessage {
-  pub(crate) fn new(instrument_token: u32, content: Tick) -> Self {
+  /// [`Tick::dedup_key`] under this message's `instrument_token`
+  pub fn dedup_key(&self) -> DedupKey {
+    DedupKey {
+      instrument_token: self.instrument_token,
+      ..self.content.dedup_key()
+    }
+  }
+
+  /// `instrument_token` as a typed [`InstrumentToken`]
+  pub fn token(&self) -> InstrumentToken {
+    InstrumentToken(self.instrument_token)
+  }
+
+  /// Wrap a decoded tick; the derived fields start empty
+  pub fn new(instrument_token: u32, content: Tick) -> Self {
     Self {
       instrument_token,
       content,
+      tick_direction: None,
+      volume_delta: None,
+      requested_mode: None,
     }
   }
 }
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::errors::ConfigError;
+use crate::{TickMessage, TickerMessage};
+
+#[derive(Debug)]
+///
+/// Caps how many ticks per second each instrument passes downstream
+///
+/// Unlike conflation nothing is buffered: a tick arriving sooner than
+/// `1 / max_ticks_per_second` after the last one let through for the same
+/// token is dropped.
+///
+/// ```rust
+/// # use kiteticker_async_manager::TickRateLimiter;
+/// # use std::time::Instant;
+/// let mut limiter = TickRateLimiter::new(10).unwrap();
+/// let now = Instant::now();
+/// assert!(limiter.allow(256265, now));
+/// assert!(!limiter.allow(256265, now));
+/// ```
+///
+pub struct TickRateLimiter {
+  min_interval: Duration,
+  last_emitted: HashMap<u32, Instant>,
+  dropped: u64,
+}
+
+impl TickRateLimiter {
+  /// Let through at most `max_ticks_per_second` ticks per token
+  ///
+  /// Fails if `max_ticks_per_second` is zero.
+  pub fn new(max_ticks_per_second: u32) -> Result<Self, ConfigError> {
+    if max_ticks_per_second == 0 {
+      return Err(ConfigError("tick rate must be non-zero".into()));
+    }
+    Ok(Self {
+      min_interval: Duration::from_secs(1) / max_ticks_per_second,
+      last_emitted: HashMap::new(),
+      dropped: 0,
+    })
+  }
+
+  /// Whether a tick for `token` at `now` may pass; records it if so
+  pub fn allow(&mut self, token: u32, now: Instant) -> bool {
+    match self.last_emitted.get(&token) {
+      Some(&last)
+        if now.saturating_duration_since(last) < self.min_interval =>
+      {
+        self.dropped += 1;
+        false
+      }
+      _ => {
+        self.last_emitted.insert(token, now);
+        true
+      }
+    }
+  }
+
+  /// Keep only the ticks allowed at `now`
+  pub fn retain(
+    &mut self,
+    mut ticks: Vec<TickMessage>,
+    now: Instant,
+  ) -> Vec<TickMessage> {
+    ticks.retain(|tick| self.allow(tick.instrument_token, now));
+    ticks
+  }
+
+  /// Ticks dropped so far
+  pub fn dropped(&self) -> u64 {
+    self.dropped
+  }
+}
+
+#[derive(Debug)]
+///
+/// Channel receiver that applies a [`TickRateLimiter`] to `Ticks` messages
+///
+/// Other messages, including `Snapshot`, pass through unchanged. A `Ticks`
+/// message left empty after limiting is skipped.
+///
+pub struct RateLimitedReceiver {
+  rx: broadcast::Receiver<TickerMessage>,
+  limiter: TickRateLimiter,
+}
+
+impl RateLimitedReceiver {
+  /// Wrap `rx`, e.g. from `KiteTickerManager::get_channel`
+  ///
+  /// Fails if `max_ticks_per_second` is zero.
+  pub fn new(
+    rx: broadcast::Receiver<TickerMessage>,
+    max_ticks_per_second: u32,
+  ) -> Result<Self, ConfigError> {
+    Ok(Self {
+      rx,
+      limiter: TickRateLimiter::new(max_ticks_per_second)?,
+    })
+  }
+
+  /// Receive the next message, with excess ticks removed
+  pub async fn recv(
+    &mut self,
+  ) -> Result<TickerMessage, broadcast::error::RecvError> {
+    loop {
+      match self.rx.recv().await? {
+        TickerMessage::Ticks(ticks) => {
+          let ticks = self.limiter.retain(ticks, Instant::now());
+          if !ticks.is_empty() {
+            return Ok(TickerMessage::Ticks(ticks));
+          }
+        }
+        other => return Ok(other),
+      }
+    }
+  }
+
+  /// Ticks dropped so far
+  pub fn dropped(&self) -> u64 {
+    self.limiter.dropped()
+  }
+}
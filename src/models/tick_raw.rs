@@ -26,7 +26,7 @@
 //! ```
 
 use zerocopy::big_endian::{I32 as BeI32, U16 as BeU16, U32 as BeU32};
-use zerocopy::{FromBytes, Immutable, KnownLayout, Ref, Unaligned};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Ref, Unaligned};
 
 /// Size of a full quote packet body used by parser Tick (not including the 2-byte length prefix).
 /// Our raw view targets the 184-byte payload region per packet for Mode::Full on equities.
@@ -40,7 +40,15 @@ pub const INST_HEADER_SIZE: usize = 64;
 /// First 64 bytes of Full payload contain header/meta before market depth.
 #[repr(C)]
 #[derive(
-  Clone, Copy, Debug, Default, Unaligned, KnownLayout, Immutable, FromBytes,
+  Clone,
+  Copy,
+  Debug,
+  Default,
+  Unaligned,
+  KnownLayout,
+  Immutable,
+  FromBytes,
+  IntoBytes,
 )]
 pub struct TickHeaderRaw {
   pub instrument_token: BeU32, // 0..4
@@ -61,7 +69,15 @@ pub struct TickHeaderRaw {
 /// A single depth entry: qty(u32), price_be(`[u8; 4]` i32), orders(u16), pad(u16)
 #[repr(C)]
 #[derive(
-  Clone, Copy, Debug, Default, Unaligned, KnownLayout, Immutable, FromBytes,
+  Clone,
+  Copy,
+  Debug,
+  Default,
+  Unaligned,
+  KnownLayout,
+  Immutable,
+  FromBytes,
+  IntoBytes,
 )]
 pub struct DepthItemRaw {
   pub qty: BeU32,
@@ -73,7 +89,15 @@ pub struct DepthItemRaw {
 /// 5 buy + 5 sell entries = 120 bytes
 #[repr(C)]
 #[derive(
-  Clone, Copy, Debug, Default, Unaligned, KnownLayout, Immutable, FromBytes,
+  Clone,
+  Copy,
+  Debug,
+  Default,
+  Unaligned,
+  KnownLayout,
+  Immutable,
+  FromBytes,
+  IntoBytes,
 )]
 pub struct DepthRaw {
   pub buy: [DepthItemRaw; 5],
@@ -83,7 +107,15 @@ pub struct DepthRaw {
 /// Complete 184-byte Full packet body
 #[repr(C)]
 #[derive(
-  Clone, Copy, Debug, Default, Unaligned, KnownLayout, Immutable, FromBytes,
+  Clone,
+  Copy,
+  Debug,
+  Default,
+  Unaligned,
+  KnownLayout,
+  Immutable,
+  FromBytes,
+  IntoBytes,
 )]
 pub struct TickRaw {
   pub header: TickHeaderRaw, // 64 bytes
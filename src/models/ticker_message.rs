@@ -4,12 +4,18 @@ use serde::{Deserialize, Serialize};
 use super::text_message::TextMessageType;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 ///
 /// Parsed message from websocket
 ///
 pub enum TickerMessage {
-  /// Quote packets for subscribed tokens
+  /// Quote packets for subscribed tokens, in the order they appear in the
+  /// frame. Messages of one connection arrive in frame order; frames dropped
+  /// under backpressure leave gaps but are never reordered. There is no
+  /// ordering across connections.
   Ticks(Vec<TickMessage>),
+  /// Cached last-known ticks replayed right after subscribing (not live)
+  Snapshot(Vec<TickMessage>),
   /// Raw binary frame (when raw-only mode enabled)
   Raw(Vec<u8>),
   /// Error response
@@ -20,6 +26,19 @@ pub enum TickerMessage {
   Message(serde_json::Value),
   /// Websocket closing frame
   ClosingMessage(serde_json::Value),
+  /// Kite is rate limiting this account: a handshake refused with HTTP 429
+  /// or a close with code 1013 (try again later). Back off instead of
+  /// reconnecting immediately
+  Throttled(String),
+  /// No message arrived within the manager's `receive_timeout`; carries how
+  /// long the connection has been silent. Sent once per silence: the next
+  /// one only follows after data has flowed again
+  NoData(std::time::Duration),
+  /// Packet whose length matches no known layout, emitted only when
+  /// `emit_unknown_packets` is on so protocol changes are visible instead of
+  /// silently counted as parse errors. Published right after the `Ticks`
+  /// of the frame it came in
+  UnknownPacket { size: usize, bytes: bytes::Bytes },
 }
 
 impl From<TextMessage> for TickerMessage {
@@ -34,3 +53,58 @@ impl From<TextMessage> for TickerMessage {
     }
   }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Typed view of a websocket closing frame
+///
+pub struct CloseInfo {
+  pub code: u16,
+  pub reason: String,
+}
+
+/// Close code asking the client to try again later (RFC 6455)
+const TRY_AGAIN_LATER: u16 = 1013;
+
+impl CloseInfo {
+  /// Whether the close signals throttling, i.e. code 1013 ("try again
+  /// later"). The reason text is not inspected.
+  pub fn is_throttle(&self) -> bool {
+    self.code == TRY_AGAIN_LATER
+  }
+
+  /// Read a `ClosingMessage` payload, accepting the code as string or number
+  pub fn from_value(value: &serde_json::Value) -> Option<Self> {
+    let code = match value.get("code")? {
+      serde_json::Value::Number(n) => u16::try_from(n.as_u64()?).ok()?,
+      serde_json::Value::String(s) => s.parse().ok()?,
+      _ => return None,
+    };
+    let reason = value
+      .get("reason")
+      .and_then(|r| r.as_str())
+      .unwrap_or_default()
+      .to_string();
+    Some(Self { code, reason })
+  }
+}
+
+impl From<CloseInfo> for serde_json::Value {
+  /// Payload of `TickerMessage::ClosingMessage` (code kept as a string)
+  fn from(info: CloseInfo) -> Self {
+    serde_json::json!({
+      "code": info.code.to_string(),
+      "reason": info.reason,
+    })
+  }
+}
+
+impl TickerMessage {
+  /// Code and reason if this is a `ClosingMessage`
+  pub fn close_info(&self) -> Option<CloseInfo> {
+    match self {
+      Self::ClosingMessage(value) => CloseInfo::from_value(value),
+      _ => None,
+    }
+  }
+}
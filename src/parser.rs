@@ -1,6 +1,14 @@
-use std::ops::Div;
+//! Pure decoding of ticker frames and packets: no IO, no async, no shared
+//! state, so everything here can be exercised on arbitrary byte buffers.
 
-use crate::models::Exchange;
+use std::collections::HashMap;
+use std::ops::{Div, Range};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use smallvec::SmallVec;
+
+use crate::models::{Exchange, Mode, Tick, TickMessage};
 
 #[inline(always)]
 pub(crate) fn value(input: &[u8]) -> Option<u32> {
@@ -32,6 +40,12 @@ pub(crate) fn price(input: &[u8], exchange: &Exchange) -> Option<f64> {
   }
 }
 
+/// Scale an already-decoded raw price by the exchange divisor
+#[inline(always)]
+pub(crate) fn scale_price(raw: i32, exchange: &Exchange) -> f64 {
+  (raw as f64).div(exchange.divisor())
+}
+
 #[inline(always)]
 pub(crate) fn packet_length(bs: &[u8]) -> usize {
   if bs.len() >= 2 {
@@ -42,3 +56,386 @@ pub(crate) fn packet_length(bs: &[u8]) -> usize {
 }
 
 // parse_tick inlined into Tick::try_from to remove an extra function call per packet
+
+/// Packet count declared in a binary frame header (0 for heartbeats)
+pub(crate) fn packet_count(frame: &[u8]) -> usize {
+  match frame {
+    [hi, lo, ..] => u16::from_be_bytes([*hi, *lo]) as usize,
+    _ => 0,
+  }
+}
+
+/// Walks the length-prefixed packets of a binary frame, yielding the range
+/// of each packet body within the frame
+///
+/// Stops at the first packet whose length prefix or body runs past the end
+/// of the frame; [`is_truncated`](Self::is_truncated) then reports it.
+#[derive(Debug, Clone)]
+pub(crate) struct Packets<'a> {
+  frame: &'a [u8],
+  offset: usize,
+  remaining: usize,
+  truncated: bool,
+}
+
+impl<'a> Packets<'a> {
+  pub(crate) fn new(frame: &'a [u8]) -> Self {
+    Self {
+      frame,
+      offset: 2,
+      remaining: packet_count(frame),
+      truncated: false,
+    }
+  }
+
+  /// Whether the frame ended before all declared packets were read
+  pub(crate) fn is_truncated(&self) -> bool {
+    self.truncated
+  }
+}
+
+impl Iterator for Packets<'_> {
+  type Item = Range<usize>;
+
+  fn next(&mut self) -> Option<Range<usize>> {
+    if self.remaining == 0 || self.truncated {
+      return None;
+    }
+    let body_start = self.offset + 2;
+    let Some(prefix) = self.frame.get(self.offset..body_start) else {
+      self.truncated = true;
+      return None;
+    };
+    let body_end = body_start + packet_length(prefix);
+    if body_end > self.frame.len() {
+      self.truncated = true;
+      return None;
+    }
+    self.remaining -= 1;
+    self.offset = body_end;
+    Some(body_start..body_end)
+  }
+}
+
+/// Zero-copy slices of the 184-byte (Full) packet bodies of `frame`
+pub(crate) fn full_payloads(frame: &Bytes) -> impl Iterator<Item = Bytes> + '_ {
+  Packets::new(frame)
+    .filter(|body| body.len() == 184)
+    .map(|body| frame.slice(body))
+}
+
+/// Instrument tokens of the packets in `frame`, whatever their mode
+pub(crate) fn packet_tokens(frame: &[u8]) -> impl Iterator<Item = u32> + '_ {
+  Packets::new(frame).filter_map(|body| value(&frame[body]))
+}
+
+/// Ticks and rejects of one binary frame
+#[derive(Debug, Default)]
+pub(crate) struct ParsedFrame {
+  /// Parsed ticks in packet order
+  pub(crate) ticks: SmallVec<[TickMessage; 32]>,
+  /// Body ranges of packets the tick parser rejected
+  pub(crate) rejected: SmallVec<[Range<usize>; 4]>,
+  /// The frame ended before all declared packets were read
+  pub(crate) truncated: bool,
+  /// Packets held back because they predate their token's mode change
+  pub(crate) held_back: u64,
+}
+
+impl ParsedFrame {
+  /// Parse errors to count: one per rejected packet, one for truncation
+  pub(crate) fn error_count(&self) -> u64 {
+    self.rejected.len() as u64 + u64::from(self.truncated)
+  }
+}
+
+/// How [`parse_frame`] decodes packets
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ParseOptions {
+  /// Frames declaring more packets are rejected
+  pub(crate) max_packets: usize,
+  /// Salvage damaged Full packets with `Tick::parse_best_effort`
+  pub(crate) best_effort: bool,
+  /// Set `TickMessage::requested_mode` from the mode hints, and emit
+  /// packets too short for the requested mode instead of holding them back
+  pub(crate) stamp_requested_mode: bool,
+  /// How long after a mode hint is set packets too short for it are held
+  /// back; later ones are emitted in the mode they were decoded in
+  pub(crate) hold_back: Duration,
+}
+
+/// Subscribed mode of a token and when it was last changed
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ModeHint {
+  pub(crate) mode: Mode,
+  pub(crate) since: Instant,
+}
+
+impl ModeHint {
+  pub(crate) fn new(mode: Mode) -> Self {
+    Self {
+      mode,
+      since: Instant::now(),
+    }
+  }
+}
+
+/// Decode every packet of a binary frame into ticks
+///
+/// `mode_hints` settle packet lengths shared by several modes; packets
+/// shorter than their hinted layout are held back rather than reported for
+/// `hold_back` after the hint was set, unless `stamp_requested_mode` is set.
+/// Errors with the declared packet count if it exceeds `max_packets`.
+pub(crate) fn parse_frame(
+  frame: &[u8],
+  mode_hints: Option<&HashMap<u32, ModeHint>>,
+  options: ParseOptions,
+) -> Result<ParsedFrame, usize> {
+  let declared = packet_count(frame);
+  if declared > options.max_packets {
+    return Err(declared);
+  }
+  let mut parsed = ParsedFrame {
+    ticks: SmallVec::with_capacity(declared.min(32)),
+    ..Default::default()
+  };
+  let mut packets = Packets::new(frame);
+  for body in packets.by_ref() {
+    let packet = &frame[body.clone()];
+    let tick = if options.best_effort {
+      Tick::parse_best_effort(packet)
+    } else {
+      Tick::try_from(packet)
+    };
+    // Ticks are pushed in packet order; `Ticks` documents that guarantee
+    match tick {
+      Ok(tick) => {
+        let hint = mode_hints
+          .and_then(|h| h.get(&tick.instrument_token))
+          .copied();
+        let requested = hint.map(|h| h.mode);
+        let tick = match hint.filter(|_| !tick.partial) {
+          Some(hint) if tick.mode != hint.mode => {
+            let decoded = tick.clone();
+            match tick.apply_mode_hint(packet, hint.mode) {
+              Some(tick) => Some(tick),
+              // Mid-transition packet: keep it if the stamp shows the
+              // mismatch, or if the mode change never seems to land
+              None
+                if options.stamp_requested_mode
+                  || hint.since.elapsed() >= options.hold_back =>
+              {
+                Some(decoded)
+              }
+              None => {
+                parsed.held_back += 1;
+                None
+              }
+            }
+          }
+          _ => Some(tick),
+        };
+        if let Some(tick) = tick {
+          let mut message = TickMessage::new(tick.instrument_token, tick);
+          if options.stamp_requested_mode {
+            message.requested_mode = requested;
+          }
+          parsed.ticks.push(message);
+        }
+      }
+      Err(_) => parsed.rejected.push(body),
+    }
+  }
+  parsed.truncated = packets.is_truncated();
+  Ok(parsed)
+}
+
+/// Run every frame decoder over `data` and check their invariants; the
+/// entry point of `fuzz/fuzz_targets/parse_frame.rs`
+///
+/// Panics if a packet range leaves the frame, packets overlap or go
+/// backwards, or more packets come out than the header declares.
+#[cfg(feature = "fuzzing")]
+pub fn fuzz_frame(data: &[u8]) {
+  check_frame(data)
+}
+
+/// Body of [`fuzz_frame`], shared with the random-buffer tests below
+#[cfg(any(test, feature = "fuzzing"))]
+fn check_frame(data: &[u8]) {
+  let declared = packet_count(data);
+  let mut end = 2;
+  let mut walked = 0;
+  for body in Packets::new(data) {
+    assert!(body.start >= end + 2 && body.start <= body.end);
+    assert!(body.end <= data.len());
+    end = body.end;
+    walked += 1;
+  }
+  assert!(walked <= declared);
+
+  let frame = Bytes::copy_from_slice(data);
+  assert!(full_payloads(&frame).all(|p| p.len() == 184));
+
+  // Hint the first few tokens with a mode picked from the data so the
+  // hinted layouts are exercised as well
+  let hints: HashMap<u32, ModeHint> = Packets::new(data)
+    .filter_map(|body| value(&data[body]))
+    .zip(data.iter().cycle())
+    .take(8)
+    .map(|(token, b)| {
+      let mode = [Mode::LTP, Mode::Quote, Mode::Full][*b as usize % 3];
+      (token, ModeHint::new(mode))
+    })
+    .collect();
+  for (best_effort, stamp_requested_mode) in
+    [(false, false), (true, false), (false, true), (true, true)]
+  {
+    let options = ParseOptions {
+      max_packets: usize::MAX,
+      best_effort,
+      stamp_requested_mode,
+      hold_back: Duration::from_secs(1),
+    };
+    for hints in [None, Some(&hints)] {
+      if let Ok(parsed) = parse_frame(data, hints, options) {
+        let emitted = parsed.ticks.len() + parsed.rejected.len();
+        assert!(emitted + parsed.held_back as usize <= declared);
+        assert!(parsed.rejected.iter().all(|r| r.end <= data.len()));
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// xorshift64*: deterministic, so a failing seed can be replayed
+  struct Rng(u64);
+
+  impl Rng {
+    fn next(&mut self) -> u64 {
+      self.0 ^= self.0 >> 12;
+      self.0 ^= self.0 << 25;
+      self.0 ^= self.0 >> 27;
+      self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+      (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, n: usize) -> Vec<u8> {
+      (0..n).map(|_| self.next() as u8).collect()
+    }
+  }
+
+  const SEEDS: u64 = 2000;
+
+  fn check(seed: u64, frame: &[u8]) {
+    let run = std::panic::catch_unwind(|| check_frame(frame));
+    assert!(run.is_ok(), "seed {}: frame {:?}", seed, frame);
+  }
+
+  /// Frame of packets with known and odd lengths and random contents, then
+  /// damaged the way a torn or corrupted read would be
+  fn random_frame(rng: &mut Rng) -> Vec<u8> {
+    const LENGTHS: [usize; 7] = [8, 28, 32, 44, 184, 0, 1];
+    let count = rng.below(12);
+    let mut frame = (count as u16).to_be_bytes().to_vec();
+    for _ in 0..count {
+      let len = match rng.below(4) {
+        0 => rng.below(300),
+        _ => LENGTHS[rng.below(LENGTHS.len())],
+      };
+      frame.extend((len as u16).to_be_bytes());
+      let mut body = rng.bytes(len);
+      // Keep real segments in the token's low byte most of the time
+      if len >= 4 && rng.below(2) == 0 {
+        body[3] = [1, 2, 3, 4, 5, 7, 9][rng.below(7)];
+      }
+      frame.extend(body);
+    }
+    match rng.below(4) {
+      0 => frame.truncate(rng.below(frame.len() + 1)),
+      1 => {
+        let at = rng.below(frame.len());
+        frame[at] = rng.next() as u8;
+      }
+      2 => frame[..2].copy_from_slice(&(rng.next() as u16).to_be_bytes()),
+      _ => {}
+    }
+    frame
+  }
+
+  #[test]
+  fn random_buffers_keep_frame_invariants() {
+    for seed in 1..=SEEDS {
+      let mut rng = Rng(seed);
+      let len = rng.below(512);
+      check(seed, &rng.bytes(len));
+    }
+  }
+
+  #[test]
+  fn random_frames_keep_frame_invariants() {
+    for seed in 1..=SEEDS {
+      let mut rng = Rng(seed);
+      check(seed, &random_frame(&mut rng));
+    }
+  }
+
+  #[test]
+  fn random_frames_parse_every_packet_they_walk() {
+    let options = ParseOptions {
+      max_packets: usize::MAX,
+      best_effort: false,
+      stamp_requested_mode: false,
+      hold_back: Duration::ZERO,
+    };
+    for seed in 1..=SEEDS {
+      let mut rng = Rng(seed);
+      let frame = random_frame(&mut rng);
+      let mut packets = Packets::new(&frame);
+      let bodies: Vec<_> = packets.by_ref().collect();
+      let parsed = parse_frame(&frame, None, options).unwrap();
+      // Without hints every walked packet is either a tick or a reject
+      assert_eq!(
+        parsed.ticks.len() + parsed.rejected.len(),
+        bodies.len(),
+        "seed {}",
+        seed
+      );
+      assert_eq!(parsed.truncated, packets.is_truncated(), "seed {}", seed);
+      assert!(parsed.rejected.iter().all(|r| bodies.contains(r)));
+      let tokens: Vec<u32> = bodies
+        .iter()
+        .filter(|b| !parsed.rejected.contains(b))
+        .filter_map(|b| value(&frame[b.clone()]))
+        .collect();
+      let parsed_tokens: Vec<u32> =
+        parsed.ticks.iter().map(|t| t.instrument_token).collect();
+      assert_eq!(parsed_tokens, tokens, "seed {}", seed);
+    }
+  }
+
+  #[test]
+  fn packet_limit_rejects_the_frame() {
+    let options = ParseOptions {
+      max_packets: 3,
+      best_effort: false,
+      stamp_requested_mode: false,
+      hold_back: Duration::ZERO,
+    };
+    let mut rng = Rng(7);
+    for _ in 0..SEEDS {
+      let frame = random_frame(&mut rng);
+      let declared = packet_count(&frame);
+      match parse_frame(&frame, None, options) {
+        Ok(parsed) => assert!(parsed.ticks.len() <= 3),
+        Err(n) => assert!(n == declared && declared > 3),
+      }
+    }
+  }
+}
@@ -1,19 +1,318 @@
 use crate::models::{
-  Mode, Request, TextMessage, Tick, TickMessage, TickerMessage,
+  CloseInfo, Mode, Request, TextMessage, TickMessage, TickerMessage,
+};
+use crate::parser::{
+  full_payloads, packet_count, parse_frame, ModeHint, ParseOptions,
 };
-use crate::parser::packet_length;
 use bytes::Bytes;
 use futures_util::{SinkExt, StreamExt};
-use serde_json::json;
-use smallvec::SmallVec;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{broadcast, mpsc};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio::task::JoinHandle;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, WebSocketConfig};
+use tokio_tungstenite::{connect_async_with_config, tungstenite::Message};
+use tokio_util::sync::CancellationToken;
+
+// Default bounded capacity for reader -> parser channel to avoid unbounded
+// memory growth
+const DEFAULT_PARSE_CHANNEL_CAP: usize = 4096;
+
+// Upper bound on packets declared by a single binary frame
+const DEFAULT_MAX_PACKETS_PER_FRAME: usize = 10_000;
+
+// How long `close` waits for the server to confirm the close handshake
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// A server close this soon after the handshake, before any data, is the
+// usual symptom of an expired access token
+const EARLY_CLOSE_WINDOW: Duration = Duration::from_secs(10);
+
+// Default bound on commands waiting for the writer task
+const DEFAULT_MAX_PENDING_COMMANDS: usize = 1024;
+
+// Default for `KiteTickerConfig::mode_hold_back`
+const DEFAULT_MODE_HOLD_BACK: Duration = Duration::from_secs(2);
+
+// Subscribed mode per token, read by the parser to settle ambiguous lengths
+type ModeHints = Arc<RwLock<HashMap<u32, ModeHint>>>;
+
+/// Record `mode` as the wanted mode of `tokens`, or forget them on `None`.
+/// Done before the command is sent so the parser holds back packets still in
+/// the previous (or Kite's default) mode. Tokens already in `mode` keep the
+/// time their hint was set.
+fn update_mode_hints(hints: &ModeHints, tokens: &[u32], mode: Option<Mode>) {
+  if let Ok(mut hints) = hints.write() {
+    for &token in tokens {
+      match mode {
+        Some(mode) => {
+          if hints.get(&token).map(|h| h.mode) != Some(mode) {
+            hints.insert(token, ModeHint::new(mode));
+          }
+        }
+        None => {
+          hints.remove(&token);
+        }
+      }
+    }
+  }
+}
+
+/// Current hints of `tokens`, to undo `update_mode_hints` if the command
+/// cannot be sent
+fn saved_mode_hints(
+  hints: &ModeHints,
+  tokens: &[u32],
+) -> Vec<(u32, Option<ModeHint>)> {
+  let Ok(hints) = hints.read() else {
+    return Vec::new();
+  };
+  tokens
+    .iter()
+    .map(|&t| (t, hints.get(&t).copied()))
+    .collect()
+}
+
+/// Put back hints taken with `saved_mode_hints`
+fn restore_mode_hints(hints: &ModeHints, saved: Vec<(u32, Option<ModeHint>)>) {
+  if let Ok(mut hints) = hints.write() {
+    for (token, hint) in saved {
+      match hint {
+        Some(hint) => hints.insert(token, hint),
+        None => hints.remove(&token),
+      };
+    }
+  }
+}
+
+/// Packets parsed successfully vs rejected, updated by the parser task
+#[derive(Debug, Default)]
+struct ParseCounters {
+  parsed: AtomicU64,
+  errors: AtomicU64,
+  /// Packets dropped while their token's mode change was in flight
+  held_back: AtomicU64,
+}
+
+impl ParseCounters {
+  fn snapshot(&self) -> (u64, u64) {
+    (
+      self.parsed.load(Ordering::Relaxed),
+      self.errors.load(Ordering::Relaxed),
+    )
+  }
+}
+
+type WsSink = futures_util::stream::SplitSink<
+  tokio_tungstenite::WebSocketStream<
+    tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+  >,
+  Message,
+>;
+
+/// Where a [`CommandSender`] delivers commands
+#[derive(Debug, Clone)]
+enum CommandTransport {
+  /// Queue for the writer task
+  Queue(mpsc::UnboundedSender<Message>),
+  /// Write to the socket from the caller, one writer at a time
+  Direct(Arc<tokio::sync::Mutex<WsSink>>),
+}
+
+/// Sending half of a connection's command queue (subscribe, mode, ping...).
+///
+/// Tracks how many commands are waiting for the socket writer and rejects new
+/// ones once `max_pending_commands` are queued, so a stalled socket cannot
+/// grow the queue without bound.
+///
+/// With [`KiteTickerConfig::direct_writes`] there is no queue: use
+/// [`send_async`](Self::send_async), which writes to the socket itself.
+#[derive(Debug, Clone)]
+pub struct CommandSender {
+  tx: CommandTransport,
+  depth: Arc<AtomicUsize>,
+  capacity: usize,
+}
 
-// Bounded capacity for reader -> parser channel to avoid unbounded memory growth
-const PARSE_CHANNEL_CAP: usize = 4096;
+impl CommandSender {
+  /// Queue a command for the writer task
+  ///
+  /// Fails on a direct-write connection, which can only be written to
+  /// asynchronously.
+  pub fn send(&self, msg: Message) -> Result<(), String> {
+    let CommandTransport::Queue(tx) = &self.tx else {
+      return Err(
+        "Direct-write connection: use CommandSender::send_async".to_string(),
+      );
+    };
+    let pending = self.depth.fetch_add(1, Ordering::Relaxed);
+    if pending >= self.capacity {
+      self.depth.fetch_sub(1, Ordering::Relaxed);
+      return Err(format!("Command queue full ({} commands pending)", pending));
+    }
+    tx.send(msg).map_err(|e| {
+      self.depth.fetch_sub(1, Ordering::Relaxed);
+      e.to_string()
+    })
+  }
+
+  /// Send a command: queued for the writer task, or written to the socket
+  /// before returning on a direct-write connection
+  pub async fn send_async(&self, msg: Message) -> Result<(), String> {
+    match &self.tx {
+      CommandTransport::Queue(_) => self.send(msg),
+      CommandTransport::Direct(sink) => {
+        sink.lock().await.send(msg).await.map_err(|e| e.to_string())
+      }
+    }
+  }
+
+  /// Number of commands queued but not yet written to the socket
+  pub fn depth(&self) -> usize {
+    self.depth.load(Ordering::Relaxed)
+  }
+
+  /// Maximum number of pending commands
+  pub fn capacity(&self) -> usize {
+    self.capacity
+  }
+}
+
+/// Callback receiving each frame's ticks as a borrowed slice
+///
+/// Runs on the parser task, so it should return quickly; the slice is only
+/// valid for the duration of the call.
+#[derive(Clone)]
+pub struct TickHandler(Arc<TickFn>);
+
+type TickFn = dyn Fn(&[TickMessage]) + Send + Sync;
+
+impl TickHandler {
+  pub fn new(f: impl Fn(&[TickMessage]) + Send + Sync + 'static) -> Self {
+    Self(Arc::new(f))
+  }
+}
+
+impl std::fmt::Debug for TickHandler {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("TickHandler")
+  }
+}
+
+/// Ticker endpoint to connect to
+///
+/// Kite does not publish a separate sandbox ticker endpoint, so development
+/// and simulation setups (local replayers, mock servers) use `Custom` with
+/// their own base URL. Credentials are appended as query parameters either way.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub enum Environment {
+  /// Production ticker at `wss://ws.kite.trade`
+  #[default]
+  Live,
+  /// Any other ticker-compatible endpoint, e.g. `ws://127.0.0.1:9000`
+  Custom(String),
+}
+
+impl Environment {
+  /// Base WebSocket URL for this environment
+  pub fn base_url(&self) -> &str {
+    match self {
+      Environment::Live => "wss://ws.kite.trade",
+      Environment::Custom(url) => url,
+    }
+  }
+}
+
+/// Connection options for a single [`KiteTickerAsync`]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct KiteTickerConfig {
+  /// If true, skip parsing and only publish raw frames
+  pub raw_only: bool,
+
+  /// Cancelling this token closes the socket and stops the reader, parser
+  /// and writer tasks cooperatively
+  pub cancellation: Option<CancellationToken>,
+
+  /// Frames declaring more packets than this are rejected with
+  /// [`TickerMessage::Error`] instead of being parsed
+  pub max_packets_per_frame: usize,
+
+  /// Commands queued beyond this many (while the socket is slow to accept
+  /// writes) are rejected instead of buffered
+  pub max_pending_commands: usize,
+
+  /// Write commands to the socket from the calling task instead of a
+  /// dedicated writer task (default: off). Saves a task and a queue when
+  /// few commands are sent; commands must go through
+  /// [`CommandSender::send_async`].
+  pub direct_writes: bool,
+
+  /// Frames the reader may queue for the parser before dropping new ones
+  /// (default 4096). Raise it if bursts cause frame drops.
+  pub parse_channel_capacity: usize,
+
+  /// Log a warning (with frame size and packet count) whenever parsing a
+  /// single frame takes longer than this
+  pub slow_parse_threshold: Option<Duration>,
+
+  /// Publish packets of unrecognised length as
+  /// [`TickerMessage::UnknownPacket`] (default: off). They are counted as
+  /// parse errors either way.
+  pub emit_unknown_packets: bool,
+
+  /// Decode packets with [`Tick::parse_best_effort`] (default: off), so a
+  /// Full packet with damaged depth still yields its prices as a `partial`
+  /// tick instead of being dropped or carrying bad depth
+  ///
+  /// [`Tick::parse_best_effort`]: crate::Tick::parse_best_effort
+  pub best_effort_parsing: bool,
+
+  /// Fill [`TickMessage::requested_mode`] with the mode each token was
+  /// subscribed in (default: off). Packets still in the previous mode
+  /// during a mode change are then delivered, carrying both modes, instead
+  /// of being held back.
+  pub stamp_requested_mode: bool,
+
+  /// How long after a subscribe or mode change packets still in the
+  /// previous mode are held back (default: 2s). If the new mode has not
+  /// arrived by then, for example because the mode command was lost, the
+  /// packets are delivered in the mode they came in. Held-back packets are
+  /// counted in [`KiteTickerAsync::held_back_count`].
+  pub mode_hold_back: Duration,
+
+  /// Endpoint to connect to (default: [`Environment::Live`])
+  pub environment: Environment,
+
+  /// Deliver ticks to this callback in place instead of broadcasting them.
+  ///
+  /// Frames with up to 32 ticks are parsed into a stack buffer and handed
+  /// over without allocating; subscribers then see no
+  /// [`TickerMessage::Ticks`], while other messages are broadcast as usual.
+  pub on_ticks: Option<TickHandler>,
+}
+
+impl Default for KiteTickerConfig {
+  fn default() -> Self {
+    Self {
+      raw_only: false,
+      cancellation: None,
+      max_packets_per_frame: DEFAULT_MAX_PACKETS_PER_FRAME,
+      max_pending_commands: DEFAULT_MAX_PENDING_COMMANDS,
+      direct_writes: false,
+      parse_channel_capacity: DEFAULT_PARSE_CHANNEL_CAP,
+      slow_parse_threshold: None,
+      emit_unknown_packets: false,
+      best_effort_parsing: false,
+      stamp_requested_mode: false,
+      mode_hold_back: DEFAULT_MODE_HOLD_BACK,
+      environment: Environment::Live,
+      on_ticks: None,
+    }
+  }
+}
 
 #[derive(Debug)]
 ///
@@ -24,16 +323,37 @@ pub struct KiteTickerAsync {
   api_key: String,
   #[allow(dead_code)]
   access_token: String,
-  cmd_tx: Option<mpsc::UnboundedSender<Message>>,
+  cmd_tx: Option<CommandSender>,
   msg_tx: broadcast::Sender<TickerMessage>,
   raw_tx: broadcast::Sender<Bytes>, // raw binary frames
+  raw_timed_tx: broadcast::Sender<(Instant, Bytes)>, // raw frames + receive time
+  latest_raw_tx: watch::Sender<Option<Bytes>>,       // most recent raw frame
+  mode_hints: ModeHints,
+  parse_counters: Arc<ParseCounters>,
   #[allow(dead_code)]
   raw_only: bool, // if true, skip parsing and emit raw frames as TickerMessage::Raw
+  closing: Arc<AtomicBool>, // set once we start the close handshake
   writer_handle: Option<JoinHandle<()>>,
-  reader_handle: Option<JoinHandle<()>>,
+  reader_handle: Option<JoinHandle<Option<CloseFrame>>>,
   parser_handle: Option<JoinHandle<()>>,
 }
 
+/// Failed connect, with the HTTP status if the server refused the handshake
+#[derive(Debug)]
+pub(crate) struct ConnectError {
+  pub(crate) status: Option<u16>,
+  pub(crate) message: String,
+}
+
+impl From<String> for ConnectError {
+  fn from(message: String) -> Self {
+    Self {
+      status: None,
+      message,
+    }
+  }
+}
+
 impl KiteTickerAsync {
   /// Establish a connection with the Kite WebSocket server
   pub async fn connect(
@@ -49,54 +369,162 @@ impl KiteTickerAsync {
     access_token: &str,
     raw_only: bool,
   ) -> Result<Self, String> {
+    let config = KiteTickerConfig {
+      raw_only,
+      ..Default::default()
+    };
+    Self::connect_with_config(api_key, access_token, config).await
+  }
+
+  /// Connect with a full [`KiteTickerConfig`]
+  pub async fn connect_with_config(
+    api_key: &str,
+    access_token: &str,
+    config: KiteTickerConfig,
+  ) -> Result<Self, String> {
+    Self::open(api_key, access_token, config)
+      .await
+      .map_err(|e| e.message)
+  }
+
+  /// [`connect_with_config`](Self::connect_with_config), keeping the HTTP
+  /// status of a refused handshake
+  pub(crate) async fn open(
+    api_key: &str,
+    access_token: &str,
+    config: KiteTickerConfig,
+  ) -> Result<Self, ConnectError> {
+    let raw_only = config.raw_only;
+    let parse_options = ParseOptions {
+      max_packets: config.max_packets_per_frame,
+      best_effort: config.best_effort_parsing,
+      stamp_requested_mode: config.stamp_requested_mode,
+      hold_back: config.mode_hold_back,
+    };
+    let parse_channel_capacity = config.parse_channel_capacity;
+    let slow_parse_threshold = config.slow_parse_threshold;
+    let emit_unknown_packets = config.emit_unknown_packets;
+    let on_ticks = config.on_ticks.clone();
+    let cancel = config.cancellation.clone().unwrap_or_default();
     // Build URL with proper percent-encoding of query params
-    let mut url = url::Url::parse("wss://ws.kite.trade")
+    let mut url = url::Url::parse(config.environment.base_url())
       .map_err(|e| format!("Invalid base URL: {}", e))?;
     {
       let mut qp = url.query_pairs_mut();
       qp.append_pair("api_key", api_key);
       qp.append_pair("access_token", access_token);
     }
+    let ws_config = WebSocketConfig::default();
     // tokio-tungstenite >=0.27 accepts types implementing IntoClientRequest (Url is fine)
     let (ws_stream, _resp) =
-      connect_async(url.as_str()).await.map_err(|e| match e {
-        tokio_tungstenite::tungstenite::Error::Http(response) => {
-          // Provide clearer context for HTTP handshake failures
-          let status = response.status();
-          let reason = status.canonical_reason().unwrap_or("");
-          format!(
-            "HTTP error during WebSocket handshake: {} {}",
-            status, reason
-          )
-        }
-        other => other.to_string(),
-      })?;
+      connect_async_with_config(url.as_str(), Some(ws_config), false)
+        .await
+        .map_err(|e| match e {
+          tokio_tungstenite::tungstenite::Error::Http(response) => {
+            // Provide clearer context for HTTP handshake failures
+            let status = response.status();
+            let reason = status.canonical_reason().unwrap_or("");
+            ConnectError {
+              status: Some(status.as_u16()),
+              message: format!(
+                "HTTP error during WebSocket handshake: {} {}",
+                status, reason
+              ),
+            }
+          }
+          other => other.to_string().into(),
+        })?;
 
     let (write_half, mut read_half) = ws_stream.split();
 
-    let (cmd_tx, mut cmd_rx) = mpsc::unbounded_channel::<Message>();
+    let depth = Arc::new(AtomicUsize::new(0));
+    let capacity = config.max_pending_commands.max(1);
+    let (cmd_tx, writer_handle, direct_sink) = if config.direct_writes {
+      let sink = Arc::new(tokio::sync::Mutex::new(write_half));
+      let cmd_tx = CommandSender {
+        tx: CommandTransport::Direct(Arc::clone(&sink)),
+        depth,
+        capacity,
+      };
+      (cmd_tx, None, Some(sink))
+    } else {
+      let (tx, mut cmd_rx) = mpsc::unbounded_channel::<Message>();
+      let cmd_tx = CommandSender {
+        tx: CommandTransport::Queue(tx),
+        depth,
+        capacity,
+      };
+      let cmd_depth = Arc::clone(&cmd_tx.depth);
+      let mut write = write_half;
+      let writer_cancel = cancel.clone();
+      let writer_handle = tokio::spawn(async move {
+        loop {
+          let msg = tokio::select! {
+            _ = writer_cancel.cancelled() => {
+              let _ = write.send(Message::Close(None)).await;
+              break;
+            }
+            msg = cmd_rx.recv() => msg,
+          };
+          let Some(msg) = msg else { break };
+          cmd_depth.fetch_sub(1, Ordering::Relaxed);
+          // Nothing may follow a close frame; stop even if senders remain
+          let is_close = matches!(msg, Message::Close(_));
+          if write.send(msg).await.is_err() || is_close {
+            break;
+          }
+        }
+      });
+      (cmd_tx, Some(writer_handle), None)
+    };
     // Increase buffer size for high-frequency tick data
     let (msg_tx, _) = broadcast::channel(1000);
     let (raw_tx, _) = broadcast::channel(1000);
-    let mut write = write_half;
-    let writer_handle = tokio::spawn(async move {
-      while let Some(msg) = cmd_rx.recv().await {
-        if write.send(msg).await.is_err() {
-          break;
-        }
-      }
-    });
+    let (raw_timed_tx, _) = broadcast::channel(1000);
+    let (latest_raw_tx, _) = watch::channel(None);
 
     // Channel to decouple read and parse so the websocket stream isn't blocked by parsing.
     // Use a bounded channel with try_send to provide lightweight backpressure under bursts.
-    let (parse_tx, mut parse_rx) = mpsc::channel::<Message>(PARSE_CHANNEL_CAP);
+    let (parse_tx, mut parse_rx) =
+      mpsc::channel::<Message>(parse_channel_capacity.max(1));
 
     // Reader: only forward messages into parse channel, avoid heavy work here
     let msg_sender_for_reader = msg_tx.clone();
+    let reader_cancel = cancel.clone();
+    let closing = Arc::new(AtomicBool::new(false));
+    let reader_closing = Arc::clone(&closing);
     let reader_handle = tokio::spawn(async move {
-      while let Some(message) = read_half.next().await {
+      let opened = Instant::now();
+      let mut close_frame = None;
+      let mut server_closed = false;
+      let mut saw_data = false;
+      loop {
+        let message = tokio::select! {
+          _ = reader_cancel.cancelled() => {
+            // Without a writer task the close frame is sent from here
+            if let Some(sink) = &direct_sink {
+              let _ = sink.lock().await.send(Message::Close(None)).await;
+            }
+            break;
+          }
+          message = read_half.next() => message,
+        };
+        let Some(message) = message else {
+          server_closed = true;
+          break;
+        };
         match message {
           Ok(msg) => {
+            match &msg {
+              Message::Close(frame) => {
+                close_frame = frame.clone();
+                server_closed = true;
+              }
+              // Anything beyond a 1-byte heartbeat proves the session works
+              Message::Binary(b) if b.len() >= 2 => saw_data = true,
+              Message::Text(_) => saw_data = true,
+              _ => {}
+            }
             // Forward to parser using non-blocking try_send; if channel is full, drop frame
             match parse_tx.try_send(msg) {
               Ok(_) => {}
@@ -122,22 +550,85 @@ impl KiteTickerAsync {
               tokio_tungstenite::tungstenite::Error::ConnectionClosed
                 | tokio_tungstenite::tungstenite::Error::AlreadyClosed
             ) {
+              server_closed = true;
               break;
             }
           }
         }
       }
+      let throttled = close_frame.as_ref().is_some_and(|c| {
+        CloseInfo {
+          code: c.code.into(),
+          reason: c.reason.to_string(),
+        }
+        .is_throttle()
+      });
+      let elapsed = opened.elapsed();
+      if server_closed
+        && !saw_data
+        && !throttled
+        && elapsed < EARLY_CLOSE_WINDOW
+        && !reader_closing.load(Ordering::Relaxed)
+      {
+        let hint = format!(
+          "Server closed the connection {:.1?} after the handshake without \
+           sending any data; the access token is likely expired or invalid \
+           (Kite tokens expire daily), generate a new one",
+          elapsed
+        );
+        log::error!("{}", hint);
+        let _ = msg_sender_for_reader.send(TickerMessage::Error(hint));
+      }
+      close_frame
     });
 
-    // Parser: processes messages from the channel and publishes results
+    // Parser: processes messages from the channel and publishes results.
+    // One reader and one parser over a FIFO channel is what guarantees
+    // subscribers see frames in arrival order; parallel parsing would have to
+    // reassemble that order before publishing.
     let msg_sender = msg_tx.clone();
     let raw_sender = raw_tx.clone();
+    let raw_timed_sender = raw_timed_tx.clone();
+    let latest_raw_sender = latest_raw_tx.clone();
+    let mode_hints: ModeHints = Arc::default();
+    let parser_hints = Arc::clone(&mode_hints);
+    let parse_counters: Arc<ParseCounters> = Arc::default();
+    let parser_counters = Arc::clone(&parse_counters);
+    let unknown_sender = emit_unknown_packets.then(|| msg_tx.clone());
     let parser_handle = tokio::spawn(async move {
       let raw_only_mode = raw_only; // capture
       while let Some(msg) = parse_rx.recv().await {
-        if let Some(processed) =
-          process_message(msg, &raw_sender, raw_only_mode)
+        let started = slow_parse_threshold.map(|t| (t, Instant::now()));
+        let frame = match (&msg, started) {
+          (Message::Binary(b), Some(_)) => Some((b.len(), packet_count(b))),
+          _ => None,
+        };
+        let processed = process_message(
+          msg,
+          &raw_sender,
+          &raw_timed_sender,
+          &latest_raw_sender,
+          &parser_hints,
+          &parser_counters,
+          on_ticks.as_ref(),
+          raw_only_mode,
+          parse_options,
+          unknown_sender.as_ref(),
+        );
+        if let (Some((threshold, start)), Some((size, packets))) =
+          (started, frame)
         {
+          let elapsed = start.elapsed();
+          if elapsed > threshold {
+            log::warn!(
+              "Slow parse: {:?} for {}-byte frame with {} packets",
+              elapsed,
+              size,
+              packets
+            );
+          }
+        }
+        if let Some(processed) = processed {
           let _ = msg_sender.send(processed);
         }
       }
@@ -149,8 +640,13 @@ impl KiteTickerAsync {
       cmd_tx: Some(cmd_tx),
       msg_tx,
       raw_tx,
+      raw_timed_tx,
+      latest_raw_tx,
+      mode_hints,
+      parse_counters,
       raw_only,
-      writer_handle: Some(writer_handle),
+      closing,
+      writer_handle,
       reader_handle: Some(reader_handle),
       parser_handle: Some(parser_handle),
     })
@@ -162,7 +658,14 @@ impl KiteTickerAsync {
     instrument_tokens: &[u32],
     mode: Option<Mode>,
   ) -> Result<KiteTickerSubscriber, String> {
-    self.subscribe_cmd(instrument_tokens, mode.as_ref()).await?;
+    let previous = saved_mode_hints(&self.mode_hints, instrument_tokens);
+    if mode.is_some() {
+      update_mode_hints(&self.mode_hints, instrument_tokens, mode);
+    }
+    if let Err(e) = self.subscribe_cmd(instrument_tokens, mode.as_ref()).await {
+      restore_mode_hints(&self.mode_hints, previous);
+      return Err(e);
+    }
     let default_mode = mode.unwrap_or_default();
     let st = instrument_tokens
       .iter()
@@ -173,25 +676,45 @@ impl KiteTickerAsync {
     Ok(KiteTickerSubscriber {
       subscribed_tokens: st,
       rx,
-      cmd_tx: self.cmd_tx.clone().map(Arc::new),
+      cmd_tx: self.cmd_tx.clone(),
+      mode_hints: Arc::clone(&self.mode_hints),
+      parse_counters: Arc::clone(&self.parse_counters),
+      pause: PauseHandle::default(),
     })
   }
 
   /// Close the websocket connection
-  pub async fn close(&mut self) -> Result<(), String> {
+  ///
+  /// Sends a close frame and waits (up to 5 seconds) for the server to answer
+  /// with its own, returning that frame's code and reason. `None` means the
+  /// server closed without a frame or did not confirm in time. The server's
+  /// frame is also published to subscribers as
+  /// [`TickerMessage::ClosingMessage`].
+  pub async fn close(&mut self) -> Result<Option<CloseFrame>, String> {
+    self.closing.store(true, Ordering::Relaxed);
     if let Some(tx) = self.cmd_tx.take() {
-      let _ = tx.send(Message::Close(None));
+      let _ = tx.send_async(Message::Close(None)).await;
     }
-    if let Some(handle) = self.writer_handle.take() {
-      handle.await.map_err(|e| e.to_string())?;
+    if let Some(mut handle) = self.writer_handle.take() {
+      match tokio::time::timeout(CLOSE_TIMEOUT, &mut handle).await {
+        Ok(res) => res.map_err(|e| e.to_string())?,
+        Err(_) => handle.abort(),
+      }
     }
-    if let Some(handle) = self.reader_handle.take() {
-      handle.await.map_err(|e| e.to_string())?;
+    let mut close_frame = None;
+    if let Some(mut handle) = self.reader_handle.take() {
+      match tokio::time::timeout(CLOSE_TIMEOUT, &mut handle).await {
+        Ok(res) => close_frame = res.map_err(|e| e.to_string())?,
+        Err(_) => {
+          log::warn!("Server did not confirm close within {:?}", CLOSE_TIMEOUT);
+          handle.abort();
+        }
+      }
     }
     if let Some(handle) = self.parser_handle.take() {
       handle.await.map_err(|e| e.to_string())?;
     }
-    Ok(())
+    Ok(close_frame)
   }
 
   async fn subscribe_cmd(
@@ -211,7 +734,7 @@ impl KiteTickerAsync {
 
     for msg in msgs {
       if let Some(tx) = &self.cmd_tx {
-        tx.send(msg).map_err(|e| e.to_string())?;
+        tx.send_async(msg).await?;
       }
     }
 
@@ -222,11 +745,9 @@ impl KiteTickerAsync {
 
   /// Check if the connection is still alive
   pub fn is_connected(&self) -> bool {
+    // Direct-write connections have no writer task
     self.cmd_tx.is_some()
-      && self
-        .writer_handle
-        .as_ref()
-        .is_some_and(|h| !h.is_finished())
+      && self.writer_handle.as_ref().is_none_or(|h| !h.is_finished())
       && self
         .reader_handle
         .as_ref()
@@ -236,8 +757,7 @@ impl KiteTickerAsync {
   /// Send a ping to keep the connection alive
   pub async fn ping(&mut self) -> Result<(), String> {
     if let Some(tx) = &self.cmd_tx {
-      tx.send(Message::Ping(bytes::Bytes::new()))
-        .map_err(|e| e.to_string())?;
+      tx.send_async(Message::Ping(bytes::Bytes::new())).await?;
       Ok(())
     } else {
       Err("Connection is closed".to_string())
@@ -266,6 +786,29 @@ impl KiteTickerAsync {
     self.raw_tx.subscribe()
   }
 
+  /// Like [`subscribe_raw_frames`](Self::subscribe_raw_frames), with each
+  /// frame stamped when the parser task picked it up, for latency analysis.
+  pub fn subscribe_raw_frames_timed(
+    &self,
+  ) -> broadcast::Receiver<(Instant, Bytes)> {
+    self.raw_timed_tx.subscribe()
+  }
+
+  /// The most recently received raw frame, without waiting.
+  ///
+  /// Returns `None` until the first binary frame arrives. The returned `Bytes`
+  /// shares the frame buffer (clone is cheap), so it is suitable for polling
+  /// from synchronous code such as snapshot-style UIs.
+  pub fn latest_raw_frame(&self) -> Option<Bytes> {
+    self.latest_raw_tx.borrow().clone()
+  }
+
+  /// Watch for new frames; cheaper than a raw subscription when only the
+  /// arrival of a frame matters (e.g. liveness tracking)
+  pub(crate) fn watch_raw_frames(&self) -> watch::Receiver<Option<Bytes>> {
+    self.latest_raw_tx.subscribe()
+  }
+
   /// Backward-compatible alias for subscribe_raw_frames.
   #[deprecated(
     note = "use subscribe_raw_frames() instead; now returns bytes::Bytes"
@@ -290,9 +833,31 @@ impl KiteTickerAsync {
   }
 
   /// Get a clone of the internal command sender for incremental ops
-  pub fn command_sender(&self) -> Option<mpsc::UnboundedSender<Message>> {
+  pub fn command_sender(&self) -> Option<CommandSender> {
     self.cmd_tx.clone()
   }
+
+  /// Packets parsed successfully and packets rejected, since connecting
+  pub fn parse_counts(&self) -> (u64, u64) {
+    self.parse_counters.snapshot()
+  }
+
+  /// Packets held back because they arrived before their token's new mode
+  /// took effect, since connecting
+  pub fn held_back_count(&self) -> u64 {
+    self.parse_counters.held_back.load(Ordering::Relaxed)
+  }
+
+  /// Replace the per-token subscription modes the parser uses as hints,
+  /// keeping the hold-back start of tokens whose mode is unchanged
+  pub(crate) fn set_mode_hints(&self, modes: &HashMap<u32, Mode>) {
+    if let Ok(mut hints) = self.mode_hints.write() {
+      hints.retain(|token, hint| modes.get(token) == Some(&hint.mode));
+      for (&token, &mode) in modes {
+        hints.entry(token).or_insert_with(|| ModeHint::new(mode));
+      }
+    }
+  }
 }
 
 #[derive(Debug)]
@@ -303,7 +868,43 @@ pub struct KiteTickerSubscriber {
   // Now independent of owning the ticker; commands go through channel retained in KiteTickerAsync
   subscribed_tokens: HashMap<u32, Mode>,
   rx: broadcast::Receiver<TickerMessage>,
-  cmd_tx: Option<Arc<mpsc::UnboundedSender<Message>>>,
+  cmd_tx: Option<CommandSender>,
+  mode_hints: ModeHints,
+  parse_counters: Arc<ParseCounters>,
+  pause: PauseHandle,
+}
+
+/// Pauses and resumes a [`KiteTickerSubscriber`] from another task, e.g.
+/// while that task is waiting in [`next_message`](KiteTickerSubscriber::next_message)
+#[derive(Debug, Clone, Default)]
+pub struct PauseHandle(Arc<PauseState>);
+
+#[derive(Debug, Default)]
+struct PauseState {
+  paused: AtomicBool,
+  /// Set by `resume`; the subscriber skips its backlog when it sees it
+  resumed: AtomicBool,
+  wake: tokio::sync::Notify,
+}
+
+impl PauseHandle {
+  /// Stop delivering messages without unsubscribing from Kite
+  pub fn pause(&self) {
+    self.0.paused.store(true, Ordering::Release);
+  }
+
+  /// Resume delivery, skipping whatever was broadcast while paused
+  pub fn resume(&self) {
+    if self.0.paused.swap(false, Ordering::AcqRel) {
+      self.0.resumed.store(true, Ordering::Release);
+      self.0.wake.notify_waiters();
+    }
+  }
+
+  /// Whether delivery is paused
+  pub fn is_paused(&self) -> bool {
+    self.0.paused.load(Ordering::Acquire)
+  }
 }
 
 impl KiteTickerSubscriber {
@@ -331,6 +932,10 @@ impl KiteTickerSubscriber {
   }
 
   /// Subscribe to new tokens
+  ///
+  /// If a command cannot be sent, the tokens are not recorded as subscribed
+  /// and the error is returned. When only the `mode` command fails, an
+  /// unsubscribe is attempted so the server does not keep them either.
   pub async fn subscribe(
     &mut self,
     tokens: &[u32],
@@ -350,18 +955,39 @@ impl KiteTickerSubscriber {
     if new_tokens.is_empty() {
       return Ok(());
     }
-    if let Some(tx) = &self.cmd_tx {
-      // send subscribe
-      let _ = tx.send(Message::Text(
+    let previous = saved_mode_hints(&self.mode_hints, &new_tokens);
+    if mode.is_some() {
+      update_mode_hints(&self.mode_hints, &new_tokens, mode);
+    }
+    let Some(tx) = &self.cmd_tx else {
+      return Ok(());
+    };
+    let mut result = tx
+      .send_async(Message::Text(
         Request::subscribe(&new_tokens).to_string().into(),
-      ));
-      if mode.is_some() {
-        let _ = tx.send(Message::Text(
+      ))
+      .await;
+    if result.is_ok() && mode.is_some() {
+      result = tx
+        .send_async(Message::Text(
           Request::mode(default_mode, &new_tokens).to_string().into(),
-        ));
+        ))
+        .await;
+      if result.is_err() {
+        let _ = tx
+          .send_async(Message::Text(
+            Request::unsubscribe(&new_tokens).to_string().into(),
+          ))
+          .await;
       }
     }
-    Ok(())
+    if result.is_err() {
+      self
+        .subscribed_tokens
+        .retain(|k, _| !new_tokens.contains(k));
+      restore_mode_hints(&self.mode_hints, previous);
+    }
+    result
   }
 
   /// Change the mode of the subscribed instrument tokens
@@ -374,10 +1000,18 @@ impl KiteTickerSubscriber {
     if tokens.is_empty() {
       return Ok(());
     }
+    let previous = saved_mode_hints(&self.mode_hints, &tokens);
+    update_mode_hints(&self.mode_hints, &tokens, Some(mode));
     if let Some(tx) = &self.cmd_tx {
-      let _ = tx.send(Message::Text(
-        Request::mode(mode, &tokens).to_string().into(),
-      ));
+      let sent = tx
+        .send_async(Message::Text(
+          Request::mode(mode, &tokens).to_string().into(),
+        ))
+        .await;
+      if sent.is_err() {
+        restore_mode_hints(&self.mode_hints, previous);
+      }
+      sent?;
     }
     Ok(())
   }
@@ -394,19 +1028,48 @@ impl KiteTickerSubscriber {
       return Ok(());
     }
     if let Some(tx) = &self.cmd_tx {
-      let _ = tx.send(Message::Text(
+      tx.send_async(Message::Text(
         Request::unsubscribe(&tokens).to_string().into(),
-      ));
+      ))
+      .await?;
     }
     self.subscribed_tokens.retain(|k, _| !tokens.contains(k));
+    update_mode_hints(&self.mode_hints, &tokens, None);
     Ok(())
   }
 
   /// Get the next message from the server, waiting if necessary.
   /// If the result is None then server is terminated
+  ///
+  /// While paused this waits, discarding what arrives, and returns promptly
+  /// with the first message after [`PauseHandle::resume`].
   pub async fn next_message(
     &mut self,
   ) -> Result<Option<TickerMessage>, String> {
+    loop {
+      if self.pause.0.resumed.swap(false, Ordering::AcqRel) {
+        self.rx = self.rx.resubscribe();
+      }
+      if !self.pause.is_paused() {
+        break;
+      }
+      let resumed = self.pause.0.wake.notified();
+      tokio::pin!(resumed);
+      // Register before re-checking, so a resume in between is not missed
+      resumed.as_mut().enable();
+      if !self.pause.is_paused() {
+        continue;
+      }
+      tokio::select! {
+        _ = resumed => {}
+        received = self.rx.recv() => {
+          // Discard; only a closed stream ends the wait
+          if let Err(broadcast::error::RecvError::Closed) = received {
+            return Ok(None);
+          }
+        }
+      }
+    }
     match self.rx.recv().await {
       Ok(msg) => Ok(Some(msg)),
       Err(broadcast::error::RecvError::Closed) => Ok(None),
@@ -414,15 +1077,90 @@ impl KiteTickerSubscriber {
     }
   }
 
+  /// Stop delivering messages without unsubscribing from Kite.
+  ///
+  /// While paused, `next_message` discards everything it receives. Use a
+  /// [`pause_handle`](Self::pause_handle) to resume from another task while
+  /// this one waits in `next_message`.
+  pub fn pause(&self) {
+    self.pause.pause();
+  }
+
+  /// Resume delivery, skipping whatever was broadcast while paused
+  pub fn resume(&self) {
+    self.pause.resume();
+  }
+
+  /// Whether [`pause`](Self::pause) is in effect
+  pub fn is_paused(&self) -> bool {
+    self.pause.is_paused()
+  }
+
+  /// Handle that pauses and resumes this subscriber from elsewhere
+  pub fn pause_handle(&self) -> PauseHandle {
+    self.pause.clone()
+  }
+
   pub async fn close(&mut self) -> Result<(), String> {
     Ok(())
   }
+
+  /// Gather ticks across messages until `n` are collected or `timeout`
+  /// elapses, returning at most `n` ticks.
+  ///
+  /// Non-tick messages are skipped and lagged receives are tolerated; fewer
+  /// than `n` ticks are returned on timeout or when the stream ends.
+  pub async fn collect_ticks(
+    &mut self,
+    n: usize,
+    timeout: Duration,
+  ) -> Result<Vec<TickMessage>, String> {
+    let mut ticks = Vec::with_capacity(n);
+    let deadline = tokio::time::Instant::now() + timeout;
+    while ticks.len() < n {
+      match tokio::time::timeout_at(deadline, self.rx.recv()).await {
+        Err(_) => break,
+        Ok(Ok(TickerMessage::Ticks(batch))) => ticks.extend(batch),
+        Ok(Ok(_)) => {}
+        Ok(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+          log::warn!("collect_ticks lagged, skipped {} messages", skipped);
+        }
+        Ok(Err(broadcast::error::RecvError::Closed)) => break,
+      }
+    }
+    ticks.truncate(n);
+    Ok(ticks)
+  }
+
+  /// Number of commands waiting to be written to the socket
+  pub fn command_queue_depth(&self) -> usize {
+    self.cmd_tx.as_ref().map_or(0, CommandSender::depth)
+  }
+
+  /// Packets parsed successfully and packets rejected, since connecting
+  pub fn parse_counts(&self) -> (u64, u64) {
+    self.parse_counters.snapshot()
+  }
+
+  /// Packets held back because they arrived before their token's new mode
+  /// took effect, since connecting
+  pub fn held_back_count(&self) -> u64 {
+    self.parse_counters.held_back.load(Ordering::Relaxed)
+  }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_message(
   message: Message,
   raw_sender: &broadcast::Sender<Bytes>,
+  raw_timed: &broadcast::Sender<(Instant, Bytes)>,
+  latest_raw: &watch::Sender<Option<Bytes>>,
+  mode_hints: &ModeHints,
+  counters: &ParseCounters,
+  on_ticks: Option<&TickHandler>,
   raw_only: bool,
+  parse_options: ParseOptions,
+  unknown_packets: Option<&broadcast::Sender<TickerMessage>>,
 ) -> Option<TickerMessage> {
   match message {
     Message::Text(text_message) => {
@@ -432,25 +1170,53 @@ fn process_message(
       // Convert once to Bytes to avoid cloning the Vec for raw subscribers
       let bytes = binary_message;
       let slice: &[u8] = &bytes;
-      // publish raw first (cheap clone)
-      let _ = raw_sender.send(bytes.clone());
+      // publish raw first (cheap clone). This must stay ahead of the
+      // heartbeat drop below: the manager's liveness watcher only sees
+      // `latest_raw`, so 1-byte heartbeats keep idle connections healthy in
+      // parsed mode too.
+      latest_raw.send_replace(Some(bytes.clone()));
+      // Skip the broadcast bookkeeping when nobody consumes raw frames
+      if raw_sender.receiver_count() > 0 {
+        let _ = raw_sender.send(bytes.clone());
+      }
+      if raw_timed.receiver_count() > 0 {
+        let _ = raw_timed.send((Instant::now(), bytes.clone()));
+      }
       if raw_only {
         // In raw-only mode, rely solely on raw_tx broadcast to deliver zero-copy frames.
         // Do not emit a TickerMessage to avoid extra allocations or duplicates.
         return None;
       }
-      // Drop 1-byte heartbeat frames per protocol (no downstream churn)
+      // Drop 1-byte heartbeat frames per protocol (no downstream churn);
+      // liveness was already recorded via `latest_raw`
       if slice.len() < 2 {
         None
       } else {
-        process_binary(slice)
+        let hints = mode_hints.read().ok();
+        let hints = hints.as_deref().filter(|h| !h.is_empty());
+        process_binary(
+          &bytes,
+          hints,
+          parse_options,
+          counters,
+          on_ticks,
+          unknown_packets,
+        )
       }
     }
     Message::Close(closing_message) => closing_message.map(|c| {
-      TickerMessage::ClosingMessage(json!({
-        "code": c.code.to_string(),
-        "reason": c.reason.to_string()
-      }))
+      let info = CloseInfo {
+        code: c.code.into(),
+        reason: c.reason.to_string(),
+      };
+      if info.is_throttle() {
+        TickerMessage::Throttled(format!(
+          "Closed by server ({}): {}",
+          info.code, info.reason
+        ))
+      } else {
+        TickerMessage::ClosingMessage(info.into())
+      }
     }),
     Message::Ping(_) => None,
     Message::Pong(_) => None,
@@ -473,7 +1239,7 @@ impl KiteTickerRawSubscriber184 {
     loop {
       match self.rx.recv().await {
         Ok(frame) => {
-          if let Some(bytes) = extract_first_full_payload(&frame) {
+          if let Some(bytes) = full_payloads(&frame).next() {
             self.last_payload = Some(bytes.clone());
             return Ok(Some(bytes));
           }
@@ -534,7 +1300,7 @@ impl KiteTickerRawSubscriber184 {
     while out.len() < max {
       match self.rx.recv().await {
         Ok(frame) => {
-          extract_all_full_payloads(&frame, max - out.len(), &mut out);
+          out.extend(full_payloads(&frame).take(max - out.len()));
           if out.len() >= max {
             break;
           }
@@ -548,111 +1314,65 @@ impl KiteTickerRawSubscriber184 {
   }
 }
 
-#[inline]
-fn extract_first_full_payload(frame: &Bytes) -> Option<Bytes> {
-  if frame.len() < 2 {
-    return None;
-  }
-  let mut start = 2usize;
-  let num_packets = u16::from_be_bytes([frame[0], frame[1]]) as usize;
-  for _ in 0..num_packets {
-    if start + 2 > frame.len() {
-      return None;
-    }
-    let packet_len = packet_length(&frame[start..start + 2]);
-    let body_start = start + 2;
-    let next_start = body_start + packet_len;
-    if next_start > frame.len() {
-      return None;
-    }
-    if packet_len == 184 {
-      // slice reference into Bytes
-      return Some(frame.slice(body_start..next_start));
+fn process_binary(
+  binary_message: &Bytes,
+  mode_hints: Option<&HashMap<u32, ModeHint>>,
+  options: ParseOptions,
+  counters: &ParseCounters,
+  on_ticks: Option<&TickHandler>,
+  unknown_packets: Option<&broadcast::Sender<TickerMessage>>,
+) -> Option<TickerMessage> {
+  let parsed = match parse_frame(binary_message, mode_hints, options) {
+    Ok(parsed) => parsed,
+    Err(declared) => {
+      counters.errors.fetch_add(1, Ordering::Relaxed);
+      return Some(TickerMessage::Error(format!(
+        "Frame declares {} packets, exceeding limit of {}",
+        declared, options.max_packets
+      )));
     }
-    start = next_start;
+  };
+  let errors = parsed.error_count();
+  counters.errors.fetch_add(errors, Ordering::Relaxed);
+  if parsed.held_back > 0 {
+    counters
+      .held_back
+      .fetch_add(parsed.held_back, Ordering::Relaxed);
   }
-  None
-}
-
-#[inline]
-fn extract_all_full_payloads(
-  frame: &Bytes,
-  limit: usize,
-  out: &mut Vec<Bytes>,
-) {
-  if frame.len() < 2 || limit == 0 {
-    return;
-  }
-  let mut start = 2usize;
-  let num_packets = u16::from_be_bytes([frame[0], frame[1]]) as usize;
-  let mut cnt = 0usize;
-  for _ in 0..num_packets {
-    if cnt >= limit {
-      break;
-    }
-    if start + 2 > frame.len() {
-      break;
-    }
-    let packet_len = packet_length(&frame[start..start + 2]);
-    let body_start = start + 2;
-    let next_start = body_start + packet_len;
-    if next_start > frame.len() {
-      break;
-    }
-    if packet_len == 184 {
-      out.push(frame.slice(body_start..next_start));
-      cnt += 1;
-      if cnt >= limit {
-        break;
+  counters
+    .parsed
+    .fetch_add(parsed.ticks.len() as u64, Ordering::Relaxed);
+  let message = if !parsed.ticks.is_empty() {
+    match on_ticks {
+      Some(handler) => {
+        (handler.0)(&parsed.ticks);
+        None
       }
+      None => Some(TickerMessage::Ticks(parsed.ticks.into_vec())),
     }
-    start = next_start;
-  }
-}
-
-fn process_binary(binary_message: &[u8]) -> Option<TickerMessage> {
-  if binary_message.len() < 2 {
-    return None;
-  }
-  let num_packets =
-    u16::from_be_bytes([binary_message[0], binary_message[1]]) as usize;
-  if num_packets > 0 {
-    let mut start = 2;
-    // Inline small optimization: most frames contain modest number of ticks
-    let mut ticks: SmallVec<[TickMessage; 32]> =
-      SmallVec::with_capacity(num_packets.min(32));
-    let mut had_error = false;
-    for _ in 0..num_packets {
-      if start + 2 > binary_message.len() {
-        had_error = true;
-        break;
-      }
-      let packet_len = packet_length(&binary_message[start..start + 2]);
-      let next_start = start + 2 + packet_len;
-      if next_start > binary_message.len() {
-        had_error = true;
-        break;
+  } else if errors > 0 {
+    Some(TickerMessage::Error(
+      "Failed to parse tick(s) in frame".to_string(),
+    ))
+  } else {
+    None
+  };
+  // Only length is checked, so a rejected packet is a layout we don't know.
+  // They are published after the frame's ticks, which go out first here.
+  match unknown_packets {
+    Some(tx) if !parsed.rejected.is_empty() => {
+      if let Some(message) = message {
+        let _ = tx.send(message);
       }
-      match Tick::try_from(&binary_message[start + 2..next_start]) {
-        Ok(tick) => ticks.push(TickMessage::new(tick.instrument_token, tick)),
-        Err(_e) => {
-          // Skip this packet, continue with others
-          had_error = true;
-        }
+      for body in parsed.rejected {
+        let _ = tx.send(TickerMessage::UnknownPacket {
+          size: body.len(),
+          bytes: binary_message.slice(body),
+        });
       }
-      start = next_start;
-    }
-    if !ticks.is_empty() {
-      Some(TickerMessage::Ticks(ticks.into_vec()))
-    } else if had_error {
-      Some(TickerMessage::Error(
-        "Failed to parse tick(s) in frame".to_string(),
-      ))
-    } else {
       None
     }
-  } else {
-    None
+    _ => message,
   }
 }
 
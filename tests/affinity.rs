@@ -0,0 +1,34 @@
+//! Parsers with a core affinity keep delivering ticks
+
+mod common;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManagerBuilder, Mode, TickerMessage,
+};
+
+#[tokio::test]
+async fn pinned_parser_delivers_ticks() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(1);
+  // Without the `core_affinity` feature, or on a core that does not exist,
+  // the parser still runs, just unpinned
+  config.parser_core_affinity = Some(vec![usize::MAX]);
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+
+  server.send_frame(0, fixtures::frame(&[fixtures::nse_equity().ltp()]));
+  let message = tokio::time::timeout(WAIT, receiver.recv()).await.unwrap();
+  let Ok(TickerMessage::Ticks(ticks)) = message else {
+    panic!("expected ticks, got {:?}", message);
+  };
+  assert_eq!(ticks[0].instrument_token, fixtures::NSE_EQUITY);
+  manager.stop().await.unwrap();
+}
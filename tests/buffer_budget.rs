@@ -0,0 +1,89 @@
+//! `max_total_buffered_messages` counting only what consumers have not read
+
+mod common;
+
+use std::time::Duration;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManager, KiteTickerManagerBuilder, Mode, TickerMessage,
+};
+use tokio::sync::broadcast;
+
+const BUDGET: usize = 16;
+
+async fn started(server: &MockServer) -> KiteTickerManager {
+  let mut config = server.manager_config(1);
+  config.connection_buffer_size = BUDGET;
+  config.parser_buffer_size = BUDGET;
+  config.max_total_buffered_messages = Some(BUDGET);
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .try_build()
+    .unwrap();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+  manager
+}
+
+async fn buffered(manager: &KiteTickerManager) -> usize {
+  manager.get_stats().await.unwrap().total_buffered_messages
+}
+
+async fn wait_for_buffered(manager: &KiteTickerManager, n: usize) {
+  tokio::time::timeout(WAIT, async {
+    while buffered(manager).await != n {
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+  })
+  .await
+  .unwrap_or_else(|_| panic!("buffered never reached {n}"));
+}
+
+async fn next_ticks(receiver: &mut broadcast::Receiver<TickerMessage>) {
+  tokio::time::timeout(WAIT, async {
+    while !matches!(receiver.recv().await, Ok(TickerMessage::Ticks(_))) {}
+  })
+  .await
+  .expect("no ticks within the wait");
+}
+
+#[tokio::test]
+async fn ticks_nobody_receives_do_not_use_the_budget() {
+  let server = MockServer::start().await;
+  let manager = started(&server).await;
+  let frame = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  for _ in 0..BUDGET * 2 {
+    server.send_frame(0, frame.clone());
+  }
+  tokio::time::sleep(Duration::from_millis(200)).await;
+  assert_eq!(buffered(&manager).await, 0);
+
+  // The budget was never exhausted, so the first receiver gets ticks
+  let mut manager = manager;
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  server.send_frame(0, frame);
+  next_ticks(&mut receiver).await;
+}
+
+#[tokio::test]
+async fn budget_follows_the_receiver_backlog() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+
+  let frame = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  for _ in 0..4 {
+    server.send_frame(0, frame.clone());
+  }
+  wait_for_buffered(&manager, 4).await;
+
+  for _ in 0..4 {
+    next_ticks(&mut receiver).await;
+  }
+  assert_eq!(buffered(&manager).await, 0);
+}
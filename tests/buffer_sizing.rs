@@ -0,0 +1,70 @@
+//! Parser buffers sized by each connection's dominant mode
+
+mod common;
+
+use std::time::Duration;
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManagerBuilder, Mode, TickerMessage,
+};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+const FRAMES: usize = 20;
+
+/// Ticks messages waiting in `receiver`, or the lag it reports first
+fn backlog(
+  receiver: &mut broadcast::Receiver<TickerMessage>,
+) -> Result<usize, u64> {
+  let mut ticks = 0;
+  loop {
+    match receiver.try_recv() {
+      Ok(TickerMessage::Ticks(_)) => ticks += 1,
+      Ok(_) => {}
+      Err(TryRecvError::Lagged(n)) => return Err(n),
+      Err(_) => return Ok(ticks),
+    }
+  }
+}
+
+#[tokio::test]
+async fn full_mode_connection_gets_the_larger_buffer() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(2))
+    .mode_buffer_sizes(4, 64)
+    .build();
+  // Queued before start: the least-loaded router puts one on each connection
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::Full))
+    .await
+    .unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::BSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  manager.start().await.unwrap();
+  let distribution = manager.get_symbol_distribution();
+  assert_eq!(
+    distribution[&ChannelId::Connection1],
+    [fixtures::NSE_EQUITY]
+  );
+  assert_eq!(
+    distribution[&ChannelId::Connection2],
+    [fixtures::BSE_EQUITY]
+  );
+
+  let mut full = manager.get_channel(ChannelId::Connection1).unwrap();
+  let mut ltp = manager.get_channel(ChannelId::Connection2).unwrap();
+  let full_frame = fixtures::frame(&[fixtures::nse_equity().full()]);
+  let ltp_frame = fixtures::frame(&[fixtures::bse_equity().ltp()]);
+  for _ in 0..FRAMES {
+    server.send_frame(0, full_frame.clone());
+    server.send_frame(1, ltp_frame.clone());
+  }
+  tokio::time::sleep(Duration::from_millis(300)).await;
+
+  // The Full connection holds every frame; the LTP one overflowed
+  assert_eq!(backlog(&mut full), Ok(FRAMES));
+  assert!(backlog(&mut ltp).is_err());
+}
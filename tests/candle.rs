@@ -0,0 +1,99 @@
+//! Candle aggregation over minute windows
+
+use kiteticker_async_manager::{CandleAggregator, Tick, TickMessage};
+use std::time::Duration;
+
+const TOKEN: u32 = 408_065;
+
+fn tick(price: f64, volume: u32) -> TickMessage {
+  let mut content = Tick::default();
+  content.instrument_token = TOKEN;
+  content.last_price = Some(price);
+  content.volume_traded = Some(volume);
+  TickMessage::new(TOKEN, content)
+}
+
+fn secs(s: u64) -> Duration {
+  Duration::from_secs(s)
+}
+
+fn minute_aggregator() -> CandleAggregator {
+  CandleAggregator::new(secs(60)).unwrap()
+}
+
+#[test]
+fn ticks_across_two_minute_boundaries_emit_two_candles() {
+  let mut agg = minute_aggregator();
+  let mut candles = Vec::new();
+  for (at, price, volume) in [
+    (0, 100.0, 1_000),
+    (20, 104.0, 1_200),
+    (40, 98.5, 1_250),
+    (59, 101.0, 1_300),
+    (60, 101.5, 1_310),
+    (90, 106.0, 1_400),
+    (110, 103.0, 1_450),
+    (120, 102.0, 1_500),
+  ] {
+    candles.extend(agg.push_at(&tick(price, volume), secs(at)));
+  }
+
+  assert_eq!(candles.len(), 2);
+  let first = &candles[0];
+  assert_eq!(
+    (first.open, first.high, first.low, first.close),
+    (100.0, 104.0, 98.5, 101.0)
+  );
+  assert_eq!(
+    (first.start, first.end, first.volume),
+    (secs(0), secs(60), 300)
+  );
+  let second = &candles[1];
+  assert_eq!(
+    (second.open, second.high, second.low, second.close),
+    (101.5, 106.0, 101.5, 103.0)
+  );
+  assert_eq!(
+    (second.start, second.end, second.volume),
+    (secs(60), secs(120), 150)
+  );
+}
+
+#[test]
+fn gaps_carry_forward_the_last_close() {
+  let mut agg = minute_aggregator();
+  agg.push_at(&tick(100.0, 10), secs(5));
+  let candles = agg.push_at(&tick(110.0, 20), secs(200));
+
+  assert_eq!(candles.len(), 3);
+  for gap in &candles[1..] {
+    assert_eq!((gap.open, gap.close, gap.volume), (100.0, 100.0, 0));
+  }
+  assert_eq!((candles[1].start, candles[2].start), (secs(60), secs(120)));
+}
+
+#[test]
+fn gap_fill_is_capped() {
+  let mut agg = minute_aggregator().with_max_gap_candles(2);
+  agg.push_at(&tick(100.0, 10), secs(0));
+  // An overnight gap would otherwise produce ~1000 flat candles
+  let candles = agg.push_at(&tick(100.0, 10), secs(17 * 3600));
+  assert_eq!(candles.len(), 3);
+
+  let mut agg = minute_aggregator().with_max_gap_candles(0);
+  agg.push_at(&tick(100.0, 10), secs(0));
+  assert_eq!(agg.flush(secs(600)).len(), 1);
+}
+
+#[test]
+fn default_cap_bounds_a_huge_gap() {
+  let mut agg = minute_aggregator();
+  agg.push_at(&tick(100.0, 10), secs(0));
+  let candles = agg.push_at(&tick(100.0, 10), secs(1_700_000_000));
+  assert_eq!(candles.len(), 1 + CandleAggregator::DEFAULT_MAX_GAP_CANDLES);
+}
+
+#[test]
+fn zero_interval_is_rejected() {
+  assert!(CandleAggregator::new(Duration::ZERO).is_err());
+}
@@ -0,0 +1,73 @@
+//! Typed close information on `TickerMessage::ClosingMessage`
+
+mod common;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  CloseInfo, KiteTickerAsync, KiteTickerConfig, Mode, TickerMessage,
+};
+use serde_json::json;
+
+#[tokio::test]
+async fn close_frame_becomes_close_info() {
+  let server = MockServer::start().await;
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  server.close_client(0, 4001, "session expired");
+  let info = tokio::time::timeout(WAIT, async {
+    loop {
+      match subscriber.next_message().await.unwrap() {
+        Some(message) => {
+          if let Some(info) = message.close_info() {
+            return info;
+          }
+        }
+        None => panic!("stream closed without a closing message"),
+      }
+    }
+  })
+  .await
+  .expect("no closing message");
+  assert_eq!(
+    info,
+    CloseInfo {
+      code: 4001,
+      reason: "session expired".to_string(),
+    }
+  );
+  assert!(!info.is_throttle());
+}
+
+#[test]
+fn closing_payloads_convert_both_ways() {
+  // 1006 never travels in a frame; it reaches consumers as a payload
+  let info = CloseInfo {
+    code: 1006,
+    reason: "abnormal closure".to_string(),
+  };
+  let value = serde_json::Value::from(info.clone());
+  assert_eq!(
+    value,
+    json!({ "code": "1006", "reason": "abnormal closure" })
+  );
+  let message = TickerMessage::ClosingMessage(value);
+  assert_eq!(message.close_info(), Some(info));
+
+  // Numeric codes and a missing reason are accepted too
+  let numeric = CloseInfo::from_value(&json!({ "code": 1013 })).unwrap();
+  assert_eq!(numeric.code, 1013);
+  assert!(numeric.reason.is_empty());
+  assert!(numeric.is_throttle());
+
+  assert_eq!(CloseInfo::from_value(&json!({ "reason": "no code" })), None);
+  assert_eq!(TickerMessage::Ticks(Vec::new()).close_info(), None);
+}
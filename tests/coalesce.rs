@@ -0,0 +1,57 @@
+//! Coalescing identical consecutive Quote/Full ticks
+
+mod common;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManagerBuilder, Mode, TickMessage, TickerMessage,
+};
+
+#[tokio::test]
+async fn repeated_full_ticks_are_dropped_and_changes_pass() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .coalesce_duplicate_ticks(true)
+    .build();
+  manager.start().await.unwrap();
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  manager
+    .subscribe_symbols(
+      &[fixtures::NSE_EQUITY, fixtures::BSE_EQUITY],
+      Some(Mode::Full),
+    )
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  let full = fixtures::nse_equity().full();
+  let mut changed = fixtures::nse_equity();
+  changed.last_price += 5;
+  server.send_frame(0, fixtures::frame(std::slice::from_ref(&full)));
+  server.send_frame(0, fixtures::frame(&[full]));
+  server.send_frame(0, fixtures::frame(&[changed.full()]));
+  // Marks the end of the sequence
+  server.send_frame(0, fixtures::frame(&[fixtures::bse_equity().full()]));
+
+  let mut received: Vec<TickMessage> = Vec::new();
+  tokio::time::timeout(WAIT, async {
+    while !received
+      .iter()
+      .any(|t| t.instrument_token == fixtures::BSE_EQUITY)
+    {
+      if let TickerMessage::Ticks(ticks) = receiver.recv().await.unwrap() {
+        received.extend(ticks);
+      }
+    }
+  })
+  .await
+  .expect("marker tick not received");
+
+  let nse: Vec<_> = received
+    .iter()
+    .filter(|t| t.instrument_token == fixtures::NSE_EQUITY)
+    .collect();
+  assert_eq!(nse.len(), 2, "{:?}", nse);
+  assert_ne!(nse[0].content, nse[1].content);
+}
@@ -0,0 +1,45 @@
+//! Subscription commands that cannot be sent leave the manager unchanged
+
+mod common;
+
+use std::time::Duration;
+
+use common::MockServer;
+use kiteticker_async_manager::{KiteTickerManagerBuilder, Mode};
+
+#[tokio::test]
+async fn failed_sends_roll_back_subscription_state() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[408065], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  // Once the socket is gone the writer stops and further commands fail
+  server.close_client(0, 1000, "session closed");
+  let mut failed = None;
+  for token in 884737..884757 {
+    if manager
+      .subscribe_symbols(&[token], Some(Mode::LTP))
+      .await
+      .is_err()
+    {
+      failed = Some(token);
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(25)).await;
+  }
+  let failed = failed.expect("commands kept succeeding on a closed socket");
+  assert!(!manager.get_subscriptions().contains_key(&failed));
+
+  assert!(manager.change_mode(&[408065], Mode::Full).await.is_err());
+  assert_eq!(manager.get_subscriptions()[&408065], Mode::LTP);
+
+  assert!(manager.unsubscribe_symbols(&[408065]).await.is_err());
+  assert!(manager.get_subscriptions().contains_key(&408065));
+}
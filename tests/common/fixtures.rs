@@ -0,0 +1,264 @@
+//! Ticker packets built byte by byte from Kite's published binary layout
+//! (the layout pykiteconnect's `_parse_binary` decodes), for regression
+//! tests of the parser.
+//!
+//! Values are stored raw, as they travel on the wire: prices are integers
+//! in the segment's smallest unit (paise for NSE, NFO, BSE and MCX), times
+//! are Unix seconds. The segment is the low byte of the token. A parsing
+//! bug report with a captured frame can be turned into one more fixture
+//! here.
+
+/// INFY on NSE (segment 1)
+pub const NSE_EQUITY: u32 = 408_065;
+/// A NIFTY future on NFO (segment 2)
+pub const NFO_FUTURE: u32 = 12_345_602;
+/// A BSE equity (segment 4); BSE reports zero orders at its depth levels
+pub const BSE_EQUITY: u32 = 128_000_004;
+/// A crude oil future on MCX (segment 7)
+pub const MCX_FUTURE: u32 = 109_119_239;
+/// NIFTY 50 index (segment 9)
+pub const NIFTY_50: u32 = 256_265;
+
+/// Single-byte keepalive Kite sends on idle connections
+pub const HEARTBEAT: [u8; 1] = [0];
+
+/// One depth level: quantity, price, orders
+pub type Level = (u32, i32, u16);
+
+/// Raw fields of a tradable instrument, in Full packet order
+#[derive(Debug, Clone)]
+pub struct Tradable {
+  pub token: u32,
+  pub last_price: i32,
+  pub last_qty: u32,
+  pub avg_price: i32,
+  pub volume: u32,
+  pub buy_qty: u32,
+  pub sell_qty: u32,
+  /// Open, high, low, close
+  pub ohlc: [i32; 4],
+  pub last_trade_time: u32,
+  pub oi: u32,
+  pub oi_day_high: u32,
+  pub oi_day_low: u32,
+  pub exchange_time: u32,
+  /// Best bid first
+  pub buy: [Level; 5],
+  /// Best ask first
+  pub sell: [Level; 5],
+}
+
+impl Tradable {
+  /// 8-byte LTP packet
+  pub fn ltp(&self) -> Vec<u8> {
+    self.full()[..8].to_vec()
+  }
+
+  /// 44-byte Quote packet
+  pub fn quote(&self) -> Vec<u8> {
+    self.full()[..44].to_vec()
+  }
+
+  /// 184-byte Full packet
+  pub fn full(&self) -> Vec<u8> {
+    let mut p = Vec::with_capacity(184);
+    p.extend(self.token.to_be_bytes());
+    p.extend(self.last_price.to_be_bytes());
+    p.extend(self.last_qty.to_be_bytes());
+    p.extend(self.avg_price.to_be_bytes());
+    p.extend(self.volume.to_be_bytes());
+    p.extend(self.buy_qty.to_be_bytes());
+    p.extend(self.sell_qty.to_be_bytes());
+    for price in self.ohlc {
+      p.extend(price.to_be_bytes());
+    }
+    p.extend(self.last_trade_time.to_be_bytes());
+    p.extend(self.oi.to_be_bytes());
+    p.extend(self.oi_day_high.to_be_bytes());
+    p.extend(self.oi_day_low.to_be_bytes());
+    p.extend(self.exchange_time.to_be_bytes());
+    for (qty, price, orders) in self.buy.iter().chain(&self.sell) {
+      p.extend(qty.to_be_bytes());
+      p.extend(price.to_be_bytes());
+      p.extend(orders.to_be_bytes());
+      p.extend([0, 0]);
+    }
+    assert_eq!(p.len(), 184);
+    p
+  }
+}
+
+/// Raw fields of an index, in Full packet order
+#[derive(Debug, Clone)]
+pub struct Index {
+  pub token: u32,
+  pub last_price: i32,
+  pub high: i32,
+  pub low: i32,
+  pub open: i32,
+  pub close: i32,
+  pub change: i32,
+  pub exchange_time: u32,
+}
+
+impl Index {
+  /// 8-byte LTP packet
+  pub fn ltp(&self) -> Vec<u8> {
+    self.full()[..8].to_vec()
+  }
+
+  /// 28-byte Quote packet
+  pub fn quote(&self) -> Vec<u8> {
+    self.full()[..28].to_vec()
+  }
+
+  /// 32-byte Full packet; OHLC travels as high, low, open, close
+  pub fn full(&self) -> Vec<u8> {
+    let mut p = Vec::with_capacity(32);
+    p.extend(self.token.to_be_bytes());
+    p.extend(self.last_price.to_be_bytes());
+    for price in [self.high, self.low, self.open, self.close, self.change] {
+      p.extend(price.to_be_bytes());
+    }
+    p.extend(self.exchange_time.to_be_bytes());
+    assert_eq!(p.len(), 32);
+    p
+  }
+}
+
+/// NSE equity with a full book: buy 1520.30 down to 1520.10, sell 1520.45
+/// up to 1520.65
+pub fn nse_equity() -> Tradable {
+  Tradable {
+    token: NSE_EQUITY,
+    last_price: 152_035,
+    last_qty: 25,
+    avg_price: 151_987,
+    volume: 1_234_567,
+    buy_qty: 456_789,
+    sell_qty: 398_765,
+    ohlc: [151_000, 153_000, 150_500, 150_800],
+    // 2023-11-14 22:13:20 UTC
+    last_trade_time: 1_700_000_000,
+    // Cash segments leave the OI slots empty
+    oi: 0,
+    oi_day_high: 0,
+    oi_day_low: 0,
+    exchange_time: 1_700_000_001,
+    buy: [
+      (1200, 152_030, 7),
+      (300, 152_025, 2),
+      (450, 152_020, 5),
+      (800, 152_015, 3),
+      (1000, 152_010, 9),
+    ],
+    sell: [
+      (950, 152_045, 4),
+      (500, 152_050, 3),
+      (700, 152_055, 6),
+      (350, 152_060, 1),
+      (1500, 152_065, 11),
+    ],
+  }
+}
+
+/// NFO future with open interest
+pub fn nfo_future() -> Tradable {
+  Tradable {
+    token: NFO_FUTURE,
+    last_price: 1_985_050,
+    last_qty: 50,
+    avg_price: 1_984_210,
+    volume: 8_765_400,
+    buy_qty: 1_200_000,
+    sell_qty: 1_350_000,
+    ohlc: [1_979_000, 1_992_500, 1_975_000, 1_977_010],
+    last_trade_time: 1_700_000_000,
+    oi: 12_500_000,
+    oi_day_high: 12_800_000,
+    oi_day_low: 12_100_000,
+    exchange_time: 1_700_000_001,
+    buy: [
+      (500, 1_985_000, 3),
+      (750, 1_984_950, 4),
+      (50, 1_984_900, 1),
+      (1000, 1_984_850, 6),
+      (250, 1_984_800, 2),
+    ],
+    sell: [
+      (300, 1_985_100, 2),
+      (450, 1_985_150, 3),
+      (900, 1_985_200, 5),
+      (150, 1_985_250, 1),
+      (600, 1_985_300, 4),
+    ],
+  }
+}
+
+/// BSE equity: BSE sends zero order counts at every depth level
+pub fn bse_equity() -> Tradable {
+  Tradable {
+    token: BSE_EQUITY,
+    buy: nse_equity().buy.map(|(qty, price, _)| (qty, price, 0)),
+    sell: nse_equity().sell.map(|(qty, price, _)| (qty, price, 0)),
+    ..nse_equity()
+  }
+}
+
+/// MCX future with open interest and a thin book
+pub fn mcx_future() -> Tradable {
+  Tradable {
+    token: MCX_FUTURE,
+    last_price: 641_500,
+    last_qty: 1,
+    avg_price: 640_875,
+    volume: 23_456,
+    buy_qty: 1_800,
+    sell_qty: 2_100,
+    ohlc: [638_000, 643_200, 637_100, 639_900],
+    last_trade_time: 1_700_000_000,
+    oi: 15_432,
+    oi_day_high: 15_900,
+    oi_day_low: 15_010,
+    exchange_time: 1_700_000_001,
+    buy: [
+      (5, 641_400, 2),
+      (3, 641_300, 1),
+      (0, 0, 0),
+      (0, 0, 0),
+      (0, 0, 0),
+    ],
+    sell: [(4, 641_600, 3), (0, 0, 0), (0, 0, 0), (0, 0, 0), (0, 0, 0)],
+  }
+}
+
+/// NIFTY 50 with distinct OHLC values, so a swapped field order shows
+pub fn nifty_50() -> Index {
+  Index {
+    token: NIFTY_50,
+    last_price: 1_985_030,
+    high: 1_990_000,
+    low: 1_975_000,
+    open: 1_980_000,
+    close: 1_970_000,
+    change: 15_030,
+    exchange_time: 1_700_000_001,
+  }
+}
+
+/// Binary frame carrying `packets`: a packet count, then each packet with
+/// its length prefix
+pub fn frame(packets: &[Vec<u8>]) -> Vec<u8> {
+  let mut frame = (packets.len() as u16).to_be_bytes().to_vec();
+  for packet in packets {
+    frame.extend((packet.len() as u16).to_be_bytes());
+    frame.extend(packet);
+  }
+  frame
+}
+
+/// `frame` with its last `bytes` bytes cut off, as a torn read delivers it
+pub fn truncated(mut frame: Vec<u8>, bytes: usize) -> Vec<u8> {
+  frame.truncate(frame.len() - bytes);
+  frame
+}
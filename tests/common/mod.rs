@@ -0,0 +1,382 @@
+//! Shared harness for the integration tests: a local stand-in for Kite's
+//! ticker endpoint plus spec-built binary frames (see [`fixtures`]).
+//!
+//! Every test binary compiles this module on its own and uses a different
+//! subset of it.
+#![allow(dead_code)]
+
+pub mod fixtures;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use kiteticker_async_manager::{Environment, KiteManagerConfig};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::handshake::server::{
+  ErrorResponse, Request, Response,
+};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+/// How long the `wait_*` helpers wait before failing the test
+pub const WAIT: Duration = Duration::from_secs(5);
+
+/// A text command a client sent, e.g. `{"a":"subscribe","v":[408065]}`
+#[derive(Debug, Clone)]
+pub struct Command {
+  /// Index of the client connection, in accept order
+  pub client: usize,
+  /// `api_key` query parameter of that connection
+  pub api_key: String,
+  pub json: Value,
+}
+
+impl Command {
+  /// The `a` field: `subscribe`, `unsubscribe` or `mode`
+  pub fn action(&self) -> &str {
+    self.json["a"].as_str().unwrap_or_default()
+  }
+
+  /// Tokens the command applies to, for every action
+  pub fn tokens(&self) -> Vec<u32> {
+    let tokens = match self.action() {
+      "mode" => &self.json["v"][1],
+      _ => &self.json["v"],
+    };
+    tokens
+      .as_array()
+      .map(|v| {
+        v.iter()
+          .filter_map(|t| t.as_u64())
+          .map(|t| t as u32)
+          .collect()
+      })
+      .unwrap_or_default()
+  }
+
+  /// Mode string of a `mode` command (`ltp`, `quote`, `full`)
+  pub fn mode(&self) -> Option<&str> {
+    self.json["v"][0].as_str()
+  }
+}
+
+#[derive(Debug)]
+struct Client {
+  api_key: String,
+  /// `None` once the connection is gone
+  tx: Option<mpsc::UnboundedSender<Message>>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+  clients: Mutex<Vec<Client>>,
+  commands: Mutex<Vec<Command>>,
+  changed: Notify,
+  /// Answer new handshakes with this HTTP status instead of upgrading
+  reject_status: Mutex<Option<u16>>,
+  /// Same, for handshakes of one `api_key` only
+  reject_keys: Mutex<HashMap<String, u16>>,
+  /// Refuse this many more handshakes with the status, then accept again
+  reject_next: Mutex<(usize, u16)>,
+  rejected: Mutex<usize>,
+  /// Wait this long before answering each handshake
+  handshake_delay: Mutex<Duration>,
+}
+
+impl Shared {
+  fn touch(&self) {
+    self.changed.notify_waiters();
+  }
+}
+
+/// Ticker-compatible WebSocket server on `127.0.0.1`
+///
+/// Accepts any credentials, records every text command and lets the test
+/// push binary frames or close frames to individual clients.
+#[derive(Debug)]
+pub struct MockServer {
+  addr: SocketAddr,
+  shared: Arc<Shared>,
+  accept_task: JoinHandle<()>,
+}
+
+impl MockServer {
+  pub async fn start() -> Self {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let shared = Arc::new(Shared::default());
+    let accept_shared = Arc::clone(&shared);
+    let accept_task = tokio::spawn(async move {
+      while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(serve(stream, Arc::clone(&accept_shared)));
+      }
+    });
+    Self {
+      addr,
+      shared,
+      accept_task,
+    }
+  }
+
+  pub fn url(&self) -> String {
+    format!("ws://{}", self.addr)
+  }
+
+  pub fn environment(&self) -> Environment {
+    Environment::Custom(self.url())
+  }
+
+  /// Manager config pointed at this server, with the periodic health check
+  /// off and short timeouts so tests stay fast
+  pub fn manager_config(&self, connections: usize) -> KiteManagerConfig {
+    let mut config = KiteManagerConfig::default();
+    config.max_connections = connections;
+    config.environment = self.environment();
+    config.health_check_interval = Duration::ZERO;
+    config.connection_timeout = Duration::from_secs(2);
+    config.reconnect_delay = Duration::from_millis(50);
+    config
+  }
+
+  /// Refuse upcoming handshakes with `status` (e.g. 429), or accept them
+  /// again with `None`
+  pub fn reject_with(&self, status: Option<u16>) {
+    *self.shared.reject_status.lock().unwrap() = status;
+  }
+
+  /// Refuse upcoming handshakes of `api_key` with `status`, or accept them
+  /// again with `None`
+  pub fn reject_key(&self, api_key: &str, status: Option<u16>) {
+    let mut keys = self.shared.reject_keys.lock().unwrap();
+    match status {
+      Some(status) => keys.insert(api_key.to_string(), status),
+      None => keys.remove(api_key),
+    };
+  }
+
+  /// Refuse only the next `count` handshakes with `status`, like one
+  /// connection of several failing at startup
+  pub fn reject_next(&self, count: usize, status: u16) {
+    *self.shared.reject_next.lock().unwrap() = (count, status);
+  }
+
+  /// Answer each upcoming handshake only after `delay`, like a slow or
+  /// distant endpoint
+  pub fn delay_handshakes(&self, delay: Duration) {
+    *self.shared.handshake_delay.lock().unwrap() = delay;
+  }
+
+  /// Handshakes refused by [`reject_with`](Self::reject_with),
+  /// [`reject_key`](Self::reject_key) or [`reject_next`](Self::reject_next)
+  /// so far
+  pub fn rejected(&self) -> usize {
+    *self.shared.rejected.lock().unwrap()
+  }
+
+  /// Connections accepted so far, including closed ones
+  pub fn client_count(&self) -> usize {
+    self.shared.clients.lock().unwrap().len()
+  }
+
+  /// `api_key` of every accepted connection, in accept order
+  pub fn client_keys(&self) -> Vec<String> {
+    let clients = self.shared.clients.lock().unwrap();
+    clients.iter().map(|c| c.api_key.clone()).collect()
+  }
+
+  /// Connections that are still open
+  pub fn open_clients(&self) -> Vec<usize> {
+    let clients = self.shared.clients.lock().unwrap();
+    (0..clients.len())
+      .filter(|&i| clients[i].tx.as_ref().is_some_and(|tx| !tx.is_closed()))
+      .collect()
+  }
+
+  /// Wait until at least `n` connections have been accepted
+  pub async fn wait_for_clients(&self, n: usize) {
+    self.wait_until(|| self.client_count() >= n).await;
+  }
+
+  /// Send a binary frame to client `client`
+  pub fn send_frame(&self, client: usize, frame: Vec<u8>) {
+    self.send(client, Message::Binary(frame.into()));
+  }
+
+  /// Send a binary frame to every open client
+  pub fn broadcast_frame(&self, frame: Vec<u8>) {
+    for client in self.open_clients() {
+      self.send_frame(client, frame.clone());
+    }
+  }
+
+  /// Close client `client` with `code`, as Kite does when it drops a session
+  pub fn close_client(&self, client: usize, code: u16, reason: &str) {
+    self.send(
+      client,
+      Message::Close(Some(CloseFrame {
+        code: CloseCode::from(code),
+        reason: reason.to_string().into(),
+      })),
+    );
+  }
+
+  fn send(&self, client: usize, message: Message) {
+    let clients = self.shared.clients.lock().unwrap();
+    let tx = clients[client].tx.as_ref().expect("client is closed");
+    let _ = tx.send(message);
+  }
+
+  /// Every command received so far, in arrival order
+  pub fn commands(&self) -> Vec<Command> {
+    self.shared.commands.lock().unwrap().clone()
+  }
+
+  /// Wait for a command matching `matches` and return the first one
+  pub async fn wait_for_command(
+    &self,
+    matches: impl Fn(&Command) -> bool,
+  ) -> Command {
+    self
+      .wait_until(|| self.commands().iter().any(&matches))
+      .await;
+    self.commands().into_iter().find(matches).unwrap()
+  }
+
+  /// Tokens client `client` is subscribed to, replaying its commands
+  pub fn subscribed(&self, client: usize) -> Vec<u32> {
+    let mut tokens: Vec<u32> = Vec::new();
+    for command in self.commands().iter().filter(|c| c.client == client) {
+      match command.action() {
+        "subscribe" => tokens.extend(command.tokens()),
+        "unsubscribe" => {
+          let removed = command.tokens();
+          tokens.retain(|t| !removed.contains(t));
+        }
+        _ => {}
+      }
+    }
+    tokens.sort_unstable();
+    tokens.dedup();
+    tokens
+  }
+
+  /// Wait until `condition` holds, re-checking whenever the server sees a
+  /// connection or command; panics after [`WAIT`]
+  pub async fn wait_until(&self, condition: impl Fn() -> bool) {
+    let deadline = tokio::time::Instant::now() + WAIT;
+    loop {
+      let changed = self.shared.changed.notified();
+      if condition() {
+        return;
+      }
+      let poll = tokio::time::sleep(Duration::from_millis(20));
+      tokio::select! {
+        _ = changed => {}
+        _ = poll => {}
+        _ = tokio::time::sleep_until(deadline) => {
+          panic!("mock server condition not met within {:?}", WAIT)
+        }
+      }
+    }
+  }
+}
+
+impl Drop for MockServer {
+  fn drop(&mut self) {
+    self.accept_task.abort();
+  }
+}
+
+// The handshake callback's error type is fixed by tungstenite
+#[allow(clippy::result_large_err)]
+async fn serve(stream: tokio::net::TcpStream, shared: Arc<Shared>) {
+  let delay = *shared.handshake_delay.lock().unwrap();
+  tokio::time::sleep(delay).await;
+  let mut api_key = String::new();
+  let reject = {
+    let mut next = shared.reject_next.lock().unwrap();
+    match *next {
+      (0, _) => *shared.reject_status.lock().unwrap(),
+      (ref mut count, status) => {
+        *count -= 1;
+        Some(status)
+      }
+    }
+  };
+  let reject_keys = shared.reject_keys.lock().unwrap().clone();
+  let handshake = tokio_tungstenite::accept_hdr_async(
+    stream,
+    |request: &Request, response: Response| {
+      api_key = request
+        .uri()
+        .query()
+        .and_then(|q| {
+          url::form_urlencoded::parse(q.as_bytes())
+            .find(|(k, _)| k == "api_key")
+            .map(|(_, v)| v.into_owned())
+        })
+        .unwrap_or_default();
+      match reject.or_else(|| reject_keys.get(&api_key).copied()) {
+        Some(status) => {
+          let mut error = ErrorResponse::new(Some("rejected".to_string()));
+          *error.status_mut() = status.try_into().unwrap();
+          Err(error)
+        }
+        None => Ok(response),
+      }
+    },
+  )
+  .await;
+  let Ok(ws) = handshake else {
+    *shared.rejected.lock().unwrap() += 1;
+    shared.touch();
+    return;
+  };
+
+  let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+  let client = {
+    let mut clients = shared.clients.lock().unwrap();
+    clients.push(Client {
+      api_key: api_key.clone(),
+      tx: Some(tx),
+    });
+    clients.len() - 1
+  };
+  shared.touch();
+
+  let (mut write, mut read) = ws.split();
+  loop {
+    tokio::select! {
+      outgoing = rx.recv() => {
+        let Some(message) = outgoing else { break };
+        let is_close = matches!(message, Message::Close(_));
+        if write.send(message).await.is_err() || is_close {
+          break;
+        }
+      }
+      incoming = read.next() => match incoming {
+        Some(Ok(Message::Text(text))) => {
+          if let Ok(json) = serde_json::from_str(&text) {
+            shared.commands.lock().unwrap().push(Command {
+              client,
+              api_key: api_key.clone(),
+              json,
+            });
+            shared.touch();
+          }
+        }
+        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+        Some(Ok(_)) => {}
+      }
+    }
+  }
+  shared.clients.lock().unwrap()[client].tx = None;
+  shared.touch();
+}
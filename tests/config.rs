@@ -0,0 +1,122 @@
+//! Manager configuration: validation in `try_build`, clamping in `build`
+
+mod common;
+
+use std::time::Duration;
+
+use common::MockServer;
+use kiteticker_async_manager::{
+  Exchange, KiteManagerConfig, KiteTickerManagerBuilder, MarketSession,
+};
+
+fn invalid_configs() -> Vec<(&'static str, KiteManagerConfig)> {
+  let base = KiteManagerConfig::default;
+  let mut cases = Vec::new();
+  let mut c = base();
+  c.max_connections = 0;
+  cases.push(("max_connections", c));
+  let mut c = base();
+  c.max_connections = 4;
+  cases.push(("max_connections", c));
+  let mut c = base();
+  c.max_symbols_per_connection = 3001;
+  cases.push(("max_symbols_per_connection", c));
+  let mut c = base();
+  c.per_connection_symbol_caps = vec![100; 4];
+  cases.push(("per_connection_symbol_caps", c));
+  let mut c = base();
+  c.per_connection_symbol_caps = vec![5000];
+  cases.push(("per_connection_symbol_caps", c));
+  let mut c = base();
+  c.reader_parser_channel_cap = 0;
+  cases.push(("buffer sizes", c));
+  let mut c = base();
+  c.full_buffer_size = Some(0);
+  cases.push(("buffer sizes", c));
+  let mut c = base();
+  c.parser_buffer_size = 100;
+  cases.push(("parser_buffer_size", c));
+  let mut c = base();
+  c.full_buffer_size = Some(20_000);
+  c.max_total_buffered_messages = Some(15_000);
+  cases.push(("max_total_buffered_messages", c));
+  let mut c = base();
+  c.auto_prune_inactive = Some(Duration::ZERO);
+  cases.push(("auto_prune_inactive", c));
+  let mut c = base();
+  let noon = chrono::NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+  c.market_sessions
+    .insert(Exchange::MCX, MarketSession::new(noon, noon));
+  cases.push(("market_sessions", c));
+  let mut c = base();
+  c.connection_timeout = Duration::ZERO;
+  cases.push(("connection_timeout", c));
+  let mut c = base();
+  c.heartbeat_liveness_threshold = Duration::ZERO;
+  cases.push(("heartbeat_liveness_threshold", c));
+  let mut c = base();
+  c.receive_timeout = Duration::ZERO;
+  cases.push(("receive_timeout", c));
+  cases
+}
+
+#[test]
+fn try_build_names_the_offending_setting() {
+  for (setting, config) in invalid_configs() {
+    let err = KiteTickerManagerBuilder::new("key", "token")
+      .config(config)
+      .try_build()
+      .err()
+      .unwrap_or_else(|| panic!("{setting}: accepted"));
+    assert!(err.to_string().contains(setting), "{setting}: {err}");
+  }
+}
+
+#[test]
+fn clamped_configs_validate() {
+  for (setting, config) in invalid_configs() {
+    let clamped = config.clamped();
+    assert_eq!(clamped.validate(), Ok(()), "{setting}");
+  }
+}
+
+#[test]
+fn clamping_keeps_values_in_range_and_valid_ones_as_is() {
+  let mut config = KiteManagerConfig::default();
+  config.max_connections = 9;
+  config.max_symbols_per_connection = 0;
+  config.per_connection_symbol_caps = vec![100, 5000, 200, 300];
+  config.parser_buffer_size = 100;
+  config.ltp_buffer_size = Some(0);
+  config.receive_timeout = Duration::ZERO;
+  config.reconnect_delay = Duration::from_millis(7);
+  let config = config.clamped();
+
+  assert_eq!(config.max_connections, 3);
+  assert_eq!(config.max_symbols_per_connection, 1);
+  assert_eq!(config.per_connection_symbol_caps, [100, 3000, 200]);
+  assert_eq!(config.parser_buffer_size, config.connection_buffer_size);
+  assert_eq!(config.ltp_buffer_size, None);
+  assert_eq!(
+    config.receive_timeout,
+    KiteManagerConfig::default().receive_timeout
+  );
+  assert_eq!(config.reconnect_delay, Duration::from_millis(7));
+}
+
+#[tokio::test]
+async fn build_clamps_the_connection_count() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(0))
+    .build();
+  manager.start().await.unwrap();
+  assert_eq!(server.client_count(), 1);
+
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(8))
+    .build();
+  manager.start().await.unwrap();
+  assert_eq!(server.client_count(), 3);
+}
@@ -0,0 +1,123 @@
+//! `subscribe_symbols_confirmed` across connections
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{
+  ChannelId, KiteError, KiteTickerManager, KiteTickerManagerBuilder, Mode,
+};
+
+async fn started(
+  server: &MockServer,
+  connections: usize,
+  grace: Duration,
+) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(connections))
+    .mode_hold_back(Duration::from_secs(60))
+    .subscribe_confirm_grace(grace)
+    .build();
+  manager.start().await.unwrap();
+  manager
+}
+
+/// Wait until `n` clients have been told a mode, then send `frame` to all
+async fn after_modes(server: &MockServer, n: usize, frame: Vec<u8>) {
+  server
+    .wait_until(|| {
+      server
+        .commands()
+        .iter()
+        .filter(|c| c.action() == "mode")
+        .count()
+        >= n
+    })
+    .await;
+  server.broadcast_frame(frame);
+}
+
+#[tokio::test]
+async fn silent_token_is_reported_by_connection() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 2, Duration::from_secs(60)).await;
+  let tokens = [
+    fixtures::NSE_EQUITY,
+    fixtures::NFO_FUTURE,
+    fixtures::BSE_EQUITY,
+  ];
+  let frame = fixtures::frame(&[
+    fixtures::nse_equity().ltp(),
+    fixtures::nfo_future().ltp(),
+  ]);
+
+  let (result, ()) = tokio::join!(
+    manager.subscribe_symbols_confirmed(
+      &tokens,
+      Some(Mode::LTP),
+      Duration::from_millis(500),
+    ),
+    after_modes(&server, 2, frame),
+  );
+  let channel = manager
+    .get_symbol_distribution()
+    .into_iter()
+    .find(|(_, tokens)| tokens.contains(&fixtures::BSE_EQUITY))
+    .unwrap()
+    .0;
+  let unconfirmed = HashMap::from([(channel, vec![fixtures::BSE_EQUITY])]);
+  assert_eq!(result, Err(KiteError::Timeout { unconfirmed }));
+  // The subscriptions are kept
+  assert_eq!(manager.get_subscriptions().len(), 3);
+}
+
+#[tokio::test]
+async fn held_back_packets_confirm_the_subscription() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 1, Duration::from_secs(60)).await;
+
+  // Still in Quote after asking for Full: held back, but proof of life
+  let frame = fixtures::frame(&[fixtures::nse_equity().quote()]);
+  let (result, ()) = tokio::join!(
+    manager.subscribe_symbols_confirmed(
+      &[fixtures::NSE_EQUITY],
+      Some(Mode::Full),
+      Duration::from_secs(5),
+    ),
+    after_modes(&server, 1, frame),
+  );
+  assert_eq!(result, Ok(()));
+  let client = manager.connection_client(ChannelId::Connection1).unwrap();
+  assert_eq!(client.held_back_count(), 1);
+}
+
+#[tokio::test]
+async fn heartbeat_confirms_a_quiet_token_after_the_grace() {
+  let server = MockServer::start().await;
+  let grace = Duration::from_millis(300);
+  let mut manager = started(&server, 1, grace).await;
+
+  let begun = Instant::now();
+  let (result, ()) = tokio::join!(
+    manager.subscribe_symbols_confirmed(
+      &[fixtures::NSE_EQUITY],
+      Some(Mode::LTP),
+      Duration::from_secs(5),
+    ),
+    after_modes(&server, 1, fixtures::HEARTBEAT.to_vec()),
+  );
+  assert_eq!(result, Ok(()));
+  assert!(begun.elapsed() >= grace);
+
+  // Without a frame since the subscribe, the grace proves nothing
+  let result = manager
+    .subscribe_symbols_confirmed(
+      &[fixtures::BSE_EQUITY],
+      Some(Mode::LTP),
+      Duration::from_millis(600),
+    )
+    .await;
+  assert!(matches!(result, Err(KiteError::Timeout { .. })));
+}
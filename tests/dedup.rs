@@ -0,0 +1,45 @@
+//! Deduplicating ticks from redundant connections
+
+mod common;
+
+use std::collections::HashSet;
+
+use common::fixtures;
+use kiteticker_async_manager::{Tick, TickMessage};
+
+fn message(raw: &fixtures::Tradable) -> TickMessage {
+  let tick = Tick::try_from(&raw.full()[..]).unwrap();
+  TickMessage::new(raw.token, tick)
+}
+
+#[test]
+fn same_token_time_and_price_share_a_dedup_key() {
+  let first = message(&fixtures::nse_equity());
+  // Same trade seen on another connection, after more volume was reported
+  let mut raw = fixtures::nse_equity();
+  raw.volume += 500;
+  let later = message(&raw);
+
+  assert_eq!(first.dedup_key(), later.dedup_key());
+  let mut seen = HashSet::new();
+  assert!(seen.insert(first.dedup_key()));
+  assert!(!seen.insert(later.dedup_key()));
+  // Equality stays structural
+  assert_ne!(first, later);
+  assert_eq!(first, message(&fixtures::nse_equity()));
+}
+
+#[test]
+fn price_time_or_token_changes_the_dedup_key() {
+  let base = message(&fixtures::nse_equity()).dedup_key();
+
+  let mut raw = fixtures::nse_equity();
+  raw.last_price += 5;
+  assert_ne!(message(&raw).dedup_key(), base);
+
+  let mut raw = fixtures::nse_equity();
+  raw.exchange_time += 1;
+  assert_ne!(message(&raw).dedup_key(), base);
+
+  assert_ne!(message(&fixtures::bse_equity()).dedup_key(), base);
+}
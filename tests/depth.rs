@@ -0,0 +1,194 @@
+//! Market depth sanity checks on spec-built Full packets
+
+mod common;
+
+use common::fixtures::{self, Tradable};
+use common::{MockServer, WAIT};
+use kiteticker_async_manager::{
+  as_tick_raw, Depth, KiteTickerAsync, KiteTickerConfig, Mode, Tick,
+  TickerMessage,
+};
+
+fn depth(raw: &Tradable) -> Depth {
+  let tick = Tick::try_from(&raw.full()[..]).expect("fixture must decode");
+  tick.depth.expect("Full packet carries depth")
+}
+
+/// (price, quantity, orders) of each level
+fn levels(depth: &Depth) -> [Vec<(f64, u32, u16)>; 2] {
+  [&depth.buy, &depth.sell]
+    .map(|side| side.iter().map(|l| (l.price, l.qty, l.orders)).collect())
+}
+
+#[test]
+fn full_packet_levels_match_pykiteconnect() {
+  // Bytes 64..184: five bids, then five asks, each quantity (u32), price
+  // (i32 paise), orders (u16) and two bytes of padding. pykiteconnect's
+  // `_parse_binary` decodes the NSE equity fixture as
+  //   depth["buy"] = [
+  //     {"quantity": 1200, "price": 1520.30, "orders": 7},
+  //     {"quantity": 300, "price": 1520.25, "orders": 2},
+  //     {"quantity": 450, "price": 1520.20, "orders": 5},
+  //     {"quantity": 800, "price": 1520.15, "orders": 3},
+  //     {"quantity": 1000, "price": 1520.10, "orders": 9},
+  //   ]
+  //   depth["sell"] = [
+  //     {"quantity": 950, "price": 1520.45, "orders": 4},
+  //     {"quantity": 500, "price": 1520.50, "orders": 3},
+  //     {"quantity": 700, "price": 1520.55, "orders": 6},
+  //     {"quantity": 350, "price": 1520.60, "orders": 1},
+  //     {"quantity": 1500, "price": 1520.65, "orders": 11},
+  //   ]
+  let packet = fixtures::nse_equity().full();
+  let tick = Tick::try_from(&packet[..]).unwrap();
+  let depth = tick.depth.as_ref().unwrap();
+  assert_eq!(
+    levels(depth),
+    [
+      vec![
+        (1520.30, 1200, 7),
+        (1520.25, 300, 2),
+        (1520.20, 450, 5),
+        (1520.15, 800, 3),
+        (1520.10, 1000, 9),
+      ],
+      vec![
+        (1520.45, 950, 4),
+        (1520.50, 500, 3),
+        (1520.55, 700, 6),
+        (1520.60, 350, 1),
+        (1520.65, 1500, 11),
+      ],
+    ]
+  );
+}
+
+#[test]
+fn zero_copy_view_decodes_full_packets_identically() {
+  for raw in [
+    fixtures::nse_equity(),
+    fixtures::nfo_future(),
+    fixtures::mcx_future(),
+  ] {
+    let packet = raw.full();
+    let view = as_tick_raw(&packet).unwrap();
+    assert_eq!(
+      Tick::from_raw(&view),
+      Tick::try_from(&packet[..]).unwrap(),
+      "token {}",
+      raw.token
+    );
+  }
+}
+
+#[test]
+fn exchange_books_are_well_formed() {
+  for raw in [
+    fixtures::nse_equity(),
+    fixtures::nfo_future(),
+    fixtures::mcx_future(),
+  ] {
+    assert!(depth(&raw).is_well_formed(), "token {}", raw.token);
+  }
+}
+
+#[test]
+fn bse_levels_without_order_counts_are_well_formed() {
+  let depth = depth(&fixtures::bse_equity());
+  assert!(depth.buy.iter().chain(&depth.sell).all(|l| l.orders == 0));
+  assert!(depth.is_well_formed());
+}
+
+#[test]
+fn corrupt_levels_are_not_well_formed() {
+  // Negative price, as a flipped sign bit would decode it
+  let mut raw = fixtures::nse_equity();
+  raw.buy[1].1 = -152_025;
+  assert!(!depth(&raw).is_well_formed());
+
+  // Quantity without price
+  let mut raw = fixtures::nse_equity();
+  raw.sell[2].1 = 0;
+  assert!(!depth(&raw).is_well_formed());
+}
+
+#[test]
+fn unusual_books_are_well_formed() {
+  // Crossed book, as in a pre-open auction: best bid above best ask
+  let mut raw = fixtures::nse_equity();
+  raw.buy[0].1 = 152_100;
+  assert!(depth(&raw).is_well_formed());
+
+  // Levels out of order
+  let mut raw = fixtures::nse_equity();
+  raw.sell.swap(1, 3);
+  assert!(depth(&raw).is_well_formed());
+
+  // Price without quantity or orders
+  let mut raw = fixtures::nse_equity();
+  raw.sell[4] = (0, 152_065, 0);
+  assert!(depth(&raw).is_well_formed());
+}
+
+#[test]
+fn best_effort_keeps_the_header_of_a_corrupt_book() {
+  let mut raw = fixtures::nse_equity();
+  raw.buy[3].1 = -1;
+  let tick = Tick::parse_best_effort(&raw.full()).unwrap();
+  assert!(tick.partial);
+  assert!(tick.depth.is_none());
+  assert_eq!(tick.mode, Mode::Full);
+  assert_eq!(tick.last_price, Some(1520.35));
+  assert_eq!(tick.volume_traded, Some(1_234_567));
+  assert_eq!(tick.total_buy_qty, Some(456_789));
+
+  // A crossed book is data, not damage: depth is kept
+  let mut raw = fixtures::nse_equity();
+  raw.buy[0].1 = 152_100;
+  let tick = Tick::parse_best_effort(&raw.full()).unwrap();
+  assert!(!tick.partial);
+  assert_eq!(tick.depth.unwrap().buy[0].price, 1521.0);
+}
+
+#[tokio::test]
+async fn best_effort_ticker_delivers_corrupt_books_as_partial() {
+  let server = MockServer::start().await;
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  config.best_effort_parsing = true;
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::Full))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  let mut corrupt = fixtures::nse_equity();
+  corrupt.sell[0].1 = 0;
+  let crossed = {
+    let mut raw = fixtures::nse_equity();
+    raw.buy[0].1 = 152_100;
+    raw
+  };
+  server.send_frame(0, fixtures::frame(&[corrupt.full(), crossed.full()]));
+
+  let ticks = tokio::time::timeout(WAIT, async {
+    loop {
+      if let Some(TickerMessage::Ticks(ticks)) =
+        subscriber.next_message().await.unwrap()
+      {
+        return ticks;
+      }
+    }
+  })
+  .await
+  .expect("no ticks within the wait");
+  let partial: Vec<_> = ticks
+    .iter()
+    .map(|t| (t.content.partial, t.content.depth.is_some()))
+    .collect();
+  assert_eq!(partial, [(true, false), (false, true)]);
+  assert_eq!(ticks[0].content.last_price, Some(1520.35));
+}
@@ -0,0 +1,158 @@
+//! Snapshots for bug reports and dashboards
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  ApiKeyId, KiteTickerManager, KiteTickerManagerBuilder, Mode,
+  MultiApiKiteTickerManager, SymbolActivity,
+};
+
+async fn started(server: &MockServer, connections: usize) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("secret_key", "secret_token")
+    .config(server.manager_config(connections))
+    .build();
+  manager.start().await.unwrap();
+  manager
+}
+
+#[tokio::test]
+async fn diagnostics_cover_connections_and_subscriptions() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 2).await;
+  let tokens = [
+    fixtures::NSE_EQUITY,
+    fixtures::NFO_FUTURE,
+    fixtures::NIFTY_50,
+  ];
+  manager
+    .subscribe_symbols(&tokens, Some(Mode::Quote))
+    .await
+    .unwrap();
+
+  let diagnostics = manager.diagnostics().await;
+  assert_eq!(diagnostics.connection_count, 2);
+  assert_eq!(diagnostics.connections.len(), 2);
+  assert_eq!(diagnostics.processor_stats.len(), 2);
+  assert_eq!(diagnostics.config.max_connections, 2);
+  let mut subscribed: Vec<u32> = diagnostics
+    .symbol_distribution
+    .values()
+    .flatten()
+    .copied()
+    .collect();
+  subscribed.sort_unstable();
+  let mut expected = tokens.to_vec();
+  expected.sort_unstable();
+  assert_eq!(subscribed, expected);
+  let symbols: usize =
+    diagnostics.connections.iter().map(|c| c.symbol_count).sum();
+  assert_eq!(symbols, 3);
+  assert!(diagnostics.health.is_some());
+  let mut logged: Vec<u32> = diagnostics
+    .recent_operations
+    .iter()
+    .flat_map(|op| op.tokens.iter().copied())
+    .collect();
+  logged.sort_unstable();
+  assert_eq!(logged, expected);
+
+  // Fit for a bug report: serializable, and without credentials
+  let json = serde_json::to_string(&diagnostics).unwrap();
+  assert!(!json.contains("secret_"));
+}
+
+#[test]
+fn fingerprints_show_only_the_key_prefix() {
+  let manager =
+    KiteTickerManagerBuilder::new("abcd1234secret", "secret_token").build();
+  assert_eq!(manager.api_key_fingerprint(), "abcd***");
+  // Too short to keep a prefix
+  let manager = KiteTickerManagerBuilder::new("abcd", "secret_token").build();
+  assert_eq!(manager.api_key_fingerprint(), "***");
+
+  let manager = MultiApiKiteTickerManager::builder()
+    .add_api_key("primary", "wxyz9876secret", "token_a")
+    .add_api_key("backup", "k1", "token_b")
+    .build();
+  let fingerprints: HashMap<_, _> =
+    manager.api_key_fingerprints().into_iter().collect();
+  assert_eq!(fingerprints.len(), 2);
+  assert_eq!(fingerprints[&ApiKeyId::from("primary")], "wxyz***");
+  assert_eq!(fingerprints[&ApiKeyId::from("backup")], "***");
+}
+
+#[tokio::test]
+async fn dashboard_reflects_subscriptions_and_traffic() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 2).await;
+  let tokens = [
+    fixtures::NSE_EQUITY,
+    fixtures::NFO_FUTURE,
+    fixtures::NIFTY_50,
+  ];
+  manager
+    .subscribe_symbols(&tokens, Some(Mode::LTP))
+    .await
+    .unwrap();
+  server
+    .wait_until(|| {
+      (0..2).map(|c| server.subscribed(c).len()).sum::<usize>() == 3
+    })
+    .await;
+  let client_of = |token| {
+    (0..2)
+      .find(|&c| server.subscribed(c).contains(&token))
+      .unwrap()
+  };
+  let nse = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  let nfo = fixtures::frame(&[fixtures::nfo_future().ltp()]);
+  for _ in 0..3 {
+    server.send_frame(client_of(fixtures::NSE_EQUITY), nse.clone());
+  }
+  server.send_frame(client_of(fixtures::NFO_FUTURE), nfo.clone());
+  // Counts are flushed once a second, on the next message
+  tokio::time::sleep(Duration::from_millis(1_100)).await;
+  server.send_frame(client_of(fixtures::NSE_EQUITY), nse);
+  server.send_frame(client_of(fixtures::NFO_FUTURE), nfo);
+
+  let snapshot = tokio::time::timeout(WAIT, async {
+    loop {
+      let snapshot = manager.dashboard_snapshot().await;
+      let ticks: u64 = snapshot.top_symbols.iter().map(|s| s.ticks).sum();
+      if ticks == 6 && snapshot.total_messages > 0 {
+        return snapshot;
+      }
+      tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+  })
+  .await
+  .expect("tick counts never flushed");
+
+  assert_eq!(snapshot.connections.len(), 2);
+  assert_eq!(snapshot.healthy_connections, 2);
+  assert_eq!(snapshot.total_symbols, 3);
+  let symbols: usize = snapshot.connections.iter().map(|c| c.symbols).sum();
+  assert_eq!(symbols, 3);
+  assert!(snapshot.connections.iter().all(|c| c.healthy));
+  assert!(snapshot.connections.iter().all(|c| c.last_error.is_none()));
+  assert_eq!(snapshot.total_errors, 0);
+  // Busiest first; the index sent nothing
+  assert_eq!(
+    snapshot.top_symbols,
+    [
+      SymbolActivity {
+        token: fixtures::NSE_EQUITY,
+        ticks: 4
+      },
+      SymbolActivity {
+        token: fixtures::NFO_FUTURE,
+        ticks: 2
+      },
+    ]
+  );
+  serde_json::to_string(&snapshot).unwrap();
+}
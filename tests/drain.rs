@@ -0,0 +1,111 @@
+//! Draining a connection onto the others
+
+mod common;
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use common::MockServer;
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManager, KiteTickerManagerBuilder, Mode,
+};
+
+const TOKENS: [u32; 6] = [408065, 884737, 738561, 2953217, 341249, 1270529];
+
+async fn started(server: &MockServer) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(3))
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&TOKENS, Some(Mode::Quote))
+    .await
+    .unwrap();
+  manager
+}
+
+fn on(manager: &KiteTickerManager, channel: ChannelId) -> HashSet<u32> {
+  manager
+    .get_symbol_distribution()
+    .remove(&channel)
+    .unwrap_or_default()
+    .into_iter()
+    .collect()
+}
+
+#[tokio::test]
+async fn drain_moves_every_symbol_once_keeping_its_mode() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  manager.change_mode(&[TOKENS[0]], Mode::Full).await.unwrap();
+  let drained = on(&manager, ChannelId::Connection1);
+  assert!(!drained.is_empty());
+
+  manager
+    .drain_connection(ChannelId::Connection1)
+    .await
+    .unwrap();
+
+  assert!(on(&manager, ChannelId::Connection1).is_empty());
+  let second = on(&manager, ChannelId::Connection2);
+  let third = on(&manager, ChannelId::Connection3);
+  assert!(second.is_disjoint(&third));
+  let all: HashSet<u32> = second.union(&third).copied().collect();
+  assert_eq!(all, TOKENS.into_iter().collect());
+  let subscriptions = manager.get_subscriptions();
+  assert_eq!(subscriptions.len(), TOKENS.len());
+  assert_eq!(subscriptions[&TOKENS[0]], Mode::Full);
+
+  server
+    .wait_until(|| {
+      server.subscribed(0).is_empty()
+        && server.subscribed(1).len() + server.subscribed(2).len()
+          == TOKENS.len()
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn failed_drain_leaves_symbols_in_place() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let source = on(&manager, ChannelId::Connection1);
+  let probe = *source.iter().next().unwrap();
+  server
+    .wait_until(|| server.subscribed(0).len() == source.len())
+    .await;
+
+  // Close the source socket and wait for its writer to give up
+  server.close_client(0, 1000, "maintenance");
+  let mut dead = false;
+  for _ in 0..20 {
+    if manager.change_mode(&[probe], Mode::Quote).await.is_err() {
+      dead = true;
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(25)).await;
+  }
+  assert!(dead, "commands kept succeeding on a closed socket");
+
+  assert!(manager
+    .drain_connection(ChannelId::Connection1)
+    .await
+    .is_err());
+
+  assert_eq!(on(&manager, ChannelId::Connection1), source);
+  let elsewhere: HashSet<u32> = on(&manager, ChannelId::Connection2)
+    .union(&on(&manager, ChannelId::Connection3))
+    .copied()
+    .collect();
+  assert!(elsewhere.is_disjoint(&source));
+  server
+    .wait_until(|| {
+      let moved: HashSet<u32> = server
+        .subscribed(1)
+        .into_iter()
+        .chain(server.subscribed(2))
+        .collect();
+      moved.is_disjoint(&source)
+    })
+    .await;
+}
@@ -0,0 +1,51 @@
+//! Health summary status and serialization
+
+use kiteticker_async_manager::HealthSummary;
+use std::time::Duration;
+
+fn summary(last_error_age: Option<Duration>) -> HealthSummary {
+  let mut summary = HealthSummary::default();
+  summary.healthy_connections = 3;
+  summary.total_errors = 4;
+  summary.last_error_age = last_error_age;
+  summary
+}
+
+#[test]
+fn recent_error_reports_degraded() {
+  let summary = summary(Some(Duration::from_secs(5)));
+  assert!(summary.has_recent_errors());
+  assert!(!summary.is_healthy());
+  assert_eq!(summary.status_str(), "degraded");
+}
+
+#[test]
+fn old_errors_no_longer_degrade_health() {
+  let summary = summary(Some(
+    HealthSummary::RECENT_ERROR_WINDOW + Duration::from_secs(1),
+  ));
+  assert!(!summary.has_recent_errors());
+  assert!(summary.is_healthy());
+  assert_eq!(summary.status_str(), "healthy");
+  assert_eq!(summary.total_errors, 4);
+}
+
+#[test]
+fn unhealthy_connection_reports_degraded_without_errors() {
+  let mut summary = summary(None);
+  summary.unhealthy_connections = vec![1];
+  assert_eq!(summary.status_str(), "degraded");
+  summary.healthy_connections = 0;
+  assert_eq!(summary.status_str(), "critical");
+}
+
+#[test]
+fn serializes_error_age_in_seconds() {
+  let json =
+    serde_json::to_value(summary(Some(Duration::from_millis(1500)))).unwrap();
+  assert_eq!(json["last_error_age"], 1.5);
+  assert_eq!(json["total_errors"], 4);
+
+  let json = serde_json::to_value(summary(None)).unwrap();
+  assert!(json["last_error_age"].is_null());
+}
@@ -0,0 +1,40 @@
+//! 1-byte heartbeats keep a parsed-mode connection alive
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{KiteTickerManagerBuilder, Mode};
+
+#[tokio::test]
+async fn heartbeats_alone_keep_the_connection_up() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(1);
+  config.receive_timeout = Duration::from_millis(100);
+  config.heartbeat_liveness_threshold = Duration::from_secs(1);
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  // No ticks for well over the threshold, only heartbeats
+  let until = Instant::now() + Duration::from_millis(2_500);
+  while Instant::now() < until {
+    server.send_frame(0, fixtures::HEARTBEAT.to_vec());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+  }
+  let stats = manager.get_stats().await.unwrap();
+  assert!(stats.connection_stats[0].is_connected);
+  assert_eq!(server.open_clients(), [0]);
+
+  // Once they stop too, the connection is given up
+  tokio::time::sleep(Duration::from_millis(2_500)).await;
+  let stats = manager.get_stats().await.unwrap();
+  assert!(!stats.connection_stats[0].is_connected);
+}
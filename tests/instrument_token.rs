@@ -0,0 +1,47 @@
+//! `InstrumentToken` conversions and use as a map key
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::fixtures;
+use kiteticker_async_manager::{Exchange, InstrumentToken, Tick, TickMessage};
+
+#[test]
+fn converts_both_ways_with_u32() {
+  let token = InstrumentToken::from(fixtures::NSE_EQUITY);
+  assert_eq!(token, fixtures::NSE_EQUITY);
+  assert_eq!(u32::from(token), fixtures::NSE_EQUITY);
+  assert_eq!(token.to_string(), "408065");
+  assert_eq!(serde_json::to_string(&token).unwrap(), "408065");
+  assert_eq!(
+    serde_json::from_str::<InstrumentToken>("408065").unwrap(),
+    token
+  );
+}
+
+#[test]
+fn exchange_comes_from_the_low_byte() {
+  for (token, exchange) in [
+    (fixtures::NSE_EQUITY, Exchange::NSE),
+    (fixtures::NFO_FUTURE, Exchange::NFO),
+    (fixtures::BSE_EQUITY, Exchange::BSE),
+    (fixtures::MCX_FUTURE, Exchange::MCX),
+    (fixtures::NIFTY_50, Exchange::INDICES),
+  ] {
+    assert_eq!(InstrumentToken(token).exchange(), exchange, "{}", token);
+  }
+}
+
+#[test]
+fn ticks_key_maps_by_typed_token() {
+  let mut latest: HashMap<InstrumentToken, f64> = HashMap::new();
+  for raw in [fixtures::nse_equity(), fixtures::bse_equity()] {
+    let tick = Tick::try_from(&raw.ltp()[..]).unwrap();
+    let message = TickMessage::new(raw.token, tick);
+    latest.insert(message.token(), message.content.last_price.unwrap());
+  }
+  assert_eq!(latest.len(), 2);
+  assert_eq!(latest[&InstrumentToken(fixtures::NSE_EQUITY)], 1520.35);
+  assert!(latest.contains_key(&fixtures::BSE_EQUITY.into()));
+}
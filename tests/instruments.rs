@@ -0,0 +1,84 @@
+//! Subscribing by trading symbol through an instrument directory
+
+mod common;
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{
+  Exchange, InstrumentDirectory, KiteTickerManagerBuilder, Mode,
+};
+
+/// A few rows of Kite's instruments dump
+const INSTRUMENTS: &str = "\
+instrument_token,exchange_token,tradingsymbol,name,last_price,expiry,strike,tick_size,lot_size,instrument_type,segment,exchange
+408065,1594,INFY,INFOSYS,0,,0,0.05,1,EQ,NSE,NSE
+128000004,500000,RELIANCE,\"RELIANCE INDUSTRIES, LTD\",0,,0,0.05,1,EQ,BSE,BSE
+12345602,48225,NIFTY24DECFUT,NIFTY,0,2024-12-26,0,0.05,25,FUT,NFO-FUT,NFO
+256265,1001,NIFTY 50,NIFTY 50,0,,0,0,0,EQ,INDICES,NSE
+";
+
+#[tokio::test]
+async fn symbols_from_a_csv_subscribe_their_tokens() {
+  let directory =
+    InstrumentDirectory::from_csv(INSTRUMENTS.as_bytes()).unwrap();
+  // The index is also reachable under INDICES
+  assert_eq!(directory.len(), 5);
+  assert_eq!(
+    directory.token("NIFTY 50", Exchange::INDICES),
+    Some(fixtures::NIFTY_50)
+  );
+
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .instrument_directory(directory)
+    .build();
+  manager.start().await.unwrap();
+
+  manager
+    .subscribe_by_symbol(
+      &[
+        ("INFY", Exchange::NSE),
+        ("RELIANCE", Exchange::BSE),
+        ("NIFTY 50", Exchange::NSE),
+      ],
+      Some(Mode::Quote),
+    )
+    .await
+    .unwrap();
+  let mut expected = vec![
+    fixtures::NSE_EQUITY,
+    fixtures::BSE_EQUITY,
+    fixtures::NIFTY_50,
+  ];
+  expected.sort_unstable();
+  server.wait_until(|| server.subscribed(0) == expected).await;
+
+  // One unknown symbol rejects the whole call
+  let err = manager
+    .subscribe_by_symbol(
+      &[("NIFTY24DECFUT", Exchange::NFO), ("INFY", Exchange::BSE)],
+      Some(Mode::Quote),
+    )
+    .await
+    .unwrap_err()
+    .to_string();
+  assert!(err.contains("BSE:INFY"), "{}", err);
+  assert!(!err.contains("NIFTY24DECFUT"), "{}", err);
+  assert!(!manager.is_subscribed(fixtures::NFO_FUTURE));
+  assert_eq!(manager.subscribed_count(), 3);
+}
+
+#[tokio::test]
+async fn subscribing_by_symbol_needs_a_directory() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+  let err = manager
+    .subscribe_by_symbol(&[("INFY", Exchange::NSE)], None)
+    .await
+    .unwrap_err()
+    .to_string();
+  assert!(err.contains("No instrument directory"), "{}", err);
+}
@@ -0,0 +1,218 @@
+//! Last-value cache: derived fields and snapshots on resubscribe
+
+mod common;
+
+use std::time::Duration;
+
+use common::fixtures;
+use common::{MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManager, KiteTickerManagerBuilder, Mode, TickDirection,
+  TickMessage, TickerMessage,
+};
+use tokio::sync::broadcast;
+
+async fn started(server: &MockServer) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .enable_last_value_cache(true)
+    .build();
+  manager.start().await.unwrap();
+  manager
+}
+
+async fn next_message(
+  receiver: &mut broadcast::Receiver<TickerMessage>,
+  want: fn(&TickerMessage) -> Option<Vec<TickMessage>>,
+) -> Vec<TickMessage> {
+  tokio::time::timeout(WAIT, async {
+    loop {
+      if let Some(ticks) = want(&receiver.recv().await.unwrap()) {
+        return ticks;
+      }
+    }
+  })
+  .await
+  .expect("no message within the wait")
+}
+
+fn ticks(message: &TickerMessage) -> Option<Vec<TickMessage>> {
+  match message {
+    TickerMessage::Ticks(ticks) => Some(ticks.clone()),
+    _ => None,
+  }
+}
+
+fn snapshot(message: &TickerMessage) -> Option<Vec<TickMessage>> {
+  match message {
+    TickerMessage::Snapshot(ticks) => Some(ticks.clone()),
+    _ => None,
+  }
+}
+
+#[tokio::test]
+async fn ticks_carry_direction_and_volume_delta() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::Quote))
+    .await
+    .unwrap();
+
+  let mut raw = fixtures::nse_equity();
+  server.send_frame(0, fixtures::frame(&[raw.quote()]));
+  let first = next_message(&mut receiver, ticks).await;
+  assert_eq!(first[0].tick_direction, None);
+
+  raw.last_price += 5;
+  raw.volume += 250;
+  server.send_frame(0, fixtures::frame(&[raw.quote()]));
+  let second = next_message(&mut receiver, ticks).await;
+  assert_eq!(second[0].tick_direction, Some(TickDirection::Up));
+  assert_eq!(second[0].volume_delta, Some(250));
+
+  let cached = manager.get_last_value(fixtures::NSE_EQUITY).await.unwrap();
+  assert_eq!(cached.content.last_price, Some(1520.40));
+  assert_eq!(cached.tick_direction, Some(TickDirection::Up));
+}
+
+#[tokio::test]
+async fn resubscribe_replays_the_cached_tick() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::Quote))
+    .await
+    .unwrap();
+  server.send_frame(0, fixtures::frame(&[fixtures::nse_equity().quote()]));
+  next_message(&mut receiver, ticks).await;
+
+  manager
+    .unsubscribe_symbols(&[fixtures::NSE_EQUITY])
+    .await
+    .unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::Quote))
+    .await
+    .unwrap();
+  let replayed = next_message(&mut receiver, snapshot).await;
+  assert_eq!(replayed.len(), 1);
+  assert_eq!(replayed[0].instrument_token, fixtures::NSE_EQUITY);
+  assert_eq!(replayed[0].content.last_price, Some(1520.35));
+}
+
+#[tokio::test]
+async fn snapshot_goes_only_to_the_new_receiver() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .enable_last_value_cache(true)
+    .snapshot_on_get_channel(true)
+    .build();
+  manager.start().await.unwrap();
+  let mut existing = manager.get_channel(ChannelId::Connection1).unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::Quote))
+    .await
+    .unwrap();
+  let mut raw = fixtures::nse_equity();
+  server.send_frame(0, fixtures::frame(&[raw.quote()]));
+  next_message(&mut existing, ticks).await;
+  // The cache is published once per frame; wait until it has the tick
+  tokio::time::timeout(WAIT, async {
+    while manager.get_last_value(fixtures::NSE_EQUITY).await.is_none() {
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+  })
+  .await
+  .expect("tick never reached the cache");
+
+  let mut fresh = manager.get_channel(ChannelId::Connection1).unwrap();
+  let first = tokio::time::timeout(WAIT, fresh.recv()).await.unwrap();
+  let Ok(TickerMessage::Snapshot(replayed)) = first else {
+    panic!("expected a snapshot first, got {:?}", first);
+  };
+  assert_eq!(replayed.len(), 1);
+  assert_eq!(replayed[0].content.last_price, Some(1520.35));
+
+  // Live ticks follow on both; the existing receiver saw no snapshot
+  raw.last_price += 5;
+  server.send_frame(0, fixtures::frame(&[raw.quote()]));
+  for receiver in [&mut existing, &mut fresh] {
+    let message = tokio::time::timeout(WAIT, receiver.recv()).await.unwrap();
+    let Ok(TickerMessage::Ticks(live)) = message else {
+      panic!("expected live ticks, got {:?}", message);
+    };
+    assert_eq!(live[0].content.last_price, Some(1520.40));
+  }
+}
+
+#[tokio::test]
+async fn resync_after_a_lag_replays_the_latest_ticks() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(1);
+  config.connection_buffer_size = 2;
+  config.parser_buffer_size = 2;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .enable_last_value_cache(true)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(
+      &[fixtures::NSE_EQUITY, fixtures::NFO_FUTURE],
+      Some(Mode::Quote),
+    )
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+  let mut receiver =
+    manager.get_resync_channel(ChannelId::Connection1).unwrap();
+
+  // Far more frames than the channel holds, none of them read
+  let mut nse = fixtures::nse_equity();
+  let mut nfo = fixtures::nfo_future();
+  for _ in 0..10 {
+    nse.last_price += 5;
+    nfo.last_price += 5;
+    server.send_frame(0, fixtures::frame(&[nse.quote(), nfo.quote()]));
+    tokio::time::sleep(Duration::from_millis(10)).await;
+  }
+  let latest = f64::from(nse.last_price) / 100.0;
+  tokio::time::timeout(WAIT, async {
+    loop {
+      let cached = manager.get_last_value(fixtures::NSE_EQUITY).await;
+      if cached.and_then(|t| t.content.last_price) == Some(latest) {
+        break;
+      }
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+  })
+  .await
+  .expect("last frame not processed");
+
+  let lagged = receiver.recv().await;
+  assert!(
+    matches!(lagged, Err(broadcast::error::RecvError::Lagged(_))),
+    "{:?}",
+    lagged
+  );
+  assert_eq!(receiver.resync().await, 2);
+  let message = receiver.recv().await.unwrap();
+  let TickerMessage::Snapshot(mut ticks) = message else {
+    panic!("expected a snapshot, got {:?}", message);
+  };
+  ticks.sort_by_key(|t| t.instrument_token);
+  // Sorted by token already
+  let expected: Vec<_> = [nse, nfo]
+    .iter()
+    .map(|raw| (raw.token, f64::from(raw.last_price) / 100.0))
+    .collect();
+  let snapshot: Vec<_> = ticks
+    .iter()
+    .map(|t| (t.instrument_token, t.content.last_price.unwrap()))
+    .collect();
+  assert_eq!(snapshot, expected);
+}
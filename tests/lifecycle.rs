@@ -0,0 +1,102 @@
+//! Manager start/stop and cancellation
+
+mod common;
+
+use std::time::Duration;
+
+use common::MockServer;
+use kiteticker_async_manager::{
+  ChannelId, KiteError, KiteTickerManagerBuilder, Mode,
+};
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn stop_leaves_the_callers_token_untouched() {
+  let server = MockServer::start().await;
+  let token = CancellationToken::new();
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager
+    .start_with_cancellation(token.clone())
+    .await
+    .unwrap();
+
+  manager.stop().await.unwrap();
+  assert!(manager.is_stopped());
+  assert!(!token.is_cancelled());
+}
+
+#[tokio::test]
+async fn cancelling_the_callers_token_stops_the_manager() {
+  let server = MockServer::start().await;
+  let token = CancellationToken::new();
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager
+    .start_with_cancellation(token.clone())
+    .await
+    .unwrap();
+
+  token.cancel();
+  assert!(manager.is_stopped());
+  let err = manager.subscribe_symbols(&[408065], Some(Mode::LTP)).await;
+  assert_eq!(err.unwrap_err(), KiteError::Closed);
+}
+
+#[tokio::test]
+async fn every_operation_fails_closed_after_stop() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(2))
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[408065], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_until(|| server.commands().len() == 2).await;
+  manager.stop().await.unwrap();
+
+  let tokens = [408065];
+  let results = [
+    manager.start().await,
+    manager.subscribe_symbols(&[884737], Some(Mode::LTP)).await,
+    manager
+      .subscribe_symbols_confirmed(&[884737], None, Duration::from_secs(1))
+      .await,
+    manager.unsubscribe_symbols(&tokens).await,
+    manager.change_mode(&tokens, Mode::Full).await,
+    manager.reconnect(ChannelId::Connection1).await,
+    manager.drain_connection(ChannelId::Connection1).await,
+    manager.expire_older_than(Duration::ZERO).await.map(drop),
+    manager.prune_inactive().await.map(drop),
+  ];
+  for (i, result) in results.into_iter().enumerate() {
+    assert_eq!(result, Err(KiteError::Closed), "operation {}", i);
+  }
+  // Nothing reached the sockets after the stop
+  assert_eq!(server.commands().len(), 2);
+}
+
+#[tokio::test]
+async fn other_failures_carry_their_message() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+
+  let err = manager
+    .drain_connection(ChannelId::Connection3)
+    .await
+    .unwrap_err();
+  assert_eq!(
+    err,
+    KiteError::Other("Connection Connection3 not found".to_string())
+  );
+  // Callers that propagate text errors keep working
+  let text: String = err.into();
+  assert_eq!(text, "Connection Connection3 not found");
+}
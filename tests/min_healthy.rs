@@ -0,0 +1,69 @@
+//! Health events against `min_healthy_connections`
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{HealthEvent, KiteTickerManagerBuilder, Mode};
+
+#[tokio::test]
+async fn losing_one_of_three_connections_fires_below_minimum() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(3);
+  config.health_check_interval = Duration::from_millis(100);
+  config.receive_timeout = Duration::from_millis(100);
+  config.heartbeat_liveness_threshold = Duration::from_secs(1);
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .min_healthy_connections(3)
+    .build();
+  let mut events = manager.health_events();
+  manager.start().await.unwrap();
+  let tokens = [
+    fixtures::NSE_EQUITY,
+    fixtures::NFO_FUTURE,
+    fixtures::BSE_EQUITY,
+  ];
+  manager
+    .subscribe_symbols(&tokens, Some(Mode::LTP))
+    .await
+    .unwrap();
+  server
+    .wait_until(|| (0..3).all(|c| !server.subscribed(c).is_empty()))
+    .await;
+  assert!(manager.get_health().await.unwrap().meets_minimum(3));
+
+  // One connection goes silent while the others keep beating
+  let silent = (0..3)
+    .find(|&c| server.subscribed(c).contains(&fixtures::NSE_EQUITY))
+    .unwrap();
+  let event = tokio::time::timeout(WAIT, async {
+    let mut next_beat = Instant::now();
+    loop {
+      if Instant::now() >= next_beat {
+        for client in (0..3).filter(|&c| c != silent) {
+          server.send_frame(client, fixtures::HEARTBEAT.to_vec());
+        }
+        next_beat += Duration::from_millis(200);
+      }
+      if let Ok(event) = events.try_recv() {
+        return event;
+      }
+      tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+  })
+  .await
+  .expect("no health event");
+
+  assert_eq!(
+    event,
+    HealthEvent::BelowMinimum {
+      healthy: 2,
+      minimum: 3
+    }
+  );
+  let health = manager.get_health().await.unwrap();
+  assert!(!health.meets_minimum(3));
+  assert!(health.meets_minimum(2));
+}
@@ -0,0 +1,51 @@
+//! Subscribed modes settle packet lengths several layouts share
+
+mod common;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  KiteTickerAsync, KiteTickerConfig, KiteTickerSubscriber, Mode, Tick,
+  TickerMessage,
+};
+
+async fn next_tick(subscriber: &mut KiteTickerSubscriber) -> Tick {
+  tokio::time::timeout(WAIT, async {
+    loop {
+      if let Some(TickerMessage::Ticks(ticks)) =
+        subscriber.next_message().await.unwrap()
+      {
+        return ticks[0].content.clone();
+      }
+    }
+  })
+  .await
+  .expect("no ticks within the wait")
+}
+
+#[tokio::test]
+async fn long_index_packet_decodes_in_the_subscribed_mode() {
+  // An index Full packet padded to 44 bytes, the tradable Quote length
+  let mut packet = fixtures::nifty_50().full();
+  packet.resize(44, 0);
+  let unhinted = Tick::try_from(&packet[..]).unwrap();
+  assert_eq!(unhinted.mode, Mode::Full);
+
+  let server = MockServer::start().await;
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NIFTY_50], Some(Mode::Quote))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  server.send_frame(0, fixtures::frame(&[packet]));
+  let tick = next_tick(&mut subscriber).await;
+  assert_eq!(tick.mode, Mode::Quote);
+  assert_eq!(tick.instrument_token, fixtures::NIFTY_50);
+  assert_eq!(tick.exchange_timestamp, None);
+  assert_eq!(tick.ohlc.map(|o| o.open), Some(19800.0));
+}
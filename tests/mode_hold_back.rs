@@ -0,0 +1,123 @@
+//! Packets sent before a requested mode takes effect
+
+mod common;
+
+use std::time::Duration;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerAsync, KiteTickerConfig, KiteTickerManagerBuilder,
+  KiteTickerSubscriber, Mode, TickMessage, TickerMessage,
+};
+
+async fn subscribed(
+  server: &MockServer,
+  hold_back: Duration,
+) -> (KiteTickerAsync, KiteTickerSubscriber) {
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  config.mode_hold_back = hold_back;
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  let subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::Full))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+  (ticker, subscriber)
+}
+
+async fn next_ticks(subscriber: &mut KiteTickerSubscriber) -> Vec<TickMessage> {
+  tokio::time::timeout(WAIT, async {
+    loop {
+      if let Some(TickerMessage::Ticks(ticks)) =
+        subscriber.next_message().await.unwrap()
+      {
+        return ticks;
+      }
+    }
+  })
+  .await
+  .expect("no ticks within the wait")
+}
+
+#[tokio::test]
+async fn first_tick_of_a_full_subscription_is_full() {
+  let server = MockServer::start().await;
+  let (ticker, mut subscriber) =
+    subscribed(&server, Duration::from_secs(60)).await;
+
+  // Kite starts a subscription in Quote until the mode command lands
+  let raw = fixtures::nse_equity();
+  server.send_frame(0, fixtures::frame(&[raw.quote()]));
+  server.send_frame(0, fixtures::frame(&[raw.full()]));
+
+  let ticks = next_ticks(&mut subscriber).await;
+  assert_eq!(ticks.len(), 1);
+  assert_eq!(ticks[0].content.mode, Mode::Full);
+  assert!(ticks[0].content.depth.is_some());
+  assert_eq!(ticker.held_back_count(), 1);
+  assert_eq!(subscriber.held_back_count(), 1);
+}
+
+#[tokio::test]
+async fn packets_are_delivered_once_the_hold_back_expires() {
+  let server = MockServer::start().await;
+  let (ticker, mut subscriber) =
+    subscribed(&server, Duration::from_millis(100)).await;
+  tokio::time::sleep(Duration::from_millis(150)).await;
+
+  // The mode command never took effect: Quote ticks still come through
+  let raw = fixtures::nse_equity();
+  server.send_frame(0, fixtures::frame(&[raw.quote()]));
+
+  let ticks = next_ticks(&mut subscriber).await;
+  assert_eq!(ticks.len(), 1);
+  assert_eq!(ticks[0].content.mode, Mode::Quote);
+  assert_eq!(ticks[0].content.last_price, Some(1520.35));
+  assert_eq!(ticker.held_back_count(), 0);
+}
+
+#[tokio::test]
+async fn manager_reports_held_back_packets() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .mode_hold_back(Duration::from_secs(60))
+    .build();
+  manager.start().await.unwrap();
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::Full))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  let raw = fixtures::nse_equity();
+  server.send_frame(0, fixtures::frame(&[raw.quote()]));
+  server.send_frame(0, fixtures::frame(&[raw.full()]));
+  // Stats are flushed once a second, on the next message
+  tokio::time::sleep(Duration::from_millis(1100)).await;
+  server.send_frame(0, fixtures::frame(&[raw.full()]));
+
+  let mut modes = Vec::new();
+  tokio::time::timeout(WAIT, async {
+    while modes.len() < 2 {
+      if let TickerMessage::Ticks(ticks) = receiver.recv().await.unwrap() {
+        modes.extend(ticks.iter().map(|t| t.content.mode));
+      }
+    }
+  })
+  .await
+  .expect("no ticks within the wait");
+  assert_eq!(modes, [Mode::Full, Mode::Full]);
+
+  tokio::time::timeout(WAIT, async {
+    while manager.get_stats().await.unwrap().total_ticks_held_back != 1 {
+      tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+  })
+  .await
+  .expect("held-back packet never reported");
+}
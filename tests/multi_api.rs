@@ -0,0 +1,409 @@
+//! Multi-API manager against the mock server
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::{fixtures, MockServer, WAIT};
+use futures_util::StreamExt;
+use kiteticker_async_manager::{
+  ApiKeyId, ChannelId, Mode, MultiApiKiteTickerManager, TickerMessage,
+};
+use tokio::sync::broadcast;
+
+#[tokio::test]
+async fn per_connection_caps_apply_to_each_api_key() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(2);
+  config.per_connection_symbol_caps = vec![1];
+  config.max_symbols_per_connection = 2;
+  let mut manager = MultiApiKiteTickerManager::builder()
+    .add_api_key("primary", "key_a", "token_a")
+    .max_connections_per_api(2)
+    .base_config(config)
+    .build();
+  manager.start().await.unwrap();
+
+  manager
+    .subscribe_symbols(&[408065, 884737, 738561], Some(Mode::LTP))
+    .await
+    .unwrap();
+  let distribution = manager.get_symbol_distribution();
+  let primary = &distribution[&ApiKeyId::from("primary")];
+  assert_eq!(primary[&0].len(), 1);
+  assert_eq!(primary[&1].len(), 2);
+
+  assert!(manager
+    .subscribe_symbols(&[2953217], Some(Mode::LTP))
+    .await
+    .is_err());
+}
+
+async fn two_keys(
+  server: &MockServer,
+  connections: usize,
+) -> MultiApiKiteTickerManager {
+  let mut manager = MultiApiKiteTickerManager::builder()
+    .add_api_key("a", "key_a", "token_a")
+    .add_api_key("b", "key_b", "token_b")
+    .max_connections_per_api(connections)
+    .base_config(server.manager_config(connections))
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols_to_api("a", &[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  manager
+    .subscribe_symbols_to_api("b", &[fixtures::NIFTY_50], Some(Mode::LTP))
+    .await
+    .unwrap();
+  manager
+}
+
+fn clients_of(server: &MockServer, key: &str) -> Vec<usize> {
+  server
+    .client_keys()
+    .iter()
+    .enumerate()
+    .filter(|(_, k)| *k == key)
+    .map(|(i, _)| i)
+    .collect()
+}
+
+#[tokio::test]
+async fn rotating_one_key_leaves_the_other_streaming() {
+  let server = MockServer::start().await;
+  let mut manager = two_keys(&server, 1).await;
+  let b = clients_of(&server, "key_b");
+  server
+    .wait_until(|| server.subscribed(b[0]) == [fixtures::NIFTY_50])
+    .await;
+  let b_commands = server.commands().len();
+  let mut unified = manager.get_unified_channel();
+
+  manager
+    .update_credentials("a", "key_a", "token_a2")
+    .await
+    .unwrap();
+
+  // Key a's subscription resumes on a new socket, the old one is closed
+  let old_a = clients_of(&server, "key_a")[0];
+  server.wait_for_clients(3).await;
+  server
+    .wait_until(|| server.subscribed(2) == [fixtures::NSE_EQUITY])
+    .await;
+  server
+    .wait_until(|| !server.open_clients().contains(&old_a))
+    .await;
+  // Key b's socket stayed open and got no new commands
+  assert!(server.open_clients().contains(&b[0]));
+  assert_eq!(
+    server.commands()[b_commands..]
+      .iter()
+      .filter(|c| c.client == b[0])
+      .count(),
+    0
+  );
+
+  server.send_frame(2, fixtures::frame(&[fixtures::nse_equity().ltp()]));
+  server.send_frame(b[0], fixtures::frame(&[fixtures::nifty_50().ltp()]));
+  let mut tokens = HashMap::new();
+  tokio::time::timeout(WAIT, async {
+    while tokens.len() < 2 {
+      if let (key, TickerMessage::Ticks(ticks)) = unified.recv().await.unwrap()
+      {
+        for tick in ticks {
+          tokens.insert(tick.instrument_token, key.clone());
+        }
+      }
+    }
+  })
+  .await
+  .expect("ticks not delivered after the rotation");
+  assert_eq!(tokens[&fixtures::NSE_EQUITY], ApiKeyId::from("a"));
+  assert_eq!(tokens[&fixtures::NIFTY_50], ApiKeyId::from("b"));
+}
+
+#[tokio::test]
+async fn failed_rotation_moves_every_connection_and_can_be_retried() {
+  let server = MockServer::start().await;
+  let mut manager = two_keys(&server, 2).await;
+  let old_a = clients_of(&server, "key_a");
+  assert_eq!(old_a.len(), 2);
+
+  server.reject_with(Some(500));
+  assert!(manager
+    .update_credentials("a", "key_a", "token_a2")
+    .await
+    .is_err());
+  // No connection of key a is left on a socket with the old token
+  server
+    .wait_until(|| old_a.iter().all(|c| !server.open_clients().contains(c)))
+    .await;
+  assert_eq!(server.rejected(), 2);
+
+  server.reject_with(None);
+  manager
+    .update_credentials("a", "key_a", "token_a2")
+    .await
+    .unwrap();
+  server
+    .wait_until(|| {
+      clients_of(&server, "key_a")
+        .into_iter()
+        .any(|c| server.subscribed(c) == [fixtures::NSE_EQUITY])
+    })
+    .await;
+  let open = server.open_clients();
+  let open_a = clients_of(&server, "key_a")
+    .into_iter()
+    .filter(|c| open.contains(c))
+    .count();
+  assert_eq!(open_a, 2);
+  let b = clients_of(&server, "key_b");
+  assert_eq!(b.len(), 2);
+  assert!(b.iter().all(|c| open.contains(c)));
+  assert_eq!(
+    manager.get_symbol_distribution()[&ApiKeyId::from("a")]
+      .values()
+      .flatten()
+      .collect::<Vec<_>>(),
+    [&fixtures::NSE_EQUITY]
+  );
+}
+
+fn open_of(server: &MockServer, key: &str) -> usize {
+  let open = server.open_clients();
+  clients_of(server, key)
+    .into_iter()
+    .filter(|c| open.contains(c))
+    .count()
+}
+
+fn three_per_key(server: &MockServer) -> MultiApiKiteTickerManager {
+  let mut config = server.manager_config(3);
+  config.startup_concurrency = 3;
+  MultiApiKiteTickerManager::builder()
+    .add_api_key("a", "key_a", "token_a")
+    .add_api_key("b", "key_b", "token_b")
+    .max_connections_per_api(3)
+    .base_config(config)
+    .build()
+}
+
+#[tokio::test]
+async fn two_keys_start_concurrently() {
+  let server = MockServer::start().await;
+  let mut manager = three_per_key(&server);
+  manager.start().await.unwrap();
+
+  assert_eq!(open_of(&server, "key_a"), 3);
+  assert_eq!(open_of(&server, "key_b"), 3);
+  assert_eq!(manager.get_stats().await.total_connections, 6);
+}
+
+#[tokio::test]
+async fn failed_start_closes_the_sockets_it_opened() {
+  let server = MockServer::start().await;
+  let mut manager = three_per_key(&server);
+  server.reject_key("key_b", Some(500));
+
+  assert!(manager.start().await.is_err());
+  assert_eq!(server.rejected(), 3);
+  // Key a's sockets did open, but none is left to count against its limit
+  assert_eq!(clients_of(&server, "key_a").len(), 3);
+  server.wait_until(|| server.open_clients().is_empty()).await;
+
+  server.reject_key("key_b", None);
+  manager.start().await.unwrap();
+  assert_eq!(open_of(&server, "key_a"), 3);
+  assert_eq!(open_of(&server, "key_b"), 3);
+}
+
+async fn two_keys_sharing(
+  server: &MockServer,
+  allow_duplicates: bool,
+) -> MultiApiKiteTickerManager {
+  let mut manager = MultiApiKiteTickerManager::builder()
+    .add_api_key("a", "key_a", "token_a")
+    .add_api_key("b", "key_b", "token_b")
+    .max_connections_per_api(1)
+    .base_config(server.manager_config(1))
+    .allow_duplicate_across_apis(allow_duplicates)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols_to_api("a", &[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  manager
+}
+
+#[tokio::test]
+async fn duplicate_token_streams_on_both_keys_tagged() {
+  let server = MockServer::start().await;
+  let mut manager = two_keys_sharing(&server, true).await;
+  manager
+    .subscribe_symbols_to_api("b", &[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  let mut unified = manager.get_unified_channel();
+  let (a, b) = (
+    clients_of(&server, "key_a")[0],
+    clients_of(&server, "key_b")[0],
+  );
+  for client in [a, b] {
+    server
+      .wait_until(|| server.subscribed(client) == [fixtures::NSE_EQUITY])
+      .await;
+  }
+
+  server.broadcast_frame(fixtures::frame(&[fixtures::nse_equity().ltp()]));
+  let mut keys = Vec::new();
+  tokio::time::timeout(WAIT, async {
+    while keys.len() < 2 {
+      if let (key, TickerMessage::Ticks(ticks)) = unified.recv().await.unwrap()
+      {
+        assert_eq!(ticks[0].instrument_token, fixtures::NSE_EQUITY);
+        keys.push(key);
+      }
+    }
+  })
+  .await
+  .expect("both copies not delivered");
+  keys.sort_by(|x, y| x.0.cmp(&y.0));
+  assert_eq!(keys, [ApiKeyId::from("a"), ApiKeyId::from("b")]);
+}
+
+#[tokio::test]
+async fn duplicate_token_is_rejected_by_default() {
+  let server = MockServer::start().await;
+  let mut manager = two_keys_sharing(&server, false).await;
+  assert!(manager
+    .subscribe_symbols_to_api("b", &[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .is_err());
+  let b = clients_of(&server, "key_b")[0];
+  assert!(server.subscribed(b).is_empty());
+}
+
+#[tokio::test]
+async fn detailed_channel_names_the_originating_connection() {
+  let server = MockServer::start().await;
+  let mut manager = MultiApiKiteTickerManager::builder()
+    .add_api_key("a", "key_a", "token_a")
+    .max_connections_per_api(2)
+    .base_config(server.manager_config(2))
+    .build();
+  manager.start().await.unwrap();
+  let tokens = [fixtures::NSE_EQUITY, fixtures::NFO_FUTURE];
+  manager
+    .subscribe_symbols(&tokens, Some(Mode::LTP))
+    .await
+    .unwrap();
+  let mut detailed = manager.get_unified_channel_detailed();
+
+  // Where each token landed, as the manager and the server see it
+  let mut expected = HashMap::new();
+  for (&index, symbols) in
+    &manager.get_symbol_distribution()[&ApiKeyId::from("a")]
+  {
+    for &token in symbols {
+      expected.insert(token, ChannelId::from_index(index).unwrap());
+    }
+  }
+  assert_eq!(expected.len(), 2);
+  for (token, packet) in [
+    (fixtures::NSE_EQUITY, fixtures::nse_equity().ltp()),
+    (fixtures::NFO_FUTURE, fixtures::nfo_future().ltp()),
+  ] {
+    server
+      .wait_until(|| (0..2).any(|c| server.subscribed(c).contains(&token)))
+      .await;
+    let client = (0..2)
+      .find(|&c| server.subscribed(c).contains(&token))
+      .unwrap();
+    server.send_frame(client, fixtures::frame(&[packet]));
+  }
+
+  let mut seen = HashMap::new();
+  tokio::time::timeout(WAIT, async {
+    while seen.len() < 2 {
+      if let (key, channel, TickerMessage::Ticks(ticks)) =
+        detailed.recv().await.unwrap()
+      {
+        assert_eq!(key, ApiKeyId::from("a"));
+        seen.insert(ticks[0].instrument_token, channel);
+      }
+    }
+  })
+  .await
+  .expect("ticks not delivered");
+  assert_eq!(seen, expected);
+}
+
+#[tokio::test]
+async fn unified_stream_tags_items_with_their_key() {
+  let server = MockServer::start().await;
+  let manager = two_keys(&server, 1).await;
+  let stream = manager.unified_stream();
+  tokio::pin!(stream);
+  let (a, b) = (
+    clients_of(&server, "key_a")[0],
+    clients_of(&server, "key_b")[0],
+  );
+  server
+    .wait_until(|| server.subscribed(a) == [fixtures::NSE_EQUITY])
+    .await;
+  server
+    .wait_until(|| server.subscribed(b) == [fixtures::NIFTY_50])
+    .await;
+
+  server.send_frame(a, fixtures::frame(&[fixtures::nse_equity().ltp()]));
+  server.send_frame(b, fixtures::frame(&[fixtures::nifty_50().ltp()]));
+  let mut tokens = HashMap::new();
+  tokio::time::timeout(WAIT, async {
+    while tokens.len() < 2 {
+      let item = stream.next().await.expect("stream ended");
+      if let (key, TickerMessage::Ticks(ticks)) = item.unwrap() {
+        for tick in ticks {
+          tokens.insert(tick.instrument_token, key.clone());
+        }
+      }
+    }
+  })
+  .await
+  .expect("ticks not delivered");
+  assert_eq!(tokens[&fixtures::NSE_EQUITY], ApiKeyId::from("a"));
+  assert_eq!(tokens[&fixtures::NIFTY_50], ApiKeyId::from("b"));
+}
+
+#[tokio::test]
+async fn unified_outputs_end_once_every_source_stops() {
+  let server = MockServer::start().await;
+  let mut manager = two_keys(&server, 2).await;
+  let stream = manager.unified_stream();
+  tokio::pin!(stream);
+  let mut detailed = manager.get_unified_channel_detailed();
+
+  manager.stop().await.unwrap();
+  tokio::time::timeout(WAIT, async { while stream.next().await.is_some() {} })
+    .await
+    .expect("unified stream still open after stop");
+  tokio::time::timeout(WAIT, async {
+    loop {
+      match detailed.recv().await {
+        Err(broadcast::error::RecvError::Closed) => break,
+        Err(broadcast::error::RecvError::Lagged(_)) | Ok(_) => {}
+      }
+    }
+  })
+  .await
+  .expect("detailed channel still open after stop");
+  // Late subscribers see a closed channel straight away
+  assert!(matches!(
+    manager.get_unified_channel().recv().await,
+    Err(broadcast::error::RecvError::Closed)
+  ));
+}
@@ -0,0 +1,51 @@
+//! `TickerMessage::NoData` on a silent connection
+
+mod common;
+
+use std::time::Duration;
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManagerBuilder, Mode, TickerMessage,
+};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+/// Messages waiting in `receiver`, in order: `true` for ticks, `false` for
+/// `NoData`
+fn drain(receiver: &mut broadcast::Receiver<TickerMessage>) -> Vec<bool> {
+  let mut seen = Vec::new();
+  loop {
+    match receiver.try_recv() {
+      Ok(TickerMessage::NoData(_)) => seen.push(false),
+      Ok(TickerMessage::Ticks(_)) => seen.push(true),
+      Ok(_) => {}
+      Err(TryRecvError::Empty) => return seen,
+      Err(e) => panic!("channel failed: {:?}", e),
+    }
+  }
+}
+
+#[tokio::test]
+async fn no_data_is_sent_once_per_silence() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(1);
+  config.receive_timeout = Duration::from_millis(100);
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+
+  // Several timeouts pass, but only the first reports the silence
+  tokio::time::sleep(Duration::from_millis(550)).await;
+  assert_eq!(drain(&mut receiver), [false]);
+
+  // Data resets it; the next silence is reported again, once
+  server.send_frame(0, fixtures::frame(&[fixtures::nse_equity().ltp()]));
+  tokio::time::sleep(Duration::from_millis(550)).await;
+  assert_eq!(drain(&mut receiver), [true, false]);
+}
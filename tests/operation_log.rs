@@ -0,0 +1,82 @@
+//! `operation_log`: the history of subscription changes, oldest first
+
+mod common;
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManagerBuilder, Mode, SubscriptionOpKind,
+};
+
+#[tokio::test]
+async fn operations_are_logged_in_order() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(1);
+  config.operation_log_capacity = 3;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+
+  let tokens = [fixtures::NSE_EQUITY, fixtures::NFO_FUTURE];
+  manager
+    .subscribe_symbols(&tokens, Some(Mode::LTP))
+    .await
+    .unwrap();
+  manager
+    .change_mode(&[fixtures::NFO_FUTURE], Mode::Full)
+    .await
+    .unwrap();
+  manager
+    .unsubscribe_symbols(&[fixtures::NSE_EQUITY])
+    .await
+    .unwrap();
+
+  let log: Vec<_> = manager
+    .operation_log()
+    .iter()
+    .map(|op| {
+      let mut tokens = op.tokens.clone();
+      tokens.sort_unstable();
+      (op.op, tokens, op.mode, op.channel)
+    })
+    .collect();
+  assert_eq!(
+    log,
+    [
+      (
+        SubscriptionOpKind::Subscribe,
+        tokens.to_vec(),
+        Some(Mode::LTP),
+        ChannelId::Connection1
+      ),
+      (
+        SubscriptionOpKind::ModeChange,
+        vec![fixtures::NFO_FUTURE],
+        Some(Mode::Full),
+        ChannelId::Connection1
+      ),
+      (
+        SubscriptionOpKind::Unsubscribe,
+        vec![fixtures::NSE_EQUITY],
+        None,
+        ChannelId::Connection1
+      ),
+    ]
+  );
+  let times: Vec<_> = manager
+    .operation_log()
+    .iter()
+    .map(|op| op.timestamp)
+    .collect();
+  assert!(times.windows(2).all(|w| w[0] <= w[1]));
+
+  // Bounded: the oldest entry makes room
+  manager
+    .subscribe_symbols(&[fixtures::MCX_FUTURE], Some(Mode::Quote))
+    .await
+    .unwrap();
+  let log = manager.operation_log();
+  assert_eq!(log.len(), 3);
+  assert_eq!(log[0].op, SubscriptionOpKind::ModeChange);
+  assert_eq!(log[2].tokens, [fixtures::MCX_FUTURE]);
+}
@@ -0,0 +1,317 @@
+//! Decoding of the spec-built packets in `common::fixtures`, one packet at
+//! a time and as whole frames read off a socket
+
+mod common;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use common::fixtures::{self, Tradable};
+use common::MockServer;
+use kiteticker_async_manager::{
+  as_tick_raw, Exchange, KiteTickerAsync, KiteTickerConfig,
+  KiteTickerSubscriber, Mode, Tick, TickMessage, TickerMessage,
+};
+
+fn assert_price(actual: Option<f64>, expected: f64) {
+  let actual = actual.expect("price missing");
+  assert!(
+    (actual - expected).abs() < 1e-9,
+    "expected {expected}, got {actual}"
+  );
+}
+
+fn decode(packet: &[u8]) -> Tick {
+  Tick::try_from(packet).expect("fixture packet must decode")
+}
+
+/// Header fields shared by every tradable fixture, scaled by 100
+fn assert_tradable_quote(tick: &Tick, raw: &Tradable) {
+  let scale = |p: i32| p as f64 / 100.0;
+  assert_eq!(tick.instrument_token, raw.token);
+  assert!(tick.is_tradable && !tick.is_index);
+  assert_price(tick.last_price, scale(raw.last_price));
+  assert_eq!(tick.last_traded_qty, Some(raw.last_qty));
+  assert_price(tick.avg_traded_price, scale(raw.avg_price));
+  assert_eq!(tick.volume_traded, Some(raw.volume));
+  assert_eq!(tick.total_buy_qty, Some(raw.buy_qty));
+  assert_eq!(tick.total_sell_qty, Some(raw.sell_qty));
+  let ohlc = tick.ohlc.as_ref().expect("quote carries ohlc");
+  assert_price(Some(ohlc.open), scale(raw.ohlc[0]));
+  assert_price(Some(ohlc.high), scale(raw.ohlc[1]));
+  assert_price(Some(ohlc.low), scale(raw.ohlc[2]));
+  assert_price(Some(ohlc.close), scale(raw.ohlc[3]));
+}
+
+#[test]
+fn ltp_packets_carry_token_and_price_only() {
+  let cases = [
+    (fixtures::nse_equity().ltp(), Exchange::NSE, 1520.35),
+    (fixtures::nfo_future().ltp(), Exchange::NFO, 19850.50),
+    (fixtures::mcx_future().ltp(), Exchange::MCX, 6415.00),
+    (fixtures::nifty_50().ltp(), Exchange::INDICES, 19850.30),
+  ];
+  for (packet, exchange, last_price) in cases {
+    let tick = decode(&packet);
+    assert_eq!(tick.mode, Mode::LTP);
+    assert_eq!(tick.exchange, exchange);
+    assert_price(tick.last_price, last_price);
+    assert!(tick.ohlc.is_none() && tick.depth.is_none());
+  }
+}
+
+#[test]
+fn quote_packets_decode_every_header_field() {
+  for raw in [
+    fixtures::nse_equity(),
+    fixtures::nfo_future(),
+    fixtures::mcx_future(),
+  ] {
+    let tick = decode(&raw.quote());
+    assert_eq!(tick.mode, Mode::Quote);
+    assert_tradable_quote(&tick, &raw);
+    assert!(tick.depth.is_none() && tick.exchange_timestamp.is_none());
+  }
+}
+
+#[test]
+fn full_packets_decode_times_open_interest_and_depth() {
+  let cases = [
+    (fixtures::nse_equity(), None),
+    (
+      fixtures::nfo_future(),
+      Some((12_500_000, 12_800_000, 12_100_000)),
+    ),
+    (fixtures::mcx_future(), Some((15_432, 15_900, 15_010))),
+  ];
+  for (raw, oi) in cases {
+    let tick = decode(&raw.full());
+    assert_eq!(tick.mode, Mode::Full);
+    assert_tradable_quote(&tick, &raw);
+    assert_eq!(
+      tick.last_traded_timestamp,
+      Some(Duration::from_secs(1_700_000_000))
+    );
+    assert_eq!(
+      tick.exchange_timestamp,
+      Some(Duration::from_secs(1_700_000_001))
+    );
+    assert_eq!(
+      (tick.oi, tick.oi_day_high, tick.oi_day_low),
+      (oi.map(|o| o.0), oi.map(|o| o.1), oi.map(|o| o.2))
+    );
+    let depth = tick.depth.as_ref().expect("full carries depth");
+    for (level, &(qty, price, orders)) in depth.buy.iter().zip(&raw.buy) {
+      assert_eq!((level.qty, level.orders), (qty, orders));
+      assert_price(Some(level.price), price as f64 / 100.0);
+    }
+    for (level, &(qty, price, orders)) in depth.sell.iter().zip(&raw.sell) {
+      assert_eq!((level.qty, level.orders), (qty, orders));
+      assert_price(Some(level.price), price as f64 / 100.0);
+    }
+  }
+}
+
+#[test]
+fn open_interest_accessors_only_answer_for_derivatives() {
+  // Nonzero OI slots on a cash segment are not open interest
+  let mut raw = fixtures::nse_equity();
+  (raw.oi, raw.oi_day_high, raw.oi_day_low) = (1_000, 1_200, 900);
+  let tick = decode(&raw.full());
+  assert_eq!(tick.open_interest(), None);
+  assert_eq!(tick.open_interest_day_high(), None);
+  assert_eq!(tick.open_interest_day_low(), None);
+
+  // Hand-built ticks are filtered as well
+  let mut tick = Tick::default();
+  tick.exchange = Exchange::NSE;
+  tick.oi = Some(1_000);
+  assert_eq!(tick.open_interest(), None);
+
+  for raw in [fixtures::nfo_future(), fixtures::mcx_future()] {
+    let tick = decode(&raw.full());
+    assert_eq!(tick.open_interest(), Some(raw.oi));
+    assert_eq!(tick.open_interest_day_high(), Some(raw.oi_day_high));
+    assert_eq!(tick.open_interest_day_low(), Some(raw.oi_day_low));
+  }
+}
+
+/// Open interest as both decoders read it from `raw`'s Full packet
+fn decoded_oi(raw: &Tradable) -> [(Option<u32>, Option<u32>, Option<u32>); 2] {
+  let packet = raw.full();
+  let zero_copy = Tick::from_raw(&as_tick_raw(&packet).unwrap());
+  [decode(&packet), zero_copy].map(|t| (t.oi, t.oi_day_high, t.oi_day_low))
+}
+
+#[test]
+fn cash_full_packets_ignore_open_interest_bytes() {
+  for mut raw in [fixtures::nse_equity(), fixtures::bse_equity()] {
+    (raw.oi, raw.oi_day_high, raw.oi_day_low) = (1_000, 1_200, 900);
+    assert_eq!(decoded_oi(&raw), [(None, None, None); 2], "{}", raw.token);
+  }
+}
+
+#[test]
+fn every_derivative_segment_decodes_open_interest() {
+  // CDS (segment 3) and BFO (segment 5) share the NFO layout
+  for (segment, exchange) in [(3, Exchange::CDS), (5, Exchange::BFO)] {
+    let raw = Tradable {
+      token: (fixtures::NFO_FUTURE & !0xff) | segment,
+      ..fixtures::nfo_future()
+    };
+    assert_eq!(decode(&raw.full()).exchange, exchange);
+    let oi = (Some(raw.oi), Some(raw.oi_day_high), Some(raw.oi_day_low));
+    assert_eq!(decoded_oi(&raw), [oi; 2], "{exchange:?}");
+  }
+}
+
+#[test]
+fn staleness_follows_the_exchange_timestamp() {
+  let tick = decode(&fixtures::nse_equity().full());
+  // Two minutes after the fixture's exchange time
+  let now = UNIX_EPOCH + Duration::from_secs(1_700_000_001 + 120);
+  assert!(tick.is_stale(Duration::from_secs(60), now));
+  assert!(!tick.is_stale(Duration::from_secs(180), now));
+
+  // No exchange timestamp below Full mode: never stale
+  let tick = decode(&fixtures::nse_equity().quote());
+  assert!(!tick.is_stale(Duration::from_secs(60), now));
+}
+
+#[test]
+fn full_packet_net_change_is_against_previous_close() {
+  let tick = decode(&fixtures::nse_equity().full());
+  // 1520.35 - 1508.00
+  assert_price(tick.net_change, 12.35);
+}
+
+#[test]
+fn index_quote_packet_reorders_hloc() {
+  // Indices send high, low, open, close; a past bug read them as OHLC
+  let tick = decode(&fixtures::nifty_50().quote());
+  assert_eq!(tick.mode, Mode::Quote);
+  assert!(tick.is_index && !tick.is_tradable);
+  assert_price(tick.last_price, 19850.30);
+  let ohlc = tick.ohlc.as_ref().unwrap();
+  assert_price(Some(ohlc.open), 19800.00);
+  assert_price(Some(ohlc.high), 19900.00);
+  assert_price(Some(ohlc.low), 19750.00);
+  assert_price(Some(ohlc.close), 19700.00);
+  assert_price(tick.net_change, 150.30);
+  assert_eq!(tick.exchange_timestamp, None);
+}
+
+#[test]
+fn index_full_packet_adds_exchange_time() {
+  let tick = decode(&fixtures::nifty_50().full());
+  assert_eq!(tick.mode, Mode::Full);
+  assert_price(tick.ohlc.as_ref().map(|o| o.open), 19800.00);
+  assert_eq!(
+    tick.exchange_timestamp,
+    Some(Duration::from_secs(1_700_000_001))
+  );
+  assert!(tick.depth.is_none());
+}
+
+#[test]
+fn packets_of_unknown_length_are_rejected() {
+  let full = fixtures::nse_equity().full();
+  for len in [0, 4, 7, 9, 30, 43, 45, 100, 183, 185] {
+    let mut packet = full.clone();
+    packet.resize(len, 0);
+    assert!(Tick::try_from(&packet[..]).is_err(), "length {len}");
+  }
+}
+
+async fn connect(
+  server: &MockServer,
+) -> (KiteTickerAsync, KiteTickerSubscriber) {
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  let subscriber = ticker.subscribe(&[], None).await.unwrap();
+  server.wait_for_clients(1).await;
+  (ticker, subscriber)
+}
+
+async fn next_ticks(subscriber: &mut KiteTickerSubscriber) -> Vec<TickMessage> {
+  loop {
+    let message = tokio::time::timeout(common::WAIT, subscriber.next_message())
+      .await
+      .expect("no ticks within the wait")
+      .unwrap()
+      .expect("stream closed");
+    if let TickerMessage::Ticks(ticks) = message {
+      return ticks;
+    }
+  }
+}
+
+#[tokio::test]
+async fn mixed_mode_frame_yields_ticks_in_packet_order() {
+  let server = MockServer::start().await;
+  let (_ticker, mut subscriber) = connect(&server).await;
+
+  server.send_frame(
+    0,
+    fixtures::frame(&[
+      fixtures::nse_equity().ltp(),
+      fixtures::nfo_future().quote(),
+      fixtures::mcx_future().full(),
+      fixtures::nifty_50().quote(),
+      fixtures::nifty_50().full(),
+    ]),
+  );
+
+  let ticks = next_ticks(&mut subscriber).await;
+  let decoded: Vec<(u32, Mode)> = ticks
+    .iter()
+    .map(|t| (t.instrument_token, t.content.mode))
+    .collect();
+  assert_eq!(
+    decoded,
+    [
+      (fixtures::NSE_EQUITY, Mode::LTP),
+      (fixtures::NFO_FUTURE, Mode::Quote),
+      (fixtures::MCX_FUTURE, Mode::Full),
+      (fixtures::NIFTY_50, Mode::Quote),
+      (fixtures::NIFTY_50, Mode::Full),
+    ]
+  );
+  assert_eq!(ticks[2].content, decode(&fixtures::mcx_future().full()));
+}
+
+#[tokio::test]
+async fn truncated_frame_keeps_complete_packets_and_counts_the_rest() {
+  let server = MockServer::start().await;
+  let (ticker, mut subscriber) = connect(&server).await;
+
+  // Second packet loses its last 10 bytes
+  let frame = fixtures::frame(&[
+    fixtures::nse_equity().quote(),
+    fixtures::nse_equity().full(),
+  ]);
+  server.send_frame(0, fixtures::truncated(frame, 10));
+  server.send_frame(0, fixtures::frame(&[fixtures::nifty_50().ltp()]));
+
+  let first = next_ticks(&mut subscriber).await;
+  assert_eq!(first.len(), 1);
+  assert_eq!(first[0].content.mode, Mode::Quote);
+  let second = next_ticks(&mut subscriber).await;
+  assert_eq!(second[0].instrument_token, fixtures::NIFTY_50);
+  assert_eq!(ticker.parse_counts(), (2, 1));
+}
+
+#[tokio::test]
+async fn heartbeats_produce_no_ticks() {
+  let server = MockServer::start().await;
+  let (ticker, mut subscriber) = connect(&server).await;
+
+  server.send_frame(0, fixtures::HEARTBEAT.to_vec());
+  server.send_frame(0, fixtures::frame(&[fixtures::nse_equity().ltp()]));
+
+  let ticks = next_ticks(&mut subscriber).await;
+  assert_eq!(ticks[0].instrument_token, fixtures::NSE_EQUITY);
+  assert_eq!(ticker.parse_counts(), (1, 0));
+}
@@ -0,0 +1,54 @@
+//! Reader-to-parser queue depth under a burst
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{
+  KiteTickerAsync, KiteTickerConfig, Mode, TickHandler,
+};
+
+const BURST: usize = 20;
+
+/// Frames of a burst that reach a parser taking 20ms per frame
+async fn frames_parsed(capacity: usize) -> usize {
+  let server = MockServer::start().await;
+  let handled = Arc::new(AtomicUsize::new(0));
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  config.parse_channel_capacity = capacity;
+  config.on_ticks = Some(TickHandler::new({
+    let handled = Arc::clone(&handled);
+    move |_| {
+      std::thread::sleep(Duration::from_millis(20));
+      handled.fetch_add(1, Ordering::Relaxed);
+    }
+  }));
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  let frame = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  for _ in 0..BURST {
+    server.send_frame(0, frame.clone());
+  }
+  // Room for the whole burst to be parsed twice over
+  tokio::time::sleep(Duration::from_millis(20 * BURST as u64 * 2)).await;
+  handled.load(Ordering::Relaxed)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn larger_capacity_drops_fewer_frames() {
+  let small = frames_parsed(1).await;
+  let large = frames_parsed(2 * BURST).await;
+  assert_eq!(large, BURST);
+  assert!(small < BURST, "capacity 1 parsed {small} of {BURST}");
+}
@@ -0,0 +1,91 @@
+//! `preview_subscribe` against what `subscribe_symbols` actually sends
+
+mod common;
+
+use common::MockServer;
+use kiteticker_async_manager::{
+  KiteTickerManager, KiteTickerManagerBuilder, Mode,
+};
+use serde_json::{json, Value};
+
+async fn started(server: &MockServer) -> KiteTickerManager {
+  let mut config = server.manager_config(2);
+  config.per_connection_symbol_caps = vec![2, 2];
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  manager
+}
+
+fn parsed(preview: Vec<String>) -> Vec<Value> {
+  preview
+    .iter()
+    .map(|command| serde_json::from_str(command).unwrap())
+    .collect()
+}
+
+#[tokio::test]
+async fn preview_matches_the_commands_sent() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+
+  let preview = manager
+    .preview_subscribe(&[408065, 884737, 738561], Some(Mode::Full))
+    .unwrap();
+  // Previewing leaves the router where it was
+  let again = manager
+    .preview_subscribe(&[408065, 884737, 738561], Some(Mode::Full))
+    .unwrap();
+  assert_eq!(preview, again);
+  assert_eq!(
+    parsed(preview),
+    [
+      json!({"a": "subscribe", "v": [408065, 738561]}),
+      json!({"a": "mode", "v": ["full", [408065, 738561]]}),
+      json!({"a": "subscribe", "v": [884737]}),
+      json!({"a": "mode", "v": ["full", [884737]]}),
+    ]
+  );
+
+  manager
+    .subscribe_symbols(&[408065, 884737, 738561], Some(Mode::Full))
+    .await
+    .unwrap();
+  server.wait_until(|| server.commands().len() == 4).await;
+  for (client, tokens) in [(0, vec![408065, 738561]), (1, vec![884737])] {
+    assert_eq!(server.subscribed(client), tokens);
+  }
+}
+
+#[tokio::test]
+async fn preview_respects_per_connection_caps() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  manager
+    .subscribe_symbols(&[408065, 884737, 738561], Some(Mode::LTP))
+    .await
+    .unwrap();
+
+  // Already subscribed tokens are skipped; one slot is left
+  let preview = manager
+    .preview_subscribe(&[408065, 2953217], Some(Mode::LTP))
+    .unwrap();
+  assert_eq!(
+    parsed(preview),
+    [
+      json!({"a": "subscribe", "v": [2953217]}),
+      json!({"a": "mode", "v": ["ltp", [2953217]]}),
+    ]
+  );
+
+  // Two new tokens do not fit, in the preview or for real
+  assert!(manager
+    .preview_subscribe(&[2953217, 341249], Some(Mode::LTP))
+    .is_err());
+  assert!(manager
+    .subscribe_symbols(&[2953217, 341249], Some(Mode::LTP))
+    .await
+    .is_err());
+  assert_eq!(manager.get_subscriptions().len(), 3);
+}
@@ -0,0 +1,89 @@
+//! Priority symbols and their dedicated channel
+
+mod common;
+
+use common::fixtures;
+use common::{MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManager, KiteTickerManagerBuilder, Mode, TickerMessage,
+};
+use tokio::sync::broadcast;
+
+async fn started(server: &MockServer) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(
+      &[fixtures::NSE_EQUITY, fixtures::NFO_FUTURE],
+      Some(Mode::LTP),
+    )
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+  manager.set_priority_symbols(&[fixtures::NSE_EQUITY]);
+  manager
+}
+
+fn send_both(server: &MockServer) {
+  server.send_frame(
+    0,
+    fixtures::frame(&[
+      fixtures::nse_equity().ltp(),
+      fixtures::nfo_future().ltp(),
+    ]),
+  );
+}
+
+async fn next_tokens(
+  receiver: &mut broadcast::Receiver<TickerMessage>,
+) -> Vec<u32> {
+  tokio::time::timeout(WAIT, async {
+    loop {
+      if let TickerMessage::Ticks(ticks) = receiver.recv().await.unwrap() {
+        return ticks.iter().map(|t| t.instrument_token).collect();
+      }
+    }
+  })
+  .await
+  .expect("no ticks within the wait")
+}
+
+#[tokio::test]
+async fn priority_ticks_use_the_dedicated_channel() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut normal = manager.get_channel(ChannelId::Connection1).unwrap();
+  let mut priority = manager.priority_channel().unwrap();
+  assert!(manager.priority_channel().is_none());
+
+  send_both(&server);
+  let tick = tokio::time::timeout(WAIT, priority.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(tick.instrument_token, fixtures::NSE_EQUITY);
+  assert_eq!(next_tokens(&mut normal).await, [fixtures::NFO_FUTURE]);
+}
+
+#[tokio::test]
+async fn priority_ticks_stay_on_the_normal_path_without_a_reader() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut normal = manager.get_channel(ChannelId::Connection1).unwrap();
+
+  // Channel never taken
+  send_both(&server);
+  assert_eq!(
+    next_tokens(&mut normal).await,
+    [fixtures::NSE_EQUITY, fixtures::NFO_FUTURE]
+  );
+
+  // Channel taken, then dropped
+  drop(manager.priority_channel().unwrap());
+  send_both(&server);
+  let mut tokens = next_tokens(&mut normal).await;
+  tokens.sort_unstable();
+  assert_eq!(tokens, [fixtures::NSE_EQUITY, fixtures::NFO_FUTURE]);
+}
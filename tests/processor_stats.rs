@@ -0,0 +1,108 @@
+//! Per-channel processor statistics
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::manager::ModeBreakdown;
+use kiteticker_async_manager::{ChannelId, KiteTickerManagerBuilder, Mode};
+
+#[tokio::test]
+async fn stats_for_a_channel_count_only_its_messages() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(2))
+    .build();
+  manager.start().await.unwrap();
+  let assigned = manager
+    .subscribe_symbols_assigned(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  let busy = assigned[&fixtures::NSE_EQUITY];
+  let idle = ChannelId::all()
+    .into_iter()
+    .take(2)
+    .find(|&c| c != busy)
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+  let client = (0..2)
+    .find(|&c| server.subscribed(c).contains(&fixtures::NSE_EQUITY))
+    .unwrap();
+
+  let frame = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  for _ in 0..3 {
+    server.send_frame(client, frame.clone());
+  }
+  // Stats are flushed once a second, on the next message
+  tokio::time::sleep(Duration::from_millis(1_100)).await;
+  server.send_frame(client, frame);
+  tokio::time::timeout(WAIT, async {
+    loop {
+      let stats = manager.get_processor_stats_for(busy).await.unwrap();
+      if stats.messages_processed == 4 {
+        break;
+      }
+      tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+  })
+  .await
+  .expect("messages not processed");
+
+  let busy_stats = manager.get_processor_stats_for(busy).await.unwrap();
+  let idle_stats = manager.get_processor_stats_for(idle).await.unwrap();
+  assert_eq!(idle_stats.messages_processed, 0);
+  // Same numbers as the full listing
+  let listed = manager.get_processor_stats().await;
+  let listed_busy = listed.iter().find(|(c, _)| *c == busy).unwrap();
+  assert_eq!(
+    listed_busy.1.messages_processed,
+    busy_stats.messages_processed
+  );
+
+  // Only two connections were started
+  assert!(manager
+    .get_processor_stats_for(ChannelId::Connection3)
+    .await
+    .is_none());
+}
+
+#[tokio::test]
+async fn stats_include_each_connections_mode_breakdown() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(2))
+    .build();
+  manager.start().await.unwrap();
+  let full = manager
+    .subscribe_symbols_assigned(&[fixtures::NSE_EQUITY], Some(Mode::Full))
+    .await
+    .unwrap()[&fixtures::NSE_EQUITY];
+  let quote = manager
+    .subscribe_symbols_assigned(
+      &[fixtures::NFO_FUTURE, fixtures::NIFTY_50],
+      Some(Mode::Quote),
+    )
+    .await
+    .unwrap();
+  manager
+    .change_mode(&[fixtures::NIFTY_50], Mode::LTP)
+    .await
+    .unwrap();
+
+  let mut expected: HashMap<ChannelId, ModeBreakdown> = HashMap::new();
+  expected.entry(full).or_default().full += 1;
+  expected
+    .entry(quote[&fixtures::NFO_FUTURE])
+    .or_default()
+    .quote += 1;
+  expected.entry(quote[&fixtures::NIFTY_50]).or_default().ltp += 1;
+
+  for (channel, stats) in manager.get_processor_stats().await {
+    let breakdown = expected.get(&channel).copied().unwrap_or_default();
+    assert_eq!(stats.symbols_by_mode, breakdown, "{channel:?}");
+    let single = manager.get_processor_stats_for(channel).await.unwrap();
+    assert_eq!(single.symbols_by_mode, breakdown, "{channel:?}");
+  }
+}
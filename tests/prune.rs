@@ -0,0 +1,170 @@
+//! `auto_prune_inactive`: silent tokens pruned while their session is open
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{NaiveTime, Utc, Weekday};
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, Exchange, KiteTickerManager, KiteTickerManagerBuilder,
+  MarketSession, Mode, SubscriptionState,
+};
+
+const GRACE: Duration = Duration::from_millis(1500);
+/// A silent NSE token
+const QUIET: u32 = 738_561;
+
+/// NSE trading around the clock, every day
+fn always_open() -> MarketSession {
+  let end = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+  MarketSession::new(NaiveTime::MIN, end).with_weekdays(&[
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+  ])
+}
+
+fn state_path(name: &str) -> PathBuf {
+  let path = std::env::temp_dir().join(format!(
+    "kiteticker-prune-{}-{}.json",
+    name,
+    std::process::id()
+  ));
+  let _ = std::fs::remove_file(&path);
+  path
+}
+
+async fn started(
+  server: &MockServer,
+  nse: MarketSession,
+  state_file: &Path,
+) -> KiteTickerManager {
+  let mut config = server.manager_config(1);
+  // Only NSE has a session: BSE tokens are never pruned
+  config.market_sessions.clear();
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .auto_prune_inactive(GRACE)
+    .market_session(Exchange::NSE, nse)
+    .state_file(state_file)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(
+      &[fixtures::NSE_EQUITY, QUIET, fixtures::BSE_EQUITY],
+      Some(Mode::LTP),
+    )
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+  manager
+}
+
+#[tokio::test]
+async fn silent_tokens_are_pruned_automatically() {
+  let server = MockServer::start().await;
+  let path = state_path("timer");
+  let manager = started(&server, always_open(), &path).await;
+  let mut events = manager.prune_events();
+
+  // Only the first token ticks, until the timer prunes the quiet one
+  let frame = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  let event = tokio::time::timeout(GRACE + WAIT, async {
+    loop {
+      server.send_frame(0, frame.clone());
+      tokio::select! {
+        event = events.recv() => return event.unwrap(),
+        _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+      }
+    }
+  })
+  .await
+  .expect("nothing pruned within the wait");
+  assert_eq!(event.tokens, [QUIET]);
+
+  // The timer itself unsubscribed it on the socket and in the state file
+  let unsubscribe = server
+    .wait_for_command(|c| c.action() == "unsubscribe")
+    .await;
+  assert_eq!(unsubscribe.tokens(), [QUIET]);
+  assert!(!manager.is_subscribed(QUIET));
+  assert_eq!(manager.subscribed_count(), 2);
+  assert!(!manager.get_subscriptions().contains_key(&QUIET));
+  let saved = SubscriptionState::load(&path).await;
+  let mut tokens: Vec<u32> = saved
+    .subscriptions
+    .iter()
+    .map(|entry| entry.instrument_token)
+    .collect();
+  tokens.sort_unstable();
+  assert_eq!(tokens, [fixtures::NSE_EQUITY, fixtures::BSE_EQUITY]);
+  let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn pruned_tokens_are_not_resubscribed_on_reconnect() {
+  let server = MockServer::start().await;
+  let path = state_path("reconnect");
+  let mut manager = started(&server, always_open(), &path).await;
+  let mut events = manager.prune_events();
+  tokio::time::timeout(GRACE + WAIT, events.recv())
+    .await
+    .expect("nothing pruned within the wait")
+    .unwrap();
+
+  manager.reconnect(ChannelId::Connection1).await.unwrap();
+  let subscribe = server
+    .wait_for_command(|c| c.client == 1 && c.action() == "subscribe")
+    .await;
+  assert!(!subscribe.tokens().contains(&QUIET));
+  assert!(!manager
+    .get_symbol_distribution()
+    .values()
+    .flatten()
+    .any(|&t| t == QUIET));
+  let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn nothing_is_pruned_on_a_holiday() {
+  let server = MockServer::start().await;
+  let today = Utc::now()
+    .with_timezone(&chrono::FixedOffset::east_opt(19_800).unwrap())
+    .date_naive();
+  let session = always_open().with_holidays([today]);
+  let path = state_path("holiday");
+  let mut manager = started(&server, session, &path).await;
+  let mut events = manager.prune_events();
+
+  tokio::time::sleep(GRACE + Duration::from_millis(500)).await;
+  assert_eq!(manager.prune_inactive().await.unwrap(), Vec::<u32>::new());
+  assert!(events.try_recv().is_err());
+  assert_eq!(manager.subscribed_count(), 3);
+  let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn session_open_time_counts_from_the_open() {
+  let open = NaiveTime::from_hms_opt(9, 15, 0).unwrap();
+  let close = NaiveTime::from_hms_opt(15, 30, 0).unwrap();
+  let session = MarketSession::new(open, close);
+  // Wednesday 2025-01-15, 10:15 IST
+  let at = |h, m| {
+    chrono::NaiveDate::from_ymd_opt(2025, 1, 15)
+      .unwrap()
+      .and_hms_opt(h, m, 0)
+      .unwrap()
+      .and_utc()
+  };
+  assert_eq!(session.open_for(at(4, 45)), Some(Duration::from_secs(3600)));
+  // 15:30 IST: closed
+  assert_eq!(session.open_for(at(10, 0)), None);
+  let holiday = chrono::NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+  assert_eq!(session.with_holidays([holiday]).open_for(at(4, 45)), None);
+}
@@ -0,0 +1,50 @@
+//! `Tick::to_kite_quote_json` against the `/quote` REST shape
+
+mod common;
+
+use common::fixtures;
+use kiteticker_async_manager::Tick;
+use serde_json::json;
+
+#[test]
+fn full_tick_serializes_like_a_rest_quote() {
+  let tick = Tick::try_from(&fixtures::nse_equity().full()[..]).unwrap();
+  let quote = tick.to_kite_quote_json();
+
+  let mut keys: Vec<&str> = quote
+    .as_object()
+    .unwrap()
+    .keys()
+    .map(String::as_str)
+    .collect();
+  keys.sort_unstable();
+  // Only fields the binary feed carries; circuit limits come from the
+  // REST quote API alone
+  assert_eq!(
+    keys,
+    [
+      "average_price",
+      "buy_quantity",
+      "depth",
+      "instrument_token",
+      "last_price",
+      "last_quantity",
+      "last_trade_time",
+      "net_change",
+      "ohlc",
+      "oi",
+      "oi_day_high",
+      "oi_day_low",
+      "sell_quantity",
+      "timestamp",
+      "volume",
+    ]
+  );
+  assert_eq!(quote["last_price"], json!(1520.35));
+  assert_eq!(quote["last_trade_time"], json!("2023-11-15 03:43:20"));
+  assert_eq!(
+    quote["depth"]["buy"][0],
+    json!({"price": 1520.3, "quantity": 1200, "orders": 7})
+  );
+  assert_eq!(quote["depth"]["sell"].as_array().unwrap().len(), 5);
+}
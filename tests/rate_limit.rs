@@ -0,0 +1,63 @@
+//! Per-token tick rate limiting
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManagerBuilder, Mode, RateLimitedReceiver,
+  TickRateLimiter, TickerMessage,
+};
+
+#[test]
+fn zero_rate_is_a_config_error() {
+  assert!(TickRateLimiter::new(0).is_err());
+  let (_tx, rx) = tokio::sync::broadcast::channel::<TickerMessage>(1);
+  assert!(RateLimitedReceiver::new(rx, 0).is_err());
+}
+
+#[test]
+fn ticks_pass_once_per_interval_per_token() {
+  let mut limiter = TickRateLimiter::new(4).unwrap();
+  let start = Instant::now();
+  assert!(limiter.allow(fixtures::NSE_EQUITY, start));
+  assert!(limiter.allow(fixtures::BSE_EQUITY, start));
+  assert!(!limiter.allow(fixtures::NSE_EQUITY, start));
+  assert!(
+    !limiter.allow(fixtures::NSE_EQUITY, start + Duration::from_millis(249))
+  );
+  assert!(
+    limiter.allow(fixtures::NSE_EQUITY, start + Duration::from_millis(250))
+  );
+  assert_eq!(limiter.dropped(), 2);
+}
+
+#[tokio::test]
+async fn receiver_drops_excess_ticks() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+  let channel = manager.get_channel(ChannelId::Connection1).unwrap();
+  let mut receiver = RateLimitedReceiver::new(channel, 1).unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+
+  let frame = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  for _ in 0..3 {
+    server.send_frame(0, frame.clone());
+  }
+  let first = tokio::time::timeout(WAIT, receiver.recv()).await.unwrap();
+  assert!(matches!(first, Ok(TickerMessage::Ticks(ticks)) if ticks.len() == 1));
+  // The other two arrive within the second and are dropped
+  assert!(
+    tokio::time::timeout(Duration::from_millis(300), receiver.recv())
+      .await
+      .is_err()
+  );
+  assert_eq!(receiver.dropped(), 2);
+}
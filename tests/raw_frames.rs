@@ -0,0 +1,159 @@
+//! Raw frame access: the latest frame, timed subscribers and the manager's
+//! merged channel
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  KiteTickerAsync, KiteTickerConfig, KiteTickerManagerBuilder, Mode,
+  TickerMessage,
+};
+use tokio::sync::broadcast;
+
+async fn connect(server: &MockServer, raw_only: bool) -> KiteTickerAsync {
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  config.raw_only = raw_only;
+  let ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  server.wait_for_clients(1).await;
+  ticker
+}
+
+async fn next_frame(frames: &mut broadcast::Receiver<bytes::Bytes>) -> Vec<u8> {
+  tokio::time::timeout(WAIT, frames.recv())
+    .await
+    .expect("no frame within the wait")
+    .unwrap()
+    .to_vec()
+}
+
+#[tokio::test]
+async fn latest_raw_frame_is_the_most_recent_one() {
+  let server = MockServer::start().await;
+  let ticker = connect(&server, true).await;
+  let mut frames = ticker.subscribe_raw_frames();
+  assert_eq!(ticker.latest_raw_frame(), None);
+
+  let first = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  let second = fixtures::frame(&[fixtures::nfo_future().quote()]);
+  server.send_frame(0, first.clone());
+  assert_eq!(next_frame(&mut frames).await, first);
+  assert_eq!(ticker.latest_raw_frame().unwrap(), first);
+
+  server.send_frame(0, second.clone());
+  assert_eq!(next_frame(&mut frames).await, second);
+  assert_eq!(ticker.latest_raw_frame().unwrap(), second);
+}
+
+#[tokio::test]
+async fn frames_without_raw_consumers_are_not_queued() {
+  let server = MockServer::start().await;
+  let mut ticker = connect(&server, false).await;
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  // Parsed consumers and the latest frame still see it
+  let unseen = fixtures::frame(&[fixtures::nse_equity().ltp()]);
+  server.send_frame(0, unseen.clone());
+  tokio::time::timeout(WAIT, async {
+    while !matches!(
+      subscriber.next_message().await.unwrap(),
+      Some(TickerMessage::Ticks(_))
+    ) {}
+  })
+  .await
+  .expect("no ticks");
+  assert_eq!(ticker.latest_raw_frame().unwrap(), unseen);
+
+  // A raw consumer starts with the next frame
+  let mut frames = ticker.subscribe_raw_frames();
+  assert!(frames.is_empty());
+  let seen = fixtures::frame(&[fixtures::nfo_future().ltp()]);
+  server.send_frame(0, seen.clone());
+  assert_eq!(next_frame(&mut frames).await, seen);
+  assert!(frames.is_empty());
+}
+
+#[tokio::test]
+async fn timed_frames_are_stamped_in_order_on_arrival() {
+  let server = MockServer::start().await;
+  let ticker = connect(&server, true).await;
+  let mut timed = ticker.subscribe_raw_frames_timed();
+
+  let frames = [
+    fixtures::frame(&[fixtures::nse_equity().ltp()]),
+    fixtures::frame(&[fixtures::nfo_future().quote()]),
+    fixtures::frame(&[fixtures::bse_equity().full()]),
+  ];
+  let sent = Instant::now();
+  for frame in &frames {
+    server.send_frame(0, frame.clone());
+  }
+  let mut stamps = Vec::new();
+  for frame in &frames {
+    let (stamp, bytes) = tokio::time::timeout(WAIT, timed.recv())
+      .await
+      .expect("no frame within the wait")
+      .unwrap();
+    assert_eq!(bytes.to_vec(), *frame);
+    assert!(stamp >= sent && stamp <= Instant::now());
+    stamps.push(stamp);
+  }
+  assert!(stamps.windows(2).all(|w| w[0] <= w[1]));
+  assert!(stamps[2] - sent < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn merged_raw_frames_carry_their_channel() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(2))
+    .build();
+  manager.start().await.unwrap();
+  let assigned = manager
+    .subscribe_symbols_assigned(
+      &[fixtures::NSE_EQUITY, fixtures::NFO_FUTURE],
+      Some(Mode::LTP),
+    )
+    .await
+    .unwrap();
+  assert_ne!(
+    assigned[&fixtures::NSE_EQUITY],
+    assigned[&fixtures::NFO_FUTURE]
+  );
+  let mut merged = manager.get_raw_merged_channel();
+
+  let mut expected = HashMap::new();
+  for (token, packet) in [
+    (fixtures::NSE_EQUITY, fixtures::nse_equity().ltp()),
+    (fixtures::NFO_FUTURE, fixtures::nfo_future().ltp()),
+  ] {
+    server
+      .wait_until(|| (0..2).any(|c| server.subscribed(c).contains(&token)))
+      .await;
+    let client = (0..2)
+      .find(|&c| server.subscribed(c).contains(&token))
+      .unwrap();
+    let frame = fixtures::frame(&[packet]);
+    server.send_frame(client, frame.clone());
+    expected.insert(frame, assigned[&token]);
+  }
+
+  let mut seen = HashMap::new();
+  while seen.len() < 2 {
+    let (channel, frame) = tokio::time::timeout(WAIT, merged.recv())
+      .await
+      .expect("no merged frame within the wait")
+      .unwrap();
+    seen.insert(frame.to_vec(), channel);
+  }
+  assert_eq!(seen, expected);
+}
@@ -0,0 +1,73 @@
+//! Warning when parsed channels are taken from a raw-only manager
+
+mod common;
+
+use std::sync::Mutex;
+
+use common::MockServer;
+use kiteticker_async_manager::{ChannelId, KiteTickerManagerBuilder};
+
+/// Keeps the crate's warnings so the test can look for them
+struct Capture(Mutex<Vec<String>>);
+
+impl log::Log for Capture {
+  fn enabled(&self, metadata: &log::Metadata) -> bool {
+    metadata.level() <= log::Level::Warn
+  }
+
+  fn log(&self, record: &log::Record) {
+    if self.enabled(record.metadata())
+      && record.target().starts_with("kiteticker_async_manager")
+    {
+      self.0.lock().unwrap().push(record.args().to_string());
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+static LOGS: Capture = Capture(Mutex::new(Vec::new()));
+
+fn raw_only_warnings() -> Vec<String> {
+  let mut logs = LOGS.0.lock().unwrap();
+  logs.drain(..).filter(|l| l.contains("raw-only")).collect()
+}
+
+#[tokio::test]
+async fn parsed_channels_on_a_raw_only_manager_warn() {
+  log::set_logger(&LOGS).unwrap();
+  log::set_max_level(log::LevelFilter::Warn);
+  let server = MockServer::start().await;
+
+  // A parsed manager hands out its channels quietly
+  let mut parsed = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  parsed.start().await.unwrap();
+  assert!(!parsed.is_raw_only());
+  assert_eq!(parsed.get_all_channels().len(), 1);
+  assert!(raw_only_warnings().is_empty());
+  parsed.stop().await.unwrap();
+
+  let mut raw = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .raw_only(true)
+    .build();
+  raw.start().await.unwrap();
+  assert!(raw.is_raw_only());
+
+  // Raw frame channels are the intended way in
+  assert_eq!(raw.get_all_raw_frame_channels().len(), 1);
+  assert!(raw_only_warnings().is_empty());
+
+  raw.get_all_channels();
+  let warnings = raw_only_warnings();
+  assert_eq!(warnings.len(), 1, "{:?}", warnings);
+  assert!(warnings[0].starts_with("get_all_channels called"));
+  assert!(warnings[0].contains("get_all_raw_frame_channels"));
+
+  raw.get_channel(ChannelId::Connection1);
+  let warnings = raw_only_warnings();
+  assert_eq!(warnings.len(), 1, "{:?}", warnings);
+  assert!(warnings[0].starts_with("get_channel called"));
+}
@@ -0,0 +1,256 @@
+//! Consumer channels across reconnects
+
+mod common;
+
+use std::time::Duration;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  BreakerState, ChannelId, KiteTickerManager, KiteTickerManagerBuilder,
+  LifecycleEventKind, Mode, TickerMessage,
+};
+use tokio::sync::broadcast;
+
+async fn started(server: &MockServer) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server
+    .wait_for_command(|c| c.client == 0 && c.action() == "mode")
+    .await;
+  manager
+}
+
+/// Last price of the next ticks message, skipping anything else
+async fn next_price(receiver: &mut broadcast::Receiver<TickerMessage>) -> f64 {
+  tokio::time::timeout(WAIT, async {
+    loop {
+      if let TickerMessage::Ticks(ticks) = receiver.recv().await.unwrap() {
+        return ticks[0].content.last_price.unwrap();
+      }
+    }
+  })
+  .await
+  .expect("no ticks within the wait")
+}
+
+fn ltp_frame(price: i32) -> Vec<u8> {
+  let mut raw = fixtures::nse_equity();
+  raw.last_price = price;
+  fixtures::frame(&[raw.ltp()])
+}
+
+#[tokio::test]
+async fn receivers_keep_delivering_after_a_reconnect() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  let (_, mut all) = manager.get_all_channels().pop().unwrap();
+
+  server.send_frame(0, ltp_frame(152_035));
+  assert_eq!(next_price(&mut receiver).await, 1520.35);
+  assert_eq!(next_price(&mut all).await, 1520.35);
+
+  manager.reconnect(ChannelId::Connection1).await.unwrap();
+  // The new socket is subscribed again before ticks flow on it
+  server
+    .wait_for_command(|c| c.client == 1 && c.action() == "mode")
+    .await;
+  server.send_frame(1, ltp_frame(152_100));
+  assert_eq!(next_price(&mut receiver).await, 1521.0);
+  assert_eq!(next_price(&mut all).await, 1521.0);
+}
+
+#[tokio::test]
+async fn receivers_survive_a_dropped_socket() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+
+  server.close_client(0, 1001, "");
+  tokio::time::timeout(WAIT, async {
+    loop {
+      let message = receiver.recv().await.unwrap();
+      if matches!(message, TickerMessage::ClosingMessage(_)) {
+        break;
+      }
+    }
+  })
+  .await
+  .expect("no close notice within the wait");
+
+  manager.reconnect(ChannelId::Connection1).await.unwrap();
+  server
+    .wait_for_command(|c| c.client == 1 && c.action() == "mode")
+    .await;
+  server.send_frame(1, ltp_frame(152_100));
+  assert_eq!(next_price(&mut receiver).await, 1521.0);
+}
+
+#[tokio::test]
+async fn breaker_opens_on_repeated_failures_and_cools_down() {
+  let server = MockServer::start().await;
+  let cooldown = Duration::from_millis(300);
+  let mut config = server.manager_config(1);
+  config.max_reconnect_attempts = 5;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .circuit_breaker(3, Duration::from_secs(60), cooldown)
+    .build();
+  manager.start().await.unwrap();
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  let health = manager.get_health().await.unwrap();
+  assert_eq!(health.circuit_breaker, BreakerState::Closed);
+
+  // The third failure opens it, before all five attempts are spent
+  server.reject_with(Some(500));
+  let error = manager
+    .reconnect(ChannelId::Connection1)
+    .await
+    .unwrap_err()
+    .to_string();
+  assert!(error.contains("circuit breaker opened"), "{error}");
+  assert_eq!(server.rejected(), 3);
+  let health = manager.get_health().await.unwrap();
+  assert_eq!(health.circuit_breaker, BreakerState::Open);
+  let notice = tokio::time::timeout(WAIT, async {
+    loop {
+      if let TickerMessage::Error(e) = receiver.recv().await.unwrap() {
+        return e;
+      }
+    }
+  })
+  .await
+  .expect("no error published");
+  assert!(notice.contains("circuit breaker opened"), "{notice}");
+
+  // While open, nothing reaches the server
+  let error = manager
+    .reconnect(ChannelId::Connection1)
+    .await
+    .unwrap_err()
+    .to_string();
+  assert!(error.contains("circuit breaker open"), "{error}");
+  assert_eq!(server.rejected(), 3);
+
+  // After the cooldown a single probe succeeds and closes it again
+  tokio::time::sleep(cooldown).await;
+  server.reject_with(None);
+  manager.reconnect(ChannelId::Connection1).await.unwrap();
+  assert_eq!(server.rejected(), 3);
+  let health = manager.get_health().await.unwrap();
+  assert_eq!(health.circuit_breaker, BreakerState::Closed);
+}
+
+#[tokio::test]
+async fn raw_only_connection_stays_raw_after_a_reconnect() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .raw_only(true)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  let mut parsed = manager.get_channel(ChannelId::Connection1).unwrap();
+
+  manager.reconnect(ChannelId::Connection1).await.unwrap();
+  server
+    .wait_for_command(|c| c.client == 1 && c.action() == "mode")
+    .await;
+  let mut frames = manager
+    .get_raw_frame_channel(ChannelId::Connection1)
+    .unwrap();
+  let frame = ltp_frame(152_035);
+  server.send_frame(1, frame.clone());
+  let received = tokio::time::timeout(WAIT, frames.recv())
+    .await
+    .expect("no raw frame after the reconnect")
+    .unwrap();
+  assert_eq!(received.to_vec(), frame);
+
+  // Still raw-only: the frame was not parsed into ticks
+  tokio::time::sleep(Duration::from_millis(100)).await;
+  while let Ok(message) = parsed.try_recv() {
+    assert!(!matches!(message, TickerMessage::Ticks(_)), "{message:?}");
+  }
+}
+
+#[tokio::test]
+async fn subscribe_while_reconnecting_is_applied_once_back() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(1);
+  config.max_reconnect_attempts = 1;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+
+  // The socket is gone and the reconnect could not replace it yet
+  server.reject_with(Some(500));
+  assert!(manager.reconnect(ChannelId::Connection1).await.is_err());
+  manager
+    .subscribe_symbols(&[fixtures::NFO_FUTURE], Some(Mode::Quote))
+    .await
+    .unwrap();
+  assert!(manager.is_subscribed(fixtures::NFO_FUTURE));
+  assert_eq!(server.client_count(), 1);
+
+  server.reject_with(None);
+  manager.reconnect(ChannelId::Connection1).await.unwrap();
+  server
+    .wait_until(|| {
+      server.subscribed(1) == [fixtures::NSE_EQUITY, fixtures::NFO_FUTURE]
+    })
+    .await;
+  let quote = server
+    .wait_for_command(|c| c.client == 1 && c.mode() == Some("quote"))
+    .await;
+  assert_eq!(quote.tokens(), [fixtures::NFO_FUTURE]);
+}
+
+#[tokio::test]
+async fn close_and_reconnect_are_kept_in_the_lifecycle_log() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let kinds = |manager: &KiteTickerManager| -> Vec<LifecycleEventKind> {
+    manager.lifecycle_log().iter().map(|e| e.kind).collect()
+  };
+  assert_eq!(kinds(&manager), [LifecycleEventKind::Connected]);
+
+  server.close_client(0, 1001, "going away");
+  tokio::time::timeout(WAIT, async {
+    while !kinds(&manager).contains(&LifecycleEventKind::Closed) {
+      tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+  })
+  .await
+  .expect("close not logged");
+  manager.reconnect(ChannelId::Connection1).await.unwrap();
+
+  let log = manager.lifecycle_log();
+  let kinds: Vec<_> = log.iter().map(|e| e.kind).collect();
+  assert_eq!(
+    kinds,
+    [
+      LifecycleEventKind::Connected,
+      LifecycleEventKind::Closed,
+      LifecycleEventKind::Connected,
+      LifecycleEventKind::Reconnected,
+    ]
+  );
+  assert!(log.iter().all(|e| e.channel == ChannelId::Connection1));
+  assert!(log[1].detail.contains("going away"), "{:?}", log[1]);
+  assert!(log.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+}
@@ -0,0 +1,73 @@
+//! Custom symbol routers
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::MockServer;
+use kiteticker_async_manager::manager::ManagedConnection;
+use kiteticker_async_manager::{
+  ChannelId, DistributionView, KiteManagerConfig, KiteTickerManagerBuilder,
+  Mode, SymbolRouter,
+};
+use tokio::sync::mpsc;
+
+/// Even tokens on the first connection, odd ones on the second
+#[derive(Debug)]
+struct ParityRouter;
+
+impl SymbolRouter for ParityRouter {
+  fn route(
+    &mut self,
+    token: u32,
+    _current: &DistributionView<'_>,
+  ) -> Option<ChannelId> {
+    Some(if token.is_multiple_of(2) {
+      ChannelId::Connection1
+    } else {
+      ChannelId::Connection2
+    })
+  }
+}
+
+#[test]
+fn view_counts_pending_symbols_against_caps() {
+  let (tx, _rx) = mpsc::unbounded_channel();
+  let mut connections = vec![
+    ManagedConnection::new(ChannelId::Connection1, tx.clone()),
+    ManagedConnection::new(ChannelId::Connection2, tx),
+  ];
+  connections[0].subscribed_symbols.insert(408065, Mode::LTP);
+  let pending = HashMap::from([(ChannelId::Connection2, vec![884737, 1])]);
+  let mut config = KiteManagerConfig::default();
+  config.per_connection_symbol_caps = vec![10, 2];
+
+  let view = DistributionView::new(&connections, &pending, &config);
+  assert_eq!(view.connection_count(), 2);
+  assert_eq!(view.symbol_count(ChannelId::Connection1), 1);
+  assert_eq!(view.symbol_count(ChannelId::Connection2), 2);
+  assert_eq!(view.capacity(ChannelId::Connection2), 2);
+  // Neither connection has a socket, so neither can take symbols
+  assert_eq!(view.available().count(), 0);
+}
+
+#[tokio::test]
+async fn custom_router_places_symbols() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(2))
+    .router(ParityRouter)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[2, 3, 10, 11, 408064], Some(Mode::LTP))
+    .await
+    .unwrap();
+
+  let mut distribution = manager.get_symbol_distribution();
+  for tokens in distribution.values_mut() {
+    tokens.sort_unstable();
+  }
+  assert_eq!(distribution[&ChannelId::Connection1], [2, 10, 408064]);
+  assert_eq!(distribution[&ChannelId::Connection2], [3, 11]);
+}
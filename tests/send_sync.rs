@@ -0,0 +1,34 @@
+//! Handles are moved into spawned tasks and shared across web handlers
+//! (actix, axum), so losing `Send`/`Sync` must fail the build here rather
+//! than in user code.
+
+use std::sync::Arc;
+
+use kiteticker_async_manager::{
+  CommandSender, HealthSummary, KiteError, KiteManagerConfig, KiteTickerAsync,
+  KiteTickerManager, KiteTickerManagerBuilder, KiteTickerRawSubscriber184,
+  KiteTickerSubscriber, ManagerStats, MultiApiKiteTickerManager,
+  RateLimitedReceiver, ResyncReceiver, TickMessage, TickSink, TickerMessage,
+  WatchlistManager,
+};
+use static_assertions::assert_impl_all;
+use tokio::sync::broadcast;
+
+assert_impl_all!(KiteTickerManager: Send, Sync);
+assert_impl_all!(KiteTickerManagerBuilder: Send, Sync);
+assert_impl_all!(MultiApiKiteTickerManager: Send, Sync);
+assert_impl_all!(WatchlistManager: Send, Sync);
+assert_impl_all!(KiteTickerAsync: Send, Sync);
+assert_impl_all!(KiteTickerSubscriber: Send, Sync);
+assert_impl_all!(KiteTickerRawSubscriber184: Send, Sync);
+assert_impl_all!(CommandSender: Send, Sync);
+assert_impl_all!(ResyncReceiver: Send, Sync);
+assert_impl_all!(RateLimitedReceiver: Send, Sync);
+assert_impl_all!(KiteManagerConfig: Send, Sync);
+assert_impl_all!(ManagerStats: Send, Sync);
+assert_impl_all!(HealthSummary: Send, Sync);
+assert_impl_all!(TickerMessage: Send, Sync);
+assert_impl_all!(TickMessage: Send, Sync);
+assert_impl_all!(KiteError: Send, Sync);
+assert_impl_all!(broadcast::Receiver<TickerMessage>: Send, Sync);
+assert_impl_all!(Arc<dyn TickSink>: Send, Sync);
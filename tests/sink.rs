@@ -0,0 +1,76 @@
+//! Forwarding ticks to an attached sink
+
+mod common;
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::{fixtures, MockServer};
+use futures_util::future::BoxFuture;
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManagerBuilder, Mode, TickMessage, TickSink,
+};
+
+/// Keeps every forwarded tick in memory
+#[derive(Default)]
+struct MemorySink {
+  received: Mutex<Vec<(ChannelId, u32)>>,
+}
+
+impl MemorySink {
+  fn tokens(&self) -> HashSet<u32> {
+    let received = self.received.lock().unwrap();
+    received.iter().map(|(_, token)| *token).collect()
+  }
+}
+
+impl TickSink for MemorySink {
+  fn send<'a>(
+    &'a self,
+    channel: ChannelId,
+    ticks: &'a [TickMessage],
+  ) -> BoxFuture<'a, ()> {
+    Box::pin(async move {
+      let mut received = self.received.lock().unwrap();
+      received.extend(ticks.iter().map(|t| (channel, t.instrument_token)));
+    })
+  }
+}
+
+#[tokio::test]
+async fn sink_receives_the_subscribed_tokens_ticks() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+  let sink = Arc::new(MemorySink::default());
+  manager.attach_sink(sink.clone());
+  manager
+    .subscribe_symbols(
+      &[fixtures::NSE_EQUITY, fixtures::NFO_FUTURE],
+      Some(Mode::Quote),
+    )
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  server.broadcast_frame(fixtures::frame(&[
+    fixtures::nse_equity().quote(),
+    fixtures::nfo_future().quote(),
+  ]));
+  let expected = HashSet::from([fixtures::NSE_EQUITY, fixtures::NFO_FUTURE]);
+  tokio::time::timeout(common::WAIT, async {
+    while sink.tokens() != expected {
+      tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+  })
+  .await
+  .expect("sink did not receive both tokens");
+
+  let received = sink.received.lock().unwrap();
+  assert!(received
+    .iter()
+    .all(|(channel, _)| *channel == ChannelId::Connection1));
+}
@@ -0,0 +1,116 @@
+//! Warning on frames that take longer than `slow_parse_threshold` to parse
+
+mod common;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  KiteTickerAsync, KiteTickerConfig, Mode, TickHandler,
+};
+
+/// Keeps the crate's warnings so the test can look for them
+struct Capture(Mutex<Vec<String>>);
+
+impl log::Log for Capture {
+  fn enabled(&self, metadata: &log::Metadata) -> bool {
+    metadata.level() <= log::Level::Warn
+  }
+
+  fn log(&self, record: &log::Record) {
+    if self.enabled(record.metadata())
+      && record.target().starts_with("kiteticker_async_manager")
+    {
+      self.0.lock().unwrap().push(record.args().to_string());
+    }
+  }
+
+  fn flush(&self) {}
+}
+
+static LOGS: Capture = Capture(Mutex::new(Vec::new()));
+
+fn slow_parse_warnings() -> Vec<String> {
+  let logs = LOGS.0.lock().unwrap();
+  logs
+    .iter()
+    .filter(|l| l.starts_with("Slow parse"))
+    .cloned()
+    .collect()
+}
+
+#[tokio::test]
+async fn frames_over_the_threshold_are_logged() {
+  log::set_logger(&LOGS).unwrap();
+  log::set_max_level(log::LevelFilter::Warn);
+
+  let server = MockServer::start().await;
+  let slow = Arc::new(AtomicBool::new(false));
+  let handled = Arc::new(Mutex::new(0));
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  config.slow_parse_threshold = Some(Duration::from_millis(20));
+  config.on_ticks = Some(TickHandler::new({
+    let slow = Arc::clone(&slow);
+    let handled = Arc::clone(&handled);
+    move |_| {
+      // Stands in for a pathological frame
+      if slow.load(Ordering::Relaxed) {
+        std::thread::sleep(Duration::from_millis(60));
+      }
+      *handled.lock().unwrap() += 1;
+    }
+  }));
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  let handled_count = |n| {
+    let handled = Arc::clone(&handled);
+    async move {
+      tokio::time::timeout(WAIT, async {
+        while *handled.lock().unwrap() < n {
+          tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+      })
+      .await
+      .expect("frame not handled");
+    }
+  };
+
+  // Fast frames stay quiet
+  server.send_frame(0, fixtures::frame(&[fixtures::nse_equity().ltp()]));
+  handled_count(1).await;
+  assert!(slow_parse_warnings().is_empty());
+
+  slow.store(true, Ordering::Relaxed);
+  let frame = fixtures::frame(&[
+    fixtures::nse_equity().ltp(),
+    fixtures::nse_equity().ltp(),
+  ]);
+  let size = frame.len();
+  server.send_frame(0, frame);
+  handled_count(2).await;
+  // Logged once the frame is done
+  tokio::time::timeout(WAIT, async {
+    while slow_parse_warnings().is_empty() {
+      tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+  })
+  .await
+  .expect("no slow parse warning");
+  let warnings = slow_parse_warnings();
+  assert_eq!(warnings.len(), 1, "{:?}", warnings);
+  assert!(
+    warnings[0].ends_with(&format!("for {}-byte frame with 2 packets", size)),
+    "{}",
+    warnings[0]
+  );
+}
@@ -0,0 +1,99 @@
+//! `start()`: how connections come up
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{KiteTickerManagerBuilder, Mode};
+
+const HANDSHAKE: Duration = Duration::from_millis(300);
+
+/// Time `start()` takes with `connections` slow handshakes
+async fn start_time(connections: usize, concurrency: usize) -> Duration {
+  let server = MockServer::start().await;
+  server.delay_handshakes(HANDSHAKE);
+  let mut config = server.manager_config(connections);
+  config.startup_concurrency = concurrency;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  let begun = Instant::now();
+  manager.start().await.unwrap();
+  let elapsed = begun.elapsed();
+  assert_eq!(server.open_clients().len(), connections);
+  elapsed
+}
+
+#[tokio::test]
+async fn connections_come_up_in_parallel() {
+  let single = start_time(1, 3).await;
+  let three = start_time(3, 3).await;
+  assert!(three < single * 2, "single {single:?}, three {three:?}");
+
+  // One at a time, the handshakes add up
+  let sequential = start_time(3, 1).await;
+  assert!(sequential >= HANDSHAKE * 3, "sequential {sequential:?}");
+}
+
+#[tokio::test]
+async fn failed_start_names_the_connection() {
+  let server = MockServer::start().await;
+  server.reject_with(Some(500));
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  let error = manager.start().await.unwrap_err().to_string();
+  assert!(error.contains("WebSocket 0"), "{error}");
+}
+
+#[tokio::test]
+async fn partial_startup_runs_on_the_connections_that_came_up() {
+  let server = MockServer::start().await;
+  server.reject_next(1, 500);
+  let mut config = server.manager_config(3);
+  config.require_all_connections = false;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  assert_eq!(server.rejected(), 1);
+  assert_eq!(server.open_clients().len(), 2);
+
+  let stats = manager.get_stats().await.unwrap();
+  assert_eq!(stats.active_connections, 2);
+
+  // Symbols only go to the live connections
+  let tokens = [
+    fixtures::NSE_EQUITY,
+    fixtures::NFO_FUTURE,
+    fixtures::BSE_EQUITY,
+    fixtures::MCX_FUTURE,
+  ];
+  manager
+    .subscribe_symbols(&tokens, Some(Mode::LTP))
+    .await
+    .unwrap();
+  let mut expected = tokens.to_vec();
+  expected.sort_unstable();
+  let subscribed = || {
+    let mut all: Vec<u32> = server
+      .open_clients()
+      .into_iter()
+      .flat_map(|c| server.subscribed(c))
+      .collect();
+    all.sort_unstable();
+    all
+  };
+  server.wait_until(|| subscribed() == expected).await;
+}
+
+#[tokio::test]
+async fn one_failed_connection_fails_a_strict_start() {
+  let server = MockServer::start().await;
+  server.reject_next(1, 500);
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(3))
+    .build();
+  assert!(manager.start().await.is_err());
+}
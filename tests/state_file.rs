@@ -0,0 +1,95 @@
+//! Subscriptions survive a manager restart through the state file
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::MockServer;
+use kiteticker_async_manager::{
+  KiteTickerManagerBuilder, Mode, SubscriptionState,
+};
+
+fn state_path(name: &str) -> PathBuf {
+  let path = std::env::temp_dir().join(format!(
+    "kiteticker-state-{}-{}.json",
+    name,
+    std::process::id()
+  ));
+  let _ = std::fs::remove_file(&path);
+  path
+}
+
+fn state(entries: &[(u32, Mode)]) -> SubscriptionState {
+  SubscriptionState::from_map(&entries.iter().copied().collect())
+}
+
+#[tokio::test]
+async fn start_restores_subscriptions_from_state_file() {
+  let server = MockServer::start().await;
+  let path = state_path("restore");
+  let saved = state(&[(408065, Mode::Full), (884737, Mode::LTP)]);
+  saved.save(&path).await.unwrap();
+
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .state_file(&path)
+    .build();
+  manager.start().await.unwrap();
+
+  assert_eq!(manager.get_subscriptions().len(), 2);
+  assert_eq!(manager.get_subscriptions()[&408065], Mode::Full);
+  assert_eq!(manager.get_subscriptions()[&884737], Mode::LTP);
+  server
+    .wait_until(|| server.subscribed(0) == [408065, 884737])
+    .await;
+  let full = server.wait_for_command(|c| c.mode() == Some("full")).await;
+  assert_eq!(full.tokens(), [408065]);
+
+  // Restoring rewrites the same set, not a subset of it
+  assert_eq!(SubscriptionState::load(&path).await, saved);
+  manager.stop().await.unwrap();
+  let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn state_file_tracks_later_changes() {
+  let server = MockServer::start().await;
+  let path = state_path("track");
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .state_file(&path)
+    .build();
+  manager.start().await.unwrap();
+
+  manager
+    .subscribe_symbols(&[408065, 884737], Some(Mode::Quote))
+    .await
+    .unwrap();
+  manager.unsubscribe_symbols(&[884737]).await.unwrap();
+  manager.change_mode(&[408065], Mode::Full).await.unwrap();
+
+  assert_eq!(
+    SubscriptionState::load(&path).await,
+    state(&[(408065, Mode::Full)])
+  );
+  manager.stop().await.unwrap();
+  let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn corrupt_state_file_starts_empty() {
+  let server = MockServer::start().await;
+  let path = state_path("corrupt");
+  std::fs::write(&path, b"{ not json").unwrap();
+
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .state_file(&path)
+    .build();
+  manager.start().await.unwrap();
+
+  assert!(manager.get_subscriptions().is_empty());
+  assert!(server.commands().is_empty());
+  manager.stop().await.unwrap();
+  let _ = std::fs::remove_file(&path);
+}
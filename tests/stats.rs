@@ -0,0 +1,151 @@
+//! Connection and manager statistics
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  KiteTickerManager, KiteTickerManagerBuilder, ManagerStats, Mode,
+};
+
+async fn started(server: &MockServer, connections: usize) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(connections))
+    .build();
+  manager.start().await.unwrap();
+  manager
+}
+
+/// Poll `get_stats` until `ready` accepts them
+async fn stats_when(
+  manager: &KiteTickerManager,
+  ready: impl Fn(&ManagerStats) -> bool,
+) -> ManagerStats {
+  tokio::time::timeout(WAIT, async {
+    loop {
+      let stats = manager.get_stats().await.unwrap();
+      if ready(&stats) {
+        return stats;
+      }
+      tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+  })
+  .await
+  .expect("stats never matched")
+}
+
+#[tokio::test]
+async fn parsed_and_rejected_packets_are_counted_apart() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 1).await;
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  let ltp = fixtures::nse_equity().ltp();
+  // Two ticks around a packet of unknown length
+  server
+    .send_frame(0, fixtures::frame(&[ltp.clone(), vec![7; 12], ltp.clone()]));
+  // A tick, then a packet cut short
+  let torn = fixtures::frame(&[ltp.clone(), fixtures::nfo_future().quote()]);
+  server.send_frame(0, fixtures::truncated(torn, 10));
+  // Stats are flushed once a second, on the next message
+  tokio::time::sleep(Duration::from_millis(1_100)).await;
+  server.send_frame(0, fixtures::frame(&[ltp]));
+
+  let stats = stats_when(&manager, |s| s.total_messages_parsed == 4).await;
+  assert_eq!(stats.total_parse_errors, 2);
+  assert_eq!(stats.connection_stats[0].messages_parsed, 4);
+  assert_eq!(stats.connection_stats[0].parse_errors, 2);
+}
+
+#[tokio::test]
+async fn stats_and_health_work_without_periodic_checks() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(2);
+  config.health_check_interval = Duration::ZERO;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+
+  let stats = manager.get_stats().await.unwrap();
+  assert_eq!(stats.active_connections, 2);
+  assert_eq!(stats.total_symbols, 1);
+  let health = manager.get_health().await.unwrap();
+  assert_eq!(health.healthy_connections, 2);
+}
+
+#[tokio::test]
+async fn parse_failures_are_kept_as_the_last_error() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 1).await;
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+  let ltp = fixtures::nse_equity().ltp();
+  server.send_frame(0, fixtures::frame(std::slice::from_ref(&ltp)));
+  tokio::time::sleep(Duration::from_millis(1_100)).await;
+  server.send_frame(0, fixtures::frame(std::slice::from_ref(&ltp)));
+  let stats =
+    stats_when(&manager, |s| s.connection_stats[0].messages_parsed == 2).await;
+  assert!(stats.connection_stats[0].last_error.is_none());
+
+  let before = Instant::now();
+  let torn = fixtures::frame(&[ltp.clone(), fixtures::nfo_future().quote()]);
+  server.send_frame(0, fixtures::truncated(torn, 10));
+  tokio::time::sleep(Duration::from_millis(1_100)).await;
+  server.send_frame(0, fixtures::frame(&[ltp]));
+
+  let stats =
+    stats_when(&manager, |s| s.connection_stats[0].last_error.is_some()).await;
+  let (at, message) = stats.connection_stats[0].last_error.clone().unwrap();
+  assert_eq!(message, "1 tick packet(s) failed to parse");
+  assert!(at >= before && at <= Instant::now());
+  let json = serde_json::to_value(&stats.connection_stats[0]).unwrap();
+  assert_eq!(json["last_error"]["message"], message);
+  assert!(json["last_error"]["age_secs"].as_f64().unwrap() >= 0.0);
+}
+
+#[tokio::test]
+async fn subscription_operations_are_timed() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+  let latency = manager.get_stats().await.unwrap().subscription_latency;
+  assert_eq!(latency.subscribe.count, 0);
+
+  let tokens = [
+    fixtures::NSE_EQUITY,
+    fixtures::NFO_FUTURE,
+    fixtures::BSE_EQUITY,
+  ];
+  for &token in &tokens {
+    manager
+      .subscribe_symbols(&[token], Some(Mode::LTP))
+      .await
+      .unwrap();
+  }
+  manager.change_mode(&tokens, Mode::Full).await.unwrap();
+  manager.unsubscribe_symbols(&tokens[..2]).await.unwrap();
+
+  let latency = manager.get_stats().await.unwrap().subscription_latency;
+  assert_eq!(latency.subscribe.count, 3);
+  assert_eq!(latency.change_mode.count, 1);
+  assert_eq!(latency.unsubscribe.count, 1);
+  for op in [latency.subscribe, latency.change_mode, latency.unsubscribe] {
+    assert!(op.max > Duration::ZERO, "{:?}", op);
+    assert!(op.avg > Duration::ZERO && op.avg <= op.max, "{:?}", op);
+  }
+}
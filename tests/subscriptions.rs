@@ -0,0 +1,316 @@
+//! Subscription bookkeeping: where tokens land and what the getters report
+
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManager, KiteTickerManagerBuilder, Mode,
+};
+
+async fn started(server: &MockServer, connections: usize) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(connections))
+    .build();
+  manager.start().await.unwrap();
+  manager
+}
+
+/// The distribution turned around: token -> connection
+fn assignments(manager: &KiteTickerManager) -> HashMap<u32, ChannelId> {
+  manager
+    .get_symbol_distribution()
+    .into_iter()
+    .flat_map(|(channel, tokens)| tokens.into_iter().map(move |t| (t, channel)))
+    .collect()
+}
+
+#[tokio::test]
+async fn assigned_channels_match_the_distribution() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 3).await;
+  let tokens = [
+    fixtures::NSE_EQUITY,
+    fixtures::NFO_FUTURE,
+    fixtures::BSE_EQUITY,
+    fixtures::MCX_FUTURE,
+    fixtures::NIFTY_50,
+  ];
+
+  let assigned = manager
+    .subscribe_symbols_assigned(&tokens, Some(Mode::LTP))
+    .await
+    .unwrap();
+  assert_eq!(assigned.len(), tokens.len());
+  assert_eq!(assigned, assignments(&manager));
+  // Spread over every connection
+  let mut channels: Vec<_> = assigned.values().map(|c| c.to_index()).collect();
+  channels.sort_unstable();
+  channels.dedup();
+  assert_eq!(channels, [0, 1, 2]);
+
+  // Tokens already subscribed are left out
+  let again = manager
+    .subscribe_symbols_assigned(
+      &[fixtures::NSE_EQUITY, 884_737],
+      Some(Mode::LTP),
+    )
+    .await
+    .unwrap();
+  assert_eq!(again.keys().collect::<Vec<_>>(), [&884_737]);
+  assert_eq!(again[&884_737], assignments(&manager)[&884_737]);
+}
+
+#[tokio::test]
+async fn connection_with_nothing_subscribed_stays_up() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(1);
+  config.receive_timeout = Duration::from_millis(100);
+  config.heartbeat_liveness_threshold = Duration::from_secs(1);
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  manager
+    .unsubscribe_symbols(&[fixtures::NSE_EQUITY])
+    .await
+    .unwrap();
+
+  // Well past the liveness threshold without a single frame
+  tokio::time::sleep(Duration::from_millis(2_500)).await;
+  let stats = manager.get_stats().await.unwrap();
+  assert!(stats.connection_stats[0].is_connected);
+  assert_eq!(stats.connection_stats[0].symbol_count, 0);
+  assert_eq!(server.open_clients(), [0]);
+  assert_eq!(server.client_count(), 1);
+}
+
+#[tokio::test]
+async fn expiry_unsubscribes_only_old_tokens() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 1).await;
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  let first = manager.get_subscription_age(fixtures::NSE_EQUITY).unwrap();
+  tokio::time::sleep(Duration::from_millis(300)).await;
+  let later = manager.get_subscription_age(fixtures::NSE_EQUITY).unwrap();
+  assert!(later >= first + Duration::from_millis(300));
+  assert_eq!(manager.get_subscription_age(fixtures::NFO_FUTURE), None);
+
+  manager
+    .subscribe_symbols(&[fixtures::NFO_FUTURE], Some(Mode::LTP))
+    .await
+    .unwrap();
+  let expired = manager
+    .expire_older_than(Duration::from_millis(200))
+    .await
+    .unwrap();
+  assert_eq!(expired, [fixtures::NSE_EQUITY]);
+  assert_eq!(manager.get_subscription_age(fixtures::NSE_EQUITY), None);
+  assert!(manager.get_subscription_age(fixtures::NFO_FUTURE).is_some());
+  let unsubscribe = server
+    .wait_for_command(|c| c.action() == "unsubscribe")
+    .await;
+  assert_eq!(unsubscribe.tokens(), [fixtures::NSE_EQUITY]);
+  assert_eq!(server.subscribed(0), [fixtures::NFO_FUTURE]);
+}
+
+#[tokio::test]
+async fn subscriptions_before_start_are_applied_on_start() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(2))
+    .build();
+  manager
+    .subscribe_symbols(
+      &[
+        fixtures::NSE_EQUITY,
+        fixtures::NFO_FUTURE,
+        fixtures::BSE_EQUITY,
+      ],
+      Some(Mode::Quote),
+    )
+    .await
+    .unwrap();
+  manager
+    .unsubscribe_symbols(&[fixtures::BSE_EQUITY])
+    .await
+    .unwrap();
+  // Nothing is sent while there are no connections
+  assert_eq!(server.client_count(), 0);
+  assert!(manager.get_symbol_distribution().is_empty());
+
+  manager.start().await.unwrap();
+  let distribution = assignments(&manager);
+  assert_eq!(distribution.len(), 2);
+  assert_ne!(
+    distribution[&fixtures::NSE_EQUITY],
+    distribution[&fixtures::NFO_FUTURE]
+  );
+  server
+    .wait_until(|| {
+      let mut all: Vec<u32> =
+        (0..2).flat_map(|c| server.subscribed(c)).collect();
+      all.sort_unstable();
+      all == [fixtures::NSE_EQUITY, fixtures::NFO_FUTURE]
+    })
+    .await;
+  assert!(server
+    .commands()
+    .iter()
+    .filter(|c| c.action() == "mode")
+    .all(|c| c.mode() == Some("quote")));
+}
+
+#[tokio::test]
+async fn tokens_outside_the_allowlist_are_dropped() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .allowed_tokens([fixtures::NSE_EQUITY, fixtures::NIFTY_50])
+    .build();
+  manager.start().await.unwrap();
+
+  let assigned = manager
+    .subscribe_symbols_assigned(
+      &[
+        fixtures::NSE_EQUITY,
+        fixtures::NFO_FUTURE,
+        fixtures::NIFTY_50,
+      ],
+      Some(Mode::LTP),
+    )
+    .await
+    .unwrap();
+  let mut tokens: Vec<u32> = assigned.into_keys().collect();
+  tokens.sort_unstable();
+  assert_eq!(tokens, [fixtures::NIFTY_50, fixtures::NSE_EQUITY]);
+  assert!(!manager.is_subscribed(fixtures::NFO_FUTURE));
+
+  // Only disallowed tokens: nothing to send
+  manager
+    .subscribe_symbols(&[fixtures::BSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server
+    .wait_until(|| {
+      server.subscribed(0) == [fixtures::NIFTY_50, fixtures::NSE_EQUITY]
+    })
+    .await;
+  assert!(server
+    .commands()
+    .iter()
+    .all(|c| !c.tokens().contains(&fixtures::NFO_FUTURE)
+      && !c.tokens().contains(&fixtures::BSE_EQUITY)));
+  assert_eq!(manager.subscribed_count(), 2);
+}
+
+/// Distribution, per-connection state and the server all agree
+async fn assert_consistent(server: &MockServer, manager: &KiteTickerManager) {
+  let distribution = manager.get_symbol_distribution();
+  let stats = manager.get_stats().await.unwrap();
+  for connection in &stats.connection_stats {
+    let channel = ChannelId::from_index(connection.connection_id).unwrap();
+    let mapped = distribution.get(&channel).map_or(0, Vec::len);
+    assert_eq!(connection.symbol_count, mapped, "{channel:?}");
+  }
+  let mut expected: Vec<u32> = distribution.into_values().flatten().collect();
+  expected.sort_unstable();
+  assert_eq!(manager.subscribed_count(), expected.len());
+  server
+    .wait_until(|| {
+      let mut all: Vec<u32> =
+        (0..3).flat_map(|c| server.subscribed(c)).collect();
+      all.sort_unstable();
+      all == expected
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn interleaved_changes_keep_every_view_consistent() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 3).await;
+  let first = [
+    fixtures::NSE_EQUITY,
+    fixtures::NFO_FUTURE,
+    fixtures::NIFTY_50,
+  ];
+  let second = [fixtures::BSE_EQUITY, fixtures::MCX_FUTURE, 884_737];
+
+  manager
+    .subscribe_symbols(&first, Some(Mode::LTP))
+    .await
+    .unwrap();
+  assert_consistent(&server, &manager).await;
+  manager
+    .unsubscribe_symbols(&[fixtures::NFO_FUTURE])
+    .await
+    .unwrap();
+  manager
+    .subscribe_symbols(&second, Some(Mode::Quote))
+    .await
+    .unwrap();
+  assert_consistent(&server, &manager).await;
+  // Back again, wherever it lands, alongside tokens that are not subscribed
+  manager
+    .unsubscribe_symbols(&[fixtures::NSE_EQUITY, fixtures::BSE_EQUITY, 1])
+    .await
+    .unwrap();
+  manager
+    .subscribe_symbols(&[fixtures::NFO_FUTURE, fixtures::NSE_EQUITY], None)
+    .await
+    .unwrap();
+  assert_consistent(&server, &manager).await;
+  manager.unsubscribe_symbols(&second).await.unwrap();
+  manager.unsubscribe_symbols(&first).await.unwrap();
+  assert_consistent(&server, &manager).await;
+  assert_eq!(manager.subscribed_count(), 0);
+}
+
+#[tokio::test]
+async fn membership_and_count_follow_subscriptions() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server, 2).await;
+  assert!(!manager.is_subscribed(fixtures::NSE_EQUITY));
+  assert_eq!(manager.subscribed_count(), 0);
+
+  manager
+    .subscribe_symbols(
+      &[
+        fixtures::NSE_EQUITY,
+        fixtures::NFO_FUTURE,
+        fixtures::NIFTY_50,
+      ],
+      Some(Mode::LTP),
+    )
+    .await
+    .unwrap();
+  assert!(manager.is_subscribed(fixtures::NSE_EQUITY));
+  assert!(manager.is_subscribed(fixtures::NIFTY_50));
+  assert!(!manager.is_subscribed(fixtures::BSE_EQUITY));
+  assert_eq!(manager.subscribed_count(), 3);
+
+  // Subscribing again does not count twice
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::Full))
+    .await
+    .unwrap();
+  assert_eq!(manager.subscribed_count(), 3);
+
+  manager
+    .unsubscribe_symbols(&[fixtures::NSE_EQUITY, fixtures::BSE_EQUITY])
+    .await
+    .unwrap();
+  assert!(!manager.is_subscribed(fixtures::NSE_EQUITY));
+  assert!(manager.is_subscribed(fixtures::NFO_FUTURE));
+  assert_eq!(manager.subscribed_count(), 2);
+}
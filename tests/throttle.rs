@@ -0,0 +1,101 @@
+//! Throttling is recognised from the handshake status and close code only
+
+mod common;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  ChannelId, KiteTickerManager, KiteTickerManagerBuilder, Mode, TickerMessage,
+};
+use tokio::sync::broadcast;
+
+async fn started(server: &MockServer) -> KiteTickerManager {
+  let mut config = server.manager_config(1);
+  // One attempt per reconnect, so only throttling can open the breaker
+  config.max_reconnect_attempts = 1;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  // The connection only reads its socket once it has a subscription
+  manager
+    .subscribe_symbols(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  manager
+}
+
+/// First `Throttled` or `ClosingMessage` on the channel
+async fn close_notice(
+  receiver: &mut broadcast::Receiver<TickerMessage>,
+) -> TickerMessage {
+  tokio::time::timeout(WAIT, async {
+    loop {
+      let message = receiver.recv().await.unwrap();
+      if matches!(
+        message,
+        TickerMessage::Throttled(_) | TickerMessage::ClosingMessage(_)
+      ) {
+        return message;
+      }
+    }
+  })
+  .await
+  .expect("no close notice within the wait")
+}
+
+#[tokio::test]
+async fn only_try_again_later_closes_are_throttling() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+
+  // Policy violation is not a rate limit, whatever the reason says
+  server.close_client(0, 1008, "too many requests");
+  let notice = close_notice(&mut receiver).await;
+  assert_eq!(notice.close_info().map(|c| c.code), Some(1008));
+
+  manager.reconnect(ChannelId::Connection1).await.unwrap();
+  server.close_client(1, 1013, "");
+  assert!(matches!(
+    close_notice(&mut receiver).await,
+    TickerMessage::Throttled(_)
+  ));
+}
+
+#[tokio::test]
+async fn refused_handshake_status_decides_throttling() {
+  let server = MockServer::start().await;
+  let mut manager = started(&server).await;
+  let mut receiver = manager.get_channel(ChannelId::Connection1).unwrap();
+  server.close_client(0, 1000, "");
+  close_notice(&mut receiver).await;
+
+  // Any other refusal is an ordinary failed attempt
+  server.reject_with(Some(503));
+  let err = manager
+    .reconnect(ChannelId::Connection1)
+    .await
+    .unwrap_err()
+    .to_string();
+  assert!(!err.contains("throttled"), "{}", err);
+
+  server.reject_with(Some(429));
+  let err = manager
+    .reconnect(ChannelId::Connection1)
+    .await
+    .unwrap_err()
+    .to_string();
+  assert!(err.contains("throttled"), "{}", err);
+  assert!(matches!(
+    close_notice(&mut receiver).await,
+    TickerMessage::Throttled(_)
+  ));
+  // The breaker now refuses attempts for the throttle cooldown
+  server.reject_with(None);
+  let err = manager
+    .reconnect(ChannelId::Connection1)
+    .await
+    .unwrap_err()
+    .to_string();
+  assert!(err.contains("circuit breaker open"), "{}", err);
+}
@@ -0,0 +1,347 @@
+//! Single-connection client against the mock server
+
+mod common;
+
+use std::time::Duration;
+
+use common::{fixtures, MockServer, WAIT};
+use kiteticker_async_manager::{
+  KiteTickerAsync, KiteTickerConfig, Mode, TickerMessage,
+};
+
+fn config(server: &MockServer) -> KiteTickerConfig {
+  let mut config = KiteTickerConfig::default();
+  config.environment = server.environment();
+  config
+}
+
+#[tokio::test]
+async fn connect_with_config_reaches_the_configured_endpoint() {
+  let server = MockServer::start().await;
+  let mut ticker =
+    KiteTickerAsync::connect_with_config("my_key", "token", config(&server))
+      .await
+      .unwrap();
+  assert!(ticker.is_connected());
+
+  ticker.subscribe(&[408065], Some(Mode::Full)).await.unwrap();
+  let mode = server.wait_for_command(|c| c.action() == "mode").await;
+  assert_eq!((mode.mode(), mode.tokens()), (Some("full"), vec![408065]));
+  assert_eq!(server.client_keys(), ["my_key"]);
+  assert_eq!(server.subscribed(0), [408065]);
+}
+
+#[tokio::test]
+async fn resume_wakes_a_paused_next_message() {
+  let server = MockServer::start().await;
+  let mut ticker =
+    KiteTickerAsync::connect_with_config("key", "token", config(&server))
+      .await
+      .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  let handle = subscriber.pause_handle();
+  handle.pause();
+  let reader = tokio::spawn(async move {
+    loop {
+      match subscriber.next_message().await.unwrap() {
+        Some(TickerMessage::Ticks(ticks)) => return ticks,
+        Some(_) => {}
+        None => panic!("stream closed"),
+      }
+    }
+  });
+
+  // Sent while paused: discarded, and the reader keeps waiting
+  let mut paused = fixtures::nse_equity();
+  paused.last_price = 10_000;
+  server.send_frame(0, fixtures::frame(&[paused.ltp()]));
+  tokio::time::sleep(Duration::from_millis(200)).await;
+  assert!(!reader.is_finished());
+
+  handle.resume();
+  assert!(!handle.is_paused());
+  let live = fixtures::nse_equity();
+  server.send_frame(0, fixtures::frame(&[live.ltp()]));
+  let ticks = tokio::time::timeout(WAIT, reader)
+    .await
+    .expect("next_message stayed paused")
+    .unwrap();
+  assert_eq!(ticks.len(), 1);
+  assert_eq!(ticks[0].content.last_price, Some(1520.35));
+}
+
+#[tokio::test]
+async fn subscriber_send_failures_are_returned_and_rolled_back() {
+  let server = MockServer::start().await;
+  let mut ticker =
+    KiteTickerAsync::connect_with_config("key", "token", config(&server))
+      .await
+      .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  // Once the socket is gone the writer stops and further commands fail
+  server.close_client(0, 1000, "session closed");
+  let mut failed = None;
+  for token in 884737..884757 {
+    if subscriber
+      .subscribe(&[token], Some(Mode::Full))
+      .await
+      .is_err()
+    {
+      failed = Some(token);
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(25)).await;
+  }
+  let failed = failed.expect("commands kept succeeding on a closed socket");
+  assert!(!subscriber.get_subscribed().contains(&failed));
+
+  assert!(subscriber
+    .set_mode(&[fixtures::NSE_EQUITY], Mode::Full)
+    .await
+    .is_err());
+  assert!(subscriber
+    .unsubscribe(&[fixtures::NSE_EQUITY])
+    .await
+    .is_err());
+  assert!(subscriber.get_subscribed().contains(&fixtures::NSE_EQUITY));
+}
+
+#[tokio::test]
+async fn unknown_packets_follow_the_ticks_of_their_frame() {
+  let server = MockServer::start().await;
+  let mut config = config(&server);
+  config.emit_unknown_packets = true;
+  let mut ticker = KiteTickerAsync::connect_with_config("key", "token", config)
+    .await
+    .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  // An unknown 12-byte layout between two known packets
+  let raw = fixtures::nse_equity();
+  let unknown = vec![7u8; 12];
+  server
+    .send_frame(0, fixtures::frame(&[raw.ltp(), unknown.clone(), raw.ltp()]));
+
+  let mut received = Vec::new();
+  tokio::time::timeout(WAIT, async {
+    while received.len() < 2 {
+      match subscriber.next_message().await.unwrap() {
+        Some(TickerMessage::Ticks(ticks)) => received.push(Ok(ticks.len())),
+        Some(TickerMessage::UnknownPacket { size, bytes }) => {
+          received.push(Err((size, bytes.to_vec())))
+        }
+        Some(_) => {}
+        None => panic!("stream closed"),
+      }
+    }
+  })
+  .await
+  .expect("frame not delivered");
+  assert_eq!(received, [Ok(2), Err((12, unknown))]);
+}
+
+#[tokio::test]
+async fn close_returns_the_servers_close_frame() {
+  let server = MockServer::start().await;
+  let mut ticker =
+    KiteTickerAsync::connect_with_config("key", "token", config(&server))
+      .await
+      .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  server.close_client(0, 1001, "going away");
+  let closing = tokio::time::timeout(WAIT, async {
+    loop {
+      match subscriber.next_message().await.unwrap() {
+        Some(message @ TickerMessage::ClosingMessage(_)) => return message,
+        Some(_) => {}
+        None => panic!("stream closed without a closing message"),
+      }
+    }
+  })
+  .await
+  .expect("no closing message");
+  let info = closing.close_info().unwrap();
+  assert_eq!((info.code, info.reason.as_str()), (1001, "going away"));
+
+  let frame = tokio::time::timeout(WAIT, ticker.close())
+    .await
+    .expect("close did not finish")
+    .unwrap()
+    .expect("no close frame");
+  assert_eq!(u16::from(frame.code), 1001);
+  assert_eq!(frame.reason.as_str(), "going away");
+  assert!(!ticker.is_connected());
+}
+
+#[tokio::test]
+async fn collect_ticks_stops_at_n_or_the_timeout() {
+  let server = MockServer::start().await;
+  let mut ticker =
+    KiteTickerAsync::connect_with_config("key", "token", config(&server))
+      .await
+      .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  // Fast feed: three frames of two ticks, cut off at four
+  let frame = fixtures::frame(&[
+    fixtures::nse_equity().ltp(),
+    fixtures::nse_equity().ltp(),
+  ]);
+  for _ in 0..3 {
+    server.send_frame(0, frame.clone());
+  }
+  let ticks = subscriber.collect_ticks(4, WAIT).await.unwrap();
+  assert_eq!(ticks.len(), 4);
+  assert!(ticks
+    .iter()
+    .all(|t| t.instrument_token == fixtures::NSE_EQUITY));
+
+  // Slow feed: the third frame plus one more tick, short of five
+  server.send_frame(0, fixtures::frame(&[fixtures::nse_equity().ltp()]));
+  let started = std::time::Instant::now();
+  let ticks = subscriber
+    .collect_ticks(5, Duration::from_millis(300))
+    .await
+    .unwrap();
+  assert_eq!(ticks.len(), 3);
+  assert!(started.elapsed() >= Duration::from_millis(300));
+}
+
+#[tokio::test]
+async fn commands_reach_the_server_with_and_without_a_writer_task() {
+  for direct_writes in [false, true] {
+    let server = MockServer::start().await;
+    let mut config = config(&server);
+    config.direct_writes = direct_writes;
+    let mut ticker =
+      KiteTickerAsync::connect_with_config("key", "token", config)
+        .await
+        .unwrap();
+    let mut subscriber = ticker
+      .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::Quote))
+      .await
+      .unwrap();
+    subscriber
+      .subscribe(&[fixtures::NFO_FUTURE], Some(Mode::Full))
+      .await
+      .unwrap();
+    subscriber
+      .set_mode(&[fixtures::NSE_EQUITY], Mode::LTP)
+      .await
+      .unwrap();
+    subscriber
+      .unsubscribe(&[fixtures::NFO_FUTURE])
+      .await
+      .unwrap();
+
+    server.wait_until(|| server.commands().len() == 6).await;
+    let sent: Vec<_> = server
+      .commands()
+      .iter()
+      .map(|c| (c.action().to_string(), c.mode().map(str::to_string)))
+      .collect();
+    let expected = [
+      ("subscribe", None),
+      ("mode", Some("quote")),
+      ("subscribe", None),
+      ("mode", Some("full")),
+      ("mode", Some("ltp")),
+      ("unsubscribe", None),
+    ]
+    .map(|(a, m)| (a.to_string(), m.map(str::to_string)));
+    assert_eq!(sent, expected, "direct_writes: {direct_writes}");
+    assert_eq!(server.subscribed(0), [fixtures::NSE_EQUITY]);
+  }
+}
+
+/// Messages received over the next half second
+async fn messages_for_a_while(
+  subscriber: &mut kiteticker_async_manager::KiteTickerSubscriber,
+) -> Vec<TickerMessage> {
+  let mut messages = Vec::new();
+  let _ = tokio::time::timeout(Duration::from_millis(500), async {
+    while let Some(message) = subscriber.next_message().await.unwrap() {
+      messages.push(message);
+    }
+  })
+  .await;
+  messages
+}
+
+fn expiry_hints(messages: &[TickerMessage]) -> Vec<&str> {
+  messages
+    .iter()
+    .filter_map(|m| match m {
+      TickerMessage::Error(e) if e.contains("access token") => Some(e.as_str()),
+      _ => None,
+    })
+    .collect()
+}
+
+#[tokio::test]
+async fn close_right_after_the_handshake_suggests_an_expired_token() {
+  let server = MockServer::start().await;
+  let mut ticker =
+    KiteTickerAsync::connect_with_config("key", "token", config(&server))
+      .await
+      .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  // Heartbeats alone do not prove the session works
+  server.send_frame(0, fixtures::HEARTBEAT.to_vec());
+  server.close_client(0, 1000, "");
+  let messages = messages_for_a_while(&mut subscriber).await;
+  let hints = expiry_hints(&messages);
+  assert_eq!(hints.len(), 1, "{messages:?}");
+  assert!(hints[0].contains("expired"), "{}", hints[0]);
+}
+
+#[tokio::test]
+async fn close_after_data_is_not_blamed_on_the_token() {
+  let server = MockServer::start().await;
+  let mut ticker =
+    KiteTickerAsync::connect_with_config("key", "token", config(&server))
+      .await
+      .unwrap();
+  let mut subscriber = ticker
+    .subscribe(&[fixtures::NSE_EQUITY], Some(Mode::LTP))
+    .await
+    .unwrap();
+  server.wait_for_command(|c| c.action() == "mode").await;
+
+  server.send_frame(0, fixtures::frame(&[fixtures::nse_equity().ltp()]));
+  server.close_client(0, 1000, "");
+  let messages = messages_for_a_while(&mut subscriber).await;
+  assert!(messages
+    .iter()
+    .any(|m| matches!(m, TickerMessage::Ticks(_))));
+  assert!(messages.iter().any(|m| m.close_info().is_some()));
+  assert!(expiry_hints(&messages).is_empty(), "{messages:?}");
+}
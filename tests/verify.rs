@@ -0,0 +1,55 @@
+//! `verify_connections` as a startup health gate
+
+mod common;
+
+use std::time::Duration;
+
+use common::{fixtures, MockServer};
+use kiteticker_async_manager::{
+  ChannelId, KiteError, KiteTickerManager, KiteTickerManagerBuilder,
+};
+
+async fn started(server: &MockServer) -> KiteTickerManager {
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(2))
+    .build();
+  manager.start().await.unwrap();
+  server.wait_for_clients(2).await;
+  manager
+}
+
+#[tokio::test]
+async fn silent_connection_is_reported() {
+  let server = MockServer::start().await;
+  let manager = started(&server).await;
+
+  // Only the first socket sends Kite's heartbeat
+  let heartbeat = async {
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.send_frame(0, fixtures::HEARTBEAT.to_vec());
+  };
+  let (result, ()) = tokio::join!(
+    manager.verify_connections(Duration::from_millis(500)),
+    heartbeat,
+  );
+  assert_eq!(
+    result,
+    Err(KiteError::SilentConnections(vec![ChannelId::Connection2]))
+  );
+}
+
+#[tokio::test]
+async fn connections_with_frames_pass() {
+  let server = MockServer::start().await;
+  let manager = started(&server).await;
+
+  let heartbeat = async {
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.broadcast_frame(fixtures::HEARTBEAT.to_vec());
+  };
+  let (result, ()) = tokio::join!(
+    manager.verify_connections(Duration::from_secs(5)),
+    heartbeat,
+  );
+  assert_eq!(result, Ok(()));
+}
@@ -0,0 +1,84 @@
+//! Watchlist groups stay in step with the manager
+
+mod common;
+
+use std::time::Duration;
+
+use common::MockServer;
+use kiteticker_async_manager::{
+  KiteTickerManagerBuilder, Mode, WatchlistManager,
+};
+
+#[tokio::test]
+async fn overlapping_groups_keep_shared_tokens() {
+  let server = MockServer::start().await;
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(server.manager_config(1))
+    .build();
+  manager.start().await.unwrap();
+  let mut watchlists = WatchlistManager::new(manager);
+  watchlists
+    .add_group("nifty", &[256265, 408065], Mode::Quote)
+    .await
+    .unwrap();
+  watchlists
+    .add_group("movers", &[408065, 738561], Mode::Full)
+    .await
+    .unwrap();
+  assert_eq!(watchlists.ref_count(408065), 2);
+
+  watchlists.remove_group("nifty").await.unwrap();
+  let subscriptions = watchlists.manager().get_subscriptions();
+  assert!(!subscriptions.contains_key(&256265));
+  assert_eq!(subscriptions[&408065], Mode::Full);
+  assert_eq!(subscriptions[&738561], Mode::Full);
+}
+
+#[tokio::test]
+async fn failed_replace_unsubscribes_what_it_added() {
+  let server = MockServer::start().await;
+  let mut config = server.manager_config(2);
+  // The second connection is full after one token, so new tokens go first
+  config.per_connection_symbol_caps = vec![2, 1];
+  let mut manager = KiteTickerManagerBuilder::new("key", "token")
+    .config(config)
+    .build();
+  manager.start().await.unwrap();
+  let mut watchlists = WatchlistManager::new(manager);
+  watchlists
+    .add_group("kept", &[408065], Mode::LTP)
+    .await
+    .unwrap();
+  watchlists
+    .add_group("moved", &[884737], Mode::LTP)
+    .await
+    .unwrap();
+  server.wait_until(|| server.subscribed(1) == [884737]).await;
+
+  // Close the second socket and wait for its writer to give up
+  server.close_client(1, 1000, "maintenance");
+  let mut dead = false;
+  for _ in 0..20 {
+    let manager = watchlists.manager_mut();
+    if manager.change_mode(&[884737], Mode::LTP).await.is_err() {
+      dead = true;
+      break;
+    }
+    tokio::time::sleep(Duration::from_millis(25)).await;
+  }
+  assert!(dead, "commands kept succeeding on a closed socket");
+
+  // Subscribing 738561 works; unsubscribing 884737 then fails
+  assert!(watchlists
+    .replace_group("moved", &[738561], Mode::LTP)
+    .await
+    .is_err());
+  assert_eq!(
+    watchlists.group("moved").unwrap().tokens,
+    [884737].into_iter().collect()
+  );
+  let manager = watchlists.manager();
+  assert!(!manager.is_subscribed(738561));
+  assert!(manager.is_subscribed(884737));
+  server.wait_until(|| server.subscribed(0) == [408065]).await;
+}